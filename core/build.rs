@@ -78,6 +78,7 @@ fn main() {
                 "cmd_get_neoforge_version_list",
                 "cmd_spawn_install_task",
                 "cmd_cancel_install_task",
+                "cmd_export_install_bundle",
             ]),
         )
         .plugin(
@@ -88,6 +89,7 @@ fn main() {
                 "cmd_get_instance_by_id",
                 "cmd_update_instance",
                 "cmd_delete_instance",
+                "cmd_duplicate_instance",
                 "cmd_add_background_file",
                 "cmd_get_background_path",
                 "cmd_calculate_playtime",
@@ -96,7 +98,26 @@ fn main() {
         )
         .plugin(
             "launch",
-            InlinedPlugin::new().commands(&["cmd_spawn_launch_task", "cmd_cancel_launch_task"]),
+            InlinedPlugin::new().commands(&[
+                "cmd_spawn_launch_task",
+                "cmd_cancel_launch_task",
+                "cmd_list_running_games",
+                "cmd_kill_game",
+                "cmd_focus_game",
+                "cmd_analyze_crash",
+                "cmd_repair_instance",
+                "cmd_start_profiling",
+                "cmd_stop_profiling",
+                "cmd_list_profile_reports",
+                "cmd_get_game_logs",
+                "cmd_subscribe_game_logs",
+                "cmd_unsubscribe_game_logs",
+                "cmd_export_launch_script",
+                "cmd_plan_launch",
+                "cmd_list_jvm_presets",
+                "cmd_import_jvm_preset",
+                "cmd_detect_performance_tools",
+            ]),
         )
         .plugin(
             "multiplayer",
@@ -151,7 +172,7 @@ fn main() {
         )
         .plugin(
             "platform",
-            InlinedPlugin::new().commands(&["cmd_get_platform_info"]),
+            InlinedPlugin::new().commands(&["cmd_get_platform_info", "cmd_list_gpus"]),
         )
         .plugin(
             "folder",
@@ -17,6 +17,48 @@ export enum Palette {
     Macchiato = "Macchiato",
 }
 
+export type JvmFlag = {
+    value: string
+    min_java?: number
+    max_java?: number
+    /** Java vendors whose builds lack the flag, e.g. "oracle" */
+    excluded_vendors?: string[]
+}
+
+export type JvmPreset = {
+    id: string
+    name: string
+    description?: string
+    flags: JvmFlag[]
+}
+
+export type LinuxPerformance = {
+    gamemode: boolean
+    mangohud: boolean
+    env: Record<string, string>
+    /** Libraries added in front of `LD_PRELOAD`, by path or by name */
+    ld_preload: string[]
+    /** From -20 to 19 */
+    niceness?: number
+}
+
+export type GpuPreference =
+    | { type: "Auto" }
+    | { type: "System" }
+    | { type: "Device"; pci_address: string }
+
+/** Where the LWJGL natives are loaded from on Linux */
+export type NativesMode = "Auto" | "Bundled" | "System"
+
+export type JavaAgent = {
+    name: string
+    source: { type: "Path"; path: string } | { type: "Download"; url: string }
+    /** Required for downloaded agents */
+    sha256?: string
+    /** Passed to the agent after `=` */
+    options: string
+}
+
 export type Config = {
     language?: string
     update_channel: UpdateChannel
@@ -65,6 +107,10 @@ export type Config = {
         width: number
         height: number
         fullscreen: boolean
+        /**
+         * The arguments, wrap command and hooks expand placeholders such as `${instance_dir}`,
+         * `${java_home}` or `${loader}`. Saving fails on an unknown placeholder.
+         */
         extra_jvm_args: string
         extra_mc_args: string
         is_demo: boolean
@@ -72,13 +118,22 @@ export type Config = {
         ignore_patch_discrepancies: boolean
         extra_class_paths: string
         gc: "Serial" | "Parallel" | "G1" | "Z"
+        jvm_preset?: string
+        jvm_presets: JvmPreset[]
         launcher_name: string
         wrap_command: string
+        linux_performance: LinuxPerformance
+        natives_mode: NativesMode
+        java_agents: JavaAgent[]
         execute_before_launch: string
+        execute_after_start: string
         execute_after_launch: string
+        execute_on_crash: string
+        hook_timeout_secs: number
         skip_refresh_account: boolean
         skip_check_files: boolean
         quit_app_after_launch: boolean
+        legacy_proxy: boolean
     }
 }
 
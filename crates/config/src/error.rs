@@ -32,4 +32,8 @@ pub enum Error {
         #[serde_as(as = "serde_with::DisplayFromStr")]
         toml::de::Error,
     ),
+    #[error("Unknown placeholder {0}")]
+    UnknownPlaceholder(String),
+    #[error("Invalid JVM preset {0}")]
+    InvalidJvmPreset(String),
 }
@@ -2,8 +2,12 @@
 // Copyright 2022-2026 ConicMC developers. All rights reserved.
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::{collections::BTreeMap, ops::Range};
+
 use serde::{Deserialize, Serialize};
 
+use crate::error::*;
+
 /// Represents a Minecraft server configuration, the game will enter the
 /// server automatically.
 #[derive(Clone, Serialize, Deserialize)]
@@ -24,6 +28,132 @@ pub enum GC {
     Z,
 }
 
+/// A named set of JVM tuning flags, selected instead of [`GC`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JvmPreset {
+    /// Unique identifier of the preset, e.g. `aikar`.
+    pub id: String,
+
+    /// Display name of the preset.
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    pub flags: Vec<JvmFlag>,
+}
+
+impl JvmPreset {
+    /// Returns why the preset is invalid: an empty id, or a flag that is not a
+    /// single JVM option.
+    pub fn invalid_reason(&self) -> Option<String> {
+        if self.id.trim().is_empty() {
+            return Some("the id is empty".to_string());
+        }
+        self.flags
+            .iter()
+            .find(|flag| !flag.value.starts_with('-') || flag.value.contains(char::is_whitespace))
+            .map(|flag| format!("{:?} is not a JVM option", flag.value))
+    }
+}
+
+/// A JVM flag of a [`JvmPreset`], with the Java runtimes supporting it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JvmFlag {
+    /// The argument passed to the JVM, e.g. `-XX:+UseZGC`.
+    pub value: String,
+
+    /// The first Java major version supporting the flag.
+    #[serde(default)]
+    pub min_java: Option<u32>,
+
+    /// The last Java major version supporting the flag.
+    #[serde(default)]
+    pub max_java: Option<u32>,
+
+    /// Java vendors whose builds lack the flag, e.g. `oracle`.
+    #[serde(default)]
+    pub excluded_vendors: Vec<String>,
+}
+
+/// The GPU running the game on Linux hybrid-graphics systems.
+#[derive(Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "type")]
+pub enum GpuPreference {
+    /// The discrete GPU, when the default renderer is an integrated one.
+    #[default]
+    Auto,
+    /// The default renderer, without any offloading.
+    System,
+    /// The GPU at a PCI address, e.g. `0000:01:00.0`.
+    Device { pci_address: String },
+}
+
+/// Where the game loads GLFW, OpenAL and the other LWJGL natives from on
+/// Linux.
+#[derive(Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum NativesMode {
+    /// The natives of the version, replaced by compatible builds on
+    /// architectures Mojang ships no natives for.
+    #[default]
+    Auto,
+    /// The natives of the version, unchanged.
+    Bundled,
+    /// The GLFW and OpenAL of the system, e.g. for native Wayland. The other
+    /// natives are replaced like [`NativesMode::Auto`] does.
+    System,
+}
+
+/// Tools and process settings wrapping the game on Linux, ignored on other
+/// platforms.
+#[derive(Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LinuxPerformance {
+    /// Runs the game through `gamemoderun` of Feral GameMode.
+    pub gamemode: bool,
+
+    /// Runs the game through `mangohud`.
+    pub mangohud: bool,
+
+    /// Environment variables set for the game.
+    pub env: BTreeMap<String, String>,
+
+    /// Libraries added in front of `LD_PRELOAD`, by path or by name.
+    pub ld_preload: Vec<String>,
+
+    /// Niceness of the game process, from -20 to 19. Negative values need
+    /// the privilege to raise the priority.
+    pub niceness: Option<i32>,
+}
+
+/// A Java agent loaded with `-javaagent`, e.g. a profiler or the spark
+/// agent.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct JavaAgent {
+    /// Display name of the agent.
+    pub name: String,
+
+    pub source: JavaAgentSource,
+
+    /// Expected SHA-256 of the jar, required for downloaded agents.
+    #[serde(default)]
+    pub sha256: Option<String>,
+
+    /// Passed to the agent after `=`.
+    #[serde(default)]
+    pub options: String,
+}
+
+/// Where the jar of a [`JavaAgent`] comes from.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum JavaAgentSource {
+    /// A jar on disk.
+    Path { path: String },
+    /// A jar downloaded to the agents folder of the launcher.
+    Download { url: String },
+}
+
 /// Represents a configuration object for launching a Minecraft instance.
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -49,10 +179,12 @@ pub struct LaunchConfig {
     /// Whether to launch the game in fullscreen mode.
     pub fullscreen: bool,
 
-    /// Additional JVM arguments specified by the user.
+    /// Additional JVM arguments specified by the user, with the placeholders
+    /// of [`PlaceholderScope::JvmArguments`].
     pub extra_jvm_args: String,
 
-    /// Additional Minecraft command-line arguments specified by the user.
+    /// Additional Minecraft command-line arguments specified by the user,
+    /// with the placeholders of [`PlaceholderScope::GameArguments`].
     pub extra_mc_args: String,
 
     /// Whether to launch in demo mode.
@@ -70,18 +202,48 @@ pub struct LaunchConfig {
     /// Selected Java Garbage Collector.
     pub gc: GC,
 
+    /// Id of the selected JVM tuning preset, which replaces the flags of
+    /// [`LaunchConfig::gc`].
+    pub jvm_preset: Option<String>,
+
+    /// JVM tuning presets defined by the user. A preset with the id of a
+    /// built-in preset replaces it.
+    pub jvm_presets: Vec<JvmPreset>,
+
     /// The name of the launcher, passed to the game.
     pub launcher_name: String,
 
     /// A command prefix to wrap around the launch command.
     pub wrap_command: String,
 
+    /// Performance tools and process settings on Linux, applied outside of
+    /// [`LaunchConfig::wrap_command`].
+    pub linux_performance: LinuxPerformance,
+
+    /// Where the LWJGL natives are loaded from on Linux.
+    pub natives_mode: NativesMode,
+
+    /// Java agents loaded by every instance, in order, before the agents of
+    /// the instance.
+    pub java_agents: Vec<JavaAgent>,
+
     /// Script or command to execute before launching the game.
+    ///
+    /// The launch is aborted when it fails.
     pub execute_before_launch: String,
 
+    /// Script or command to execute once the game window is ready.
+    pub execute_after_start: String,
+
     /// Script or command to execute after the game exits.
     pub execute_after_launch: String,
 
+    /// Script or command to execute after the game exits with an error.
+    pub execute_on_crash: String,
+
+    /// Seconds a hook may run before it is killed, `0` disables the timeout.
+    pub hook_timeout_secs: u64,
+
     /// If true, skips refreshing the account before launch.
     pub skip_refresh_account: bool,
 
@@ -89,6 +251,10 @@ pub struct LaunchConfig {
     pub skip_check_files: bool,
 
     pub quit_app_after_launch: bool,
+
+    /// Whether to route versions older than 1.7.6 through the local legacy
+    /// proxy, which restores skins, sounds and server joining.
+    pub legacy_proxy: bool,
 }
 
 impl Default for LaunchConfig {
@@ -107,13 +273,168 @@ impl Default for LaunchConfig {
             ignore_patch_discrepancies: false,
             extra_class_paths: String::new(),
             gc: GC::default(),
+            jvm_preset: None,
+            jvm_presets: Vec::new(),
             launcher_name: "Conic_Launcher".to_string(),
             wrap_command: String::new(),
+            linux_performance: LinuxPerformance::default(),
+            natives_mode: NativesMode::default(),
+            java_agents: Vec::new(),
             execute_after_launch: String::new(),
             execute_before_launch: String::new(),
+            execute_after_start: String::new(),
+            execute_on_crash: String::new(),
+            hook_timeout_secs: 60,
             skip_refresh_account: false,
             skip_check_files: false,
             quit_app_after_launch: false,
+            legacy_proxy: true,
+        }
+    }
+}
+
+impl LaunchConfig {
+    /// Returns [`Error::InvalidJvmPreset`] when a preset of the user is
+    /// invalid, see [`JvmPreset::invalid_reason`].
+    pub fn validate_presets(&self) -> Result<()> {
+        match self.jvm_presets.iter().find_map(|preset| {
+            preset
+                .invalid_reason()
+                .map(|reason| format!("{}: {reason}", preset.name))
+        }) {
+            Some(reason) => Err(Error::InvalidJvmPreset(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns [`Error::UnknownPlaceholder`] when an argument, the wrap
+    /// command or a hook uses a placeholder the launcher does not expand.
+    pub fn validate_placeholders(&self) -> Result<()> {
+        match find_unknown_placeholder([
+            self.extra_jvm_args.as_str(),
+            self.extra_mc_args.as_str(),
+            self.wrap_command.as_str(),
+            self.execute_before_launch.as_str(),
+            self.execute_after_start.as_str(),
+            self.execute_after_launch.as_str(),
+            self.execute_on_crash.as_str(),
+        ]) {
+            Some(unknown) => Err(Error::UnknownPlaceholder(unknown)),
+            None => Ok(()),
         }
     }
 }
+
+/// The placeholders expanded in every argument, wrap command and hook set by
+/// the user.
+pub const LAUNCHER_PLACEHOLDERS: [&str; 6] = [
+    "instance_dir",
+    "instance_name",
+    "java_home",
+    "minecraft_version",
+    "loader",
+    "account_name",
+];
+
+/// The placeholders of the JVM arguments of the version JSON.
+pub const JVM_ARGUMENT_PLACEHOLDERS: [&str; 7] = [
+    "natives_directory",
+    "launcher_name",
+    "launcher_version",
+    "classpath",
+    "classpath_separator",
+    "version_name",
+    "library_directory",
+];
+
+/// The placeholders of the game arguments of the version JSON.
+pub const GAME_ARGUMENT_PLACEHOLDERS: [&str; 15] = [
+    "version_name",
+    "version_type",
+    "assets_root",
+    "game_assets",
+    "asset_index",
+    "assets_index_name",
+    "game_directory",
+    "auth_player_name",
+    "auth_uuid",
+    "auth_access_token",
+    "auth_session",
+    "user_properties",
+    "user_type",
+    "resolution_width",
+    "resolution_height",
+];
+
+/// Where a string set by the user is used, which decides the placeholders it
+/// may contain besides [`LAUNCHER_PLACEHOLDERS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderScope {
+    /// Also the placeholders of the version JVM arguments.
+    JvmArguments,
+    /// Also the placeholders of the version game arguments.
+    GameArguments,
+    WrapCommand,
+    /// Other names are left to the shell, e.g. `${HOME}`.
+    Hook,
+}
+
+/// The launch options set by the user that may contain placeholders, in the
+/// order [`find_unknown_placeholder`] takes their values.
+pub const PLACEHOLDER_FIELDS: [(&str, PlaceholderScope); 7] = [
+    ("extra_jvm_args", PlaceholderScope::JvmArguments),
+    ("extra_mc_args", PlaceholderScope::GameArguments),
+    ("wrap_command", PlaceholderScope::WrapCommand),
+    ("execute_before_launch", PlaceholderScope::Hook),
+    ("execute_after_start", PlaceholderScope::Hook),
+    ("execute_after_launch", PlaceholderScope::Hook),
+    ("execute_on_crash", PlaceholderScope::Hook),
+];
+
+impl PlaceholderScope {
+    pub fn allows(self, name: &str) -> bool {
+        LAUNCHER_PLACEHOLDERS.contains(&name)
+            || match self {
+                Self::JvmArguments => JVM_ARGUMENT_PLACEHOLDERS.contains(&name),
+                Self::GameArguments => GAME_ARGUMENT_PLACEHOLDERS.contains(&name),
+                Self::WrapCommand => false,
+                Self::Hook => is_shell_variable(name),
+            }
+    }
+}
+
+/// Returns whether `name` is a valid shell variable name.
+pub fn is_shell_variable(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Returns the `${name}` placeholders of `template`, with their byte range.
+pub fn find_placeholders(template: &str) -> Vec<(Range<usize>, &str)> {
+    let mut placeholders = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = template[offset..].find("${") {
+        let start = offset + start;
+        let Some(length) = template[start + 2..].find('}') else {
+            break;
+        };
+        let end = start + 2 + length + 1;
+        placeholders.push((start..end, &template[start + 2..end - 1]));
+        offset = end;
+    }
+    placeholders
+}
+
+/// Returns the first placeholder of the values of [`PLACEHOLDER_FIELDS`] its
+/// scope does not allow, formatted as `${name} in field`.
+pub fn find_unknown_placeholder(values: [&str; PLACEHOLDER_FIELDS.len()]) -> Option<String> {
+    PLACEHOLDER_FIELDS
+        .iter()
+        .zip(values)
+        .find_map(|((field, scope), template)| {
+            find_placeholders(template)
+                .into_iter()
+                .find(|(_, name)| !scope.allows(name))
+                .map(|(_, name)| format!("${{{name}}} in {field}"))
+        })
+}
@@ -115,6 +115,8 @@ pub fn reset_config() -> Result<Config> {
 
 /// Saves the current configuration to the configuration file.
 pub fn save_config(config: Config) -> Result<()> {
+    config.launch.validate_placeholders()?;
+    config.launch.validate_presets()?;
     let data = toml::to_string_pretty(&config)?;
     let config_file_path = &DATA_LOCATION.config;
     std::fs::write(config_file_path, data)?;
@@ -8,6 +8,7 @@ export type DataLocation = {
     root: string
     accounts: string
     authlib_injector: string
+    agents: string
     instances: string
     cache: string
     logs: string
@@ -94,6 +94,8 @@ pub struct DataLocation {
     pub root: PathBuf,
     pub accounts: PathBuf,
     pub authlib_injector: PathBuf,
+    /// Downloaded Java agents.
+    pub agents: PathBuf,
     pub instances: PathBuf,
     pub cache: PathBuf,
     pub logs: PathBuf,
@@ -113,6 +115,7 @@ impl DataLocation {
         Self {
             accounts: data_folder_root.join("accounts"),
             authlib_injector: data_folder_root.join("authlib-injector.jar"),
+            agents: data_folder_root.join("agents"),
             instances: data_folder_root.join("instances"),
             runtime: data_folder_root.join("runtime"),
             music: data_folder_root.join("music"),
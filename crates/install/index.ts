@@ -159,6 +159,9 @@ export enum InstallErrorKind {
     NoAvailableForgeVersion = "NoAvailableForgeVersion ",
     InvalidAuthlibResponse = "InvalidAuthlibResponse",
     ChunkLengthMismatch = "ChunkLengthMismatch",
+    InvalidBundle = "InvalidBundle",
+    BundleMismatch = "BundleMismatch",
+    BundleFileMissing = "BundleFileMissing",
 }
 
 export enum Job {
@@ -190,10 +193,20 @@ export class InstallTask {
     protected _callbacks?: {
         onProgress?: (progress: InstallProgress) => void
     }
-    constructor(config: Config, instance: Instance, callbacks?: typeof this._callbacks) {
+    protected _bundle?: string
+    /**
+     * @param bundle Path of an offline install bundle to install from instead of downloading.
+     */
+    constructor(
+        config: Config,
+        instance: Instance,
+        callbacks?: typeof this._callbacks,
+        bundle?: string,
+    ) {
         this._config = config
         this._instance = instance
         this._callbacks = callbacks
+        this._bundle = bundle
     }
     async start() {
         const channel = new Channel<InstallProgress>()
@@ -203,6 +216,7 @@ export class InstallTask {
         await invoke("plugin:install|cmd_spawn_install_task", {
             config: this._config,
             instance: this._instance,
+            bundle: this._bundle,
             channel,
         })
     }
@@ -210,3 +224,32 @@ export class InstallTask {
         await invoke("plugin:install|cmd_cancel_install_task")
     }
 }
+
+export type BundleManifest = {
+    format_version: number
+    minecraft: string
+    version_id: string
+    include_java: boolean
+    files: {
+        path: string
+        sha1: string
+        size: number
+        executable: boolean
+    }[]
+}
+
+/**
+ * Writes every file needed to install the instance into an offline install bundle.
+ * The instance must be installed.
+ */
+export async function exportInstallBundle(
+    instance: Instance,
+    output: string,
+    includeJava: boolean,
+): Promise<BundleManifest> {
+    return await invoke("plugin:install|cmd_export_install_bundle", {
+        instance,
+        output,
+        includeJava,
+    })
+}
@@ -0,0 +1,636 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Offline install bundles for machines without internet access.
+//!
+//! A bundle is a zip archive holding every file needed to install an instance
+//! runtime: the version JSONs and jars, libraries, the asset index and its
+//! objects, mod loader artifacts and optionally the Mojang Java runtime.
+//!
+//! - `bundle.json` is the [`BundleManifest`];
+//! - `files/<path>` are the files, relative to the data folder.
+//!
+//! Every file is checked against the SHA-1 recorded in the manifest before it
+//! is written to the data folder.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::File,
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    sync::atomic::Ordering,
+};
+
+#[cfg(not(windows))]
+use std::os::unix::fs::PermissionsExt;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+use download::{Checksum, DownloadTask, progress::DownloadState};
+use folder::{DATA_LOCATION, MinecraftLocation};
+use instance::{Instance, ModLoaderType};
+use version::{AssetIndexObject, Version, load_patches, resolve_version_with_patches};
+
+use crate::error::*;
+
+const MANIFEST_FILE_NAME: &str = "bundle.json";
+const FILES_PREFIX: &str = "files";
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// The file recording which bundle an instance was installed from, so launch
+/// can keep using it as a local source.
+const BUNDLE_SOURCE_FILE_NAME: &str = ".install-bundle";
+
+/// Describes the content of an install bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+
+    /// Minecraft version of the bundled runtime.
+    pub minecraft: String,
+
+    /// Version id to launch, including the mod loader (e.g. `neoforge-21.1.77`).
+    pub version_id: String,
+
+    /// Whether the Mojang Java runtime is included.
+    pub include_java: bool,
+
+    pub files: Vec<BundleEntry>,
+}
+
+/// A single file stored in a bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    /// Path relative to the data folder, separated by `/`.
+    pub path: String,
+    pub sha1: String,
+    pub size: u64,
+    #[serde(default)]
+    pub executable: bool,
+}
+
+/// Collects every file needed to install the runtime of `instance` and writes
+/// them into a bundle at `output`.
+///
+/// The instance must already be installed on this machine.
+pub async fn export_bundle(
+    instance: &Instance,
+    output: &Path,
+    include_java: bool,
+) -> Result<BundleManifest> {
+    let minecraft_location = MinecraftLocation::new(&DATA_LOCATION.root);
+    let version_id = instance
+        .get_version_id()
+        .map_err(|_| Error::InvalidInstanceConfig)?;
+    let raw_version_json =
+        async_fs::read_to_string(minecraft_location.get_version_json(&version_id)).await?;
+    // Resolved with the patches of the instance, so the bundle holds the
+    // libraries it actually launches with.
+    let patches = load_patches(instance.get_patches_root())?;
+    let resolved_version = resolve_version_with_patches(
+        &Version::from_str(&raw_version_json)?,
+        &minecraft_location,
+        &[],
+        &patches,
+    )
+    .await?;
+
+    let mut files = BTreeSet::new();
+    for id in std::iter::once(&resolved_version.id).chain(&resolved_version.inheritances) {
+        collect_folder(&minecraft_location.get_version_root(id), &mut files)?;
+    }
+    for library in &resolved_version.libraries {
+        files.insert(minecraft_location.get_library_by_path(&library.download_info().path));
+    }
+    if let Some(asset_index) = &resolved_version.asset_index {
+        let index_path = minecraft_location.get_assets_index(&asset_index.id);
+        let index: serde_json::Value =
+            serde_json::from_str(&async_fs::read_to_string(&index_path).await?)?;
+        let objects: AssetIndexObject = serde_json::from_value(index["objects"].clone())?;
+        for object in objects.values() {
+            files.insert(
+                minecraft_location
+                    .assets
+                    .join("objects")
+                    .join(&object.hash[0..2])
+                    .join(&object.hash),
+            );
+        }
+        files.insert(index_path);
+    }
+    // Forge and NeoForge installers generate patched jars that are not listed in
+    // the version JSON, so the folders they write to for this version are
+    // bundled as a whole.
+    let runtime = &instance.config.runtime;
+    match (&runtime.mod_loader_type, &runtime.mod_loader_version) {
+        (Some(ModLoaderType::Forge), Some(loader_version)) => {
+            let libraries = &minecraft_location.libraries;
+            collect_minecraft_folders(
+                &libraries.join("net/minecraft/client"),
+                &runtime.minecraft,
+                &mut files,
+            )?;
+            collect_folder(
+                &libraries
+                    .join("net/minecraftforge/forge")
+                    .join(format!("{}-{loader_version}", runtime.minecraft)),
+                &mut files,
+            )?;
+        }
+        (Some(ModLoaderType::Neoforge), Some(loader_version)) => {
+            let libraries = &minecraft_location.libraries;
+            collect_minecraft_folders(
+                &libraries.join("net/minecraft/client"),
+                &runtime.minecraft,
+                &mut files,
+            )?;
+            collect_folder(
+                &libraries
+                    .join("net/neoforged/neoforge")
+                    .join(loader_version),
+                &mut files,
+            )?;
+        }
+        _ => (),
+    }
+    if include_java {
+        let java_directory =
+            crate::java::get_installation_directory(&resolved_version.java_version.component)?;
+        if !java_directory.is_dir() {
+            return Err(Error::BundleFileMissing(
+                java_directory.to_string_lossy().to_string(),
+            ));
+        }
+        collect_folder(&java_directory, &mut files)?;
+    }
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        minecraft: runtime.minecraft.clone(),
+        version_id,
+        include_java,
+        files: vec![],
+    };
+    let output = output.to_path_buf();
+    info!(
+        "Writing {} files into the install bundle {}",
+        files.len(),
+        output.display()
+    );
+    tokio::task::spawn_blocking(move || write_bundle(&DATA_LOCATION.root, &output, manifest, files))
+        .await?
+}
+
+/// Writes `files`, which are inside the data folder `root`, into a bundle at
+/// `output`.
+fn write_bundle(
+    root: &Path,
+    output: &Path,
+    mut manifest: BundleManifest,
+    files: BTreeSet<PathBuf>,
+) -> Result<BundleManifest> {
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = ZipWriter::new(File::create(output)?);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    for path in files {
+        let relative_path = relative_to_data_folder(root, &path)
+            .ok_or_else(|| Error::InvalidBundle(path.to_string_lossy().to_string()))?;
+        let file = File::open(&path)
+            .map_err(|_| Error::BundleFileMissing(path.to_string_lossy().to_string()))?;
+        let executable = is_executable(&path);
+        let file_options = if executable {
+            options.unix_permissions(0o755)
+        } else {
+            options
+        };
+        writer.start_file(format!("{FILES_PREFIX}/{relative_path}"), file_options)?;
+        let mut reader = Sha1Reader::new(file);
+        io::copy(&mut reader, &mut writer)?;
+        manifest.files.push(BundleEntry {
+            path: relative_path,
+            sha1: reader.digest(),
+            size: reader.size,
+            executable,
+        });
+    }
+    writer.start_file(MANIFEST_FILE_NAME, options)?;
+    writer.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    writer.finish()?;
+    Ok(manifest)
+}
+
+/// Adds every file below `folder` to `files`. A missing folder is skipped.
+fn collect_folder(folder: &Path, files: &mut BTreeSet<PathBuf>) -> Result<()> {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        // Natives are extracted again on launch.
+        if entry.file_name() == "conic-natives" {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_folder(&path, files)?;
+        } else if path.is_file() {
+            files.insert(path);
+        }
+    }
+    Ok(())
+}
+
+/// Adds every file below the folders of `folder` named `{minecraft}-*` to
+/// `files`, leaving out the folders of other Minecraft versions.
+fn collect_minecraft_folders(
+    folder: &Path,
+    minecraft: &str,
+    files: &mut BTreeSet<PathBuf>,
+) -> Result<()> {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let is_version_folder = entry.file_name().to_str().is_some_and(|name| {
+            name.strip_prefix(minecraft)
+                .is_some_and(|suffix| suffix.starts_with('-'))
+        });
+        if is_version_folder {
+            collect_folder(&entry.path(), files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts a path inside the data folder `root` into a `/` separated
+/// relative path.
+fn relative_to_data_folder(root: &Path, path: &Path) -> Option<String> {
+    let relative_path = path.strip_prefix(root).ok()?;
+    let components = relative_path
+        .components()
+        .map(|component| match component {
+            Component::Normal(component) => Some(component.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(components.join("/"))
+}
+
+#[cfg(not(windows))]
+fn is_executable(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+/// An opened install bundle, used as a local file source.
+pub struct BundleSource {
+    /// The data folder the files are restored to.
+    root: PathBuf,
+    archive: ZipArchive<File>,
+    manifest: BundleManifest,
+    entries: HashMap<String, BundleEntry>,
+}
+
+impl BundleSource {
+    /// Opens the bundle at `path` and reads its manifest.
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::open_in(path, DATA_LOCATION.root.clone())
+    }
+
+    /// Opens the bundle at `path`, restoring files to the data folder `root`.
+    fn open_in(path: &Path, root: PathBuf) -> Result<Self> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let manifest: BundleManifest = {
+            let mut manifest_file = archive.by_name(MANIFEST_FILE_NAME)?;
+            let mut content = String::new();
+            manifest_file.read_to_string(&mut content)?;
+            serde_json::from_str(&content)?
+        };
+        if manifest.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(Error::InvalidBundle(format!(
+                "unsupported format version {}",
+                manifest.format_version
+            )));
+        }
+        let mut entries = HashMap::with_capacity(manifest.files.len());
+        for entry in &manifest.files {
+            if !is_safe_relative_path(&entry.path) {
+                return Err(Error::InvalidBundle(entry.path.clone()));
+            }
+            entries.insert(entry.path.clone(), entry.clone());
+        }
+        Ok(Self {
+            root,
+            archive,
+            manifest,
+            entries,
+        })
+    }
+
+    pub fn manifest(&self) -> &BundleManifest {
+        &self.manifest
+    }
+
+    /// Writes every bundled file into the data folder, skipping files that are
+    /// already present with the recorded checksum.
+    pub fn extract_all(&mut self, progress: &DownloadState) -> Result<()> {
+        progress.reset(Ordering::SeqCst);
+        progress
+            .total_tasks
+            .store(self.manifest.files.len() as u64, Ordering::SeqCst);
+        progress.total_bytes.store(
+            self.manifest.files.iter().map(|entry| entry.size).sum(),
+            Ordering::SeqCst,
+        );
+        for entry in self.manifest.files.clone() {
+            if !is_file_valid(&self.root, &entry) {
+                self.extract(&entry)?;
+            }
+            progress.completed_tasks.fetch_add(1, Ordering::SeqCst);
+            progress
+                .completed_bytes
+                .fetch_add(entry.size, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Restores the files of `tasks` from the bundle and returns the tasks that
+    /// the bundle cannot satisfy, which still have to be downloaded.
+    pub fn restore(&mut self, tasks: Vec<DownloadTask>) -> Result<Vec<DownloadTask>> {
+        let mut remaining = Vec::new();
+        let mut restored = 0;
+        for task in tasks {
+            let entry = relative_to_data_folder(&self.root, &task.file)
+                .and_then(|relative_path| self.entries.get(&relative_path))
+                .cloned();
+            let Some(entry) = entry else {
+                remaining.push(task);
+                continue;
+            };
+            if let Checksum::Sha1(sha1) = &task.checksum
+                && sha1 != &entry.sha1
+            {
+                warn!(
+                    "Bundled file {} does not match the version JSON, it will be downloaded",
+                    entry.path
+                );
+                remaining.push(task);
+                continue;
+            }
+            if !is_file_valid(&self.root, &entry) {
+                self.extract(&entry)?;
+                restored += 1;
+            }
+        }
+        if restored > 0 {
+            info!("Restored {restored} files from the install bundle");
+        }
+        Ok(remaining)
+    }
+
+    fn extract(&mut self, entry: &BundleEntry) -> Result<()> {
+        let path = self.root.join(&entry.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Written next to the file and moved over it once verified.
+        let mut partial_path = path.clone().into_os_string();
+        partial_path.push(".part");
+        let partial_path = PathBuf::from(partial_path);
+        let mut reader = Sha1Reader::new(
+            self.archive
+                .by_name(&format!("{FILES_PREFIX}/{}", entry.path))?,
+        );
+        io::copy(&mut reader, &mut File::create(&partial_path)?)?;
+        if reader.digest() != entry.sha1 {
+            let _ = std::fs::remove_file(&partial_path);
+            return Err(Error::ChecksumMissmatch(entry.path.clone()));
+        }
+        std::fs::rename(&partial_path, &path)?;
+        #[cfg(not(windows))]
+        if entry.executable {
+            let mut permissions = std::fs::metadata(&path)?.permissions();
+            permissions.set_mode(0o755);
+            std::fs::set_permissions(&path, permissions)?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the SHA-1 and size of the bytes read through it.
+struct Sha1Reader<R> {
+    inner: R,
+    sha1: sha1_smol::Sha1,
+    size: u64,
+}
+
+impl<R: Read> Sha1Reader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            sha1: sha1_smol::Sha1::new(),
+            size: 0,
+        }
+    }
+
+    fn digest(&self) -> String {
+        self.sha1.digest().to_string()
+    }
+}
+
+impl<R: Read> Read for Sha1Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.sha1.update(&buf[..read]);
+        self.size += read as u64;
+        Ok(read)
+    }
+}
+
+/// Whether the copy of a bundled file in the data folder `root` exists and
+/// matches its checksum.
+fn is_file_valid(root: &Path, entry: &BundleEntry) -> bool {
+    let path = root.join(&entry.path);
+    match File::open(path) {
+        Ok(file)
+            if file
+                .metadata()
+                .is_ok_and(|metadata| metadata.len() == entry.size) =>
+        {
+            let mut reader = Sha1Reader::new(file);
+            io::copy(&mut reader, &mut io::sink()).is_ok() && reader.digest() == entry.sha1
+        }
+        _ => false,
+    }
+}
+
+fn is_safe_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Records that `instance` was installed from the bundle at `bundle_path`.
+pub async fn save_instance_bundle_source(instance: &Instance, bundle_path: &Path) -> Result<()> {
+    async_fs::write(
+        DATA_LOCATION
+            .get_instance_root(&instance.id)
+            .join(BUNDLE_SOURCE_FILE_NAME),
+        bundle_path.to_string_lossy().as_bytes(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Opens the bundle `instance` was installed from, if it is still available.
+pub fn open_instance_bundle_source(instance: &Instance) -> Option<BundleSource> {
+    let bundle_path = std::fs::read_to_string(
+        DATA_LOCATION
+            .get_instance_root(&instance.id)
+            .join(BUNDLE_SOURCE_FILE_NAME),
+    )
+    .ok()?;
+    match BundleSource::open(Path::new(bundle_path.trim())) {
+        Ok(source) => Some(source),
+        Err(error) => {
+            warn!("Could not open the install bundle {bundle_path}: {error}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use download::DownloadTaskType;
+
+    use super::*;
+
+    fn sha1(content: &str) -> String {
+        sha1_smol::Sha1::from(content).digest().to_string()
+    }
+
+    fn task(file: PathBuf, checksum: Checksum) -> DownloadTask {
+        DownloadTask {
+            url: String::new(),
+            file,
+            size_bytes: None,
+            checksum,
+            task_type: DownloadTaskType::Unknown,
+        }
+    }
+
+    /// Writes `files` into the data folder `root` and bundles them at `output`.
+    fn bundle_fixture(root: &Path, output: &Path, files: &[(&str, &str)]) -> BundleManifest {
+        for (path, content) in files {
+            let file = root.join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+        }
+        let manifest = BundleManifest {
+            format_version: BUNDLE_FORMAT_VERSION,
+            minecraft: "1.21.4".to_string(),
+            version_id: "1.21.4".to_string(),
+            include_java: false,
+            files: vec![],
+        };
+        let files = files.iter().map(|(path, _)| root.join(path)).collect();
+        write_bundle(root, output, manifest, files).unwrap()
+    }
+
+    #[test]
+    fn rejects_unsafe_paths() {
+        assert!(is_safe_relative_path("libraries/org/lwjgl/lwjgl.jar"));
+        assert!(!is_safe_relative_path(""));
+        assert!(!is_safe_relative_path("../instances/pack/instance.toml"));
+        assert!(!is_safe_relative_path("libraries/../../evil.jar"));
+        assert!(!is_safe_relative_path("/etc/passwd"));
+        assert!(!is_safe_relative_path("./libraries/lwjgl.jar"));
+        #[cfg(windows)]
+        assert!(!is_safe_relative_path(r"C:\Windows\evil.dll"));
+    }
+
+    #[test]
+    fn exports_and_opens_bundles() {
+        let dir = std::env::temp_dir().join(format!("conic-bundle-{}", uuid::Uuid::new_v4()));
+        let (from, to) = (dir.join("from"), dir.join("to"));
+        let output = dir.join("pack.zip");
+        let manifest = bundle_fixture(
+            &from,
+            &output,
+            &[
+                ("versions/1.21.4/1.21.4.json", "{}"),
+                ("libraries/org/lwjgl/lwjgl.jar", "lwjgl"),
+            ],
+        );
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(manifest.files[1].path, "versions/1.21.4/1.21.4.json");
+        assert_eq!(manifest.files[1].sha1, sha1("{}"));
+
+        let mut source = BundleSource::open_in(&output, to.clone()).unwrap();
+        assert_eq!(source.manifest().version_id, "1.21.4");
+        source.extract_all(&DownloadState::default()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(to.join("libraries/org/lwjgl/lwjgl.jar")).unwrap(),
+            "lwjgl"
+        );
+        assert!(to.join("versions/1.21.4/1.21.4.json").is_file());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn restores_only_matching_files() {
+        let dir = std::env::temp_dir().join(format!("conic-bundle-{}", uuid::Uuid::new_v4()));
+        let (from, to) = (dir.join("from"), dir.join("to"));
+        let output = dir.join("pack.zip");
+        bundle_fixture(
+            &from,
+            &output,
+            &[
+                ("libraries/a.jar", "a"),
+                ("libraries/b.jar", "b"),
+                ("libraries/c.jar", "c"),
+            ],
+        );
+
+        let mut source = BundleSource::open_in(&output, to.clone()).unwrap();
+        let remaining = source
+            .restore(vec![
+                task(to.join("libraries/a.jar"), Checksum::Sha1(sha1("a"))),
+                task(to.join("libraries/b.jar"), Checksum::Sha1(sha1("newer b"))),
+                task(to.join("libraries/c.jar"), Checksum::None),
+                task(to.join("libraries/d.jar"), Checksum::Sha1(sha1("d"))),
+            ])
+            .unwrap();
+        let remaining: Vec<_> = remaining.iter().map(|task| task.file.clone()).collect();
+        assert_eq!(
+            remaining,
+            vec![to.join("libraries/b.jar"), to.join("libraries/d.jar")]
+        );
+        assert_eq!(
+            std::fs::read_to_string(to.join("libraries/a.jar")).unwrap(),
+            "a"
+        );
+        assert!(to.join("libraries/c.jar").is_file());
+        assert!(!to.join("libraries/b.jar").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -81,6 +81,15 @@ pub enum Error {
     #[error("Chunk length mismatch")]
     ChunkLengthMismatch,
 
+    #[error("Invalid install bundle: {0}")]
+    InvalidBundle(String),
+
+    #[error("The install bundle was made for {0}")]
+    BundleMismatch(String),
+
+    #[error("Missing file for the install bundle: {0}")]
+    BundleFileMissing(String),
+
     #[error(transparent)]
     Aborted(
         #[from]
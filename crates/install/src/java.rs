@@ -15,7 +15,7 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
 };
-use version::resolve_version;
+use version::{load_patches, resolve_version_with_patches};
 
 use download::{DownloadTask, DownloadTaskType};
 use platform::{OsArch, OsFamily, PLATFORM_INFO};
@@ -198,7 +198,14 @@ pub async fn install_for_instance(
     let unresolved_version = serde_json::from_str::<version::Version>(
         &async_fs::read_to_string(version_json_path).await?,
     )?;
-    let resolved_version = resolve_version(&unresolved_version, &minecraft_location, &[]).await?;
+    // A patched `javaVersion` decides the runtime, like at launch.
+    let resolved_version = resolve_version_with_patches(
+        &unresolved_version,
+        &minecraft_location,
+        &[],
+        &load_patches(instance.get_patches_root())?,
+    )
+    .await?;
     let java_version_list = MojangJavaVersionList::new().await?;
 
     let java_runtime_info = match PLATFORM_INFO.os_family {
@@ -5,6 +5,7 @@
 // TODO: Support Optifine auto install
 
 use std::{
+    path::PathBuf,
     str::FromStr,
     sync::{
         Arc, Mutex,
@@ -29,13 +30,17 @@ use download::download_concurrent;
 use download::progress::DownloadState;
 use folder::{DATA_LOCATION, MinecraftLocation};
 use instance::{Instance, InstanceRuntime, ModLoaderType};
-use version::Version;
+use version::{Version, load_patches};
 
 use crate::{
-    forge::ForgeVersionList, neoforge::get_neoforge_version_list, vanilla::VersionManifest,
+    bundle::{BundleManifest, BundleSource, export_bundle, save_instance_bundle_source},
+    forge::ForgeVersionList,
+    neoforge::get_neoforge_version_list,
+    vanilla::VersionManifest,
 };
 
 pub mod authlib_injector;
+pub mod bundle;
 mod error;
 pub mod fabric;
 pub mod forge;
@@ -68,6 +73,7 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             cmd_get_neoforge_version_list,
             cmd_spawn_install_task,
             cmd_cancel_install_task,
+            cmd_export_install_bundle,
         ])
         .setup(|app, _| {
             app.manage(PluginState::default());
@@ -175,6 +181,7 @@ async fn cmd_spawn_install_task(
     state: State<'_, PluginState>,
     config: Config,
     instance: Instance,
+    bundle: Option<PathBuf>,
     channel: Channel<InstallEvent>,
 ) -> Result<()> {
     if state.task.lock().expect("Internal error").is_some() {
@@ -186,7 +193,12 @@ async fn cmd_spawn_install_task(
         let task_status_cloned = task_status.clone();
         let finished = finished.clone();
         async move {
-            let result = install(config, instance, task_status_cloned).await;
+            let result = match bundle {
+                Some(bundle) => {
+                    install_from_bundle(config, instance, bundle, task_status_cloned).await
+                }
+                None => install(config, instance, task_status_cloned).await,
+            };
             finished.store(true, Ordering::SeqCst);
             result
         }
@@ -230,6 +242,15 @@ fn cmd_cancel_install_task(state: State<'_, PluginState>) {
     *current_task = None;
 }
 
+#[command]
+async fn cmd_export_install_bundle(
+    instance: Instance,
+    output: PathBuf,
+    include_java: bool,
+) -> Result<BundleManifest> {
+    export_bundle(&instance, &output, include_java).await
+}
+
 /// Installs Minecraft, Java, and optionally a mod loader for the given instance.
 ///
 /// This function runs a full installation pipeline including:
@@ -263,6 +284,7 @@ pub async fn install(
     let download_list = generate_download_info(
         &runtime.minecraft,
         MinecraftLocation::new(&DATA_LOCATION.root),
+        &load_patches(instance.get_patches_root())?,
     )
     .await?;
 
@@ -297,6 +319,67 @@ pub async fn install(
 
     configure_first_launch_language(config, &instance).await;
 
+    save_install_lock(&instance).await
+}
+
+/// Installs the instance from an offline bundle made by [`bundle::export_bundle`]
+/// instead of downloading the files.
+///
+/// The bundle must contain the same version as the instance. The bundle path is
+/// remembered so launch can restore files from it without network access.
+pub async fn install_from_bundle(
+    config: Config,
+    instance: Instance,
+    bundle_path: PathBuf,
+    status: Arc<Mutex<InstallEvent>>,
+) -> Result<()> {
+    {
+        let mut status = status.lock().expect("Internal Error");
+        *status = InstallEvent::Prepare;
+    }
+    info!(
+        "Start installing the game for instance {} from bundle {}",
+        instance.config.name,
+        bundle_path.display()
+    );
+    print_runtime_info(&instance.config.runtime);
+
+    let mut source = BundleSource::open(&bundle_path)?;
+    let version_id = instance
+        .get_version_id()
+        .map_err(|_| Error::InvalidInstanceConfig)?;
+    if source.manifest().version_id != version_id {
+        return Err(Error::BundleMismatch(source.manifest().version_id.clone()));
+    }
+
+    let progress = DownloadState::default();
+    {
+        let mut status = status.lock().expect("internal error");
+        *status = InstallEvent::InstallGame(progress.clone())
+    }
+    let include_java = source.manifest().include_java;
+    info!("Extracting files");
+    tokio::task::spawn_blocking(move || source.extract_all(&progress)).await??;
+    save_instance_bundle_source(&instance, &bundle_path).await?;
+
+    // Bundles made without Java still need the runtime from the network,
+    // unless the instance uses a custom Java.
+    if !include_java && instance.config.launch_config.java_path.is_none() {
+        info!("Installing Java");
+        let progress = DownloadState::default();
+        {
+            let mut status = status.lock().expect("Internal error");
+            *status = InstallEvent::InstallJava(progress.clone())
+        }
+        java::install_for_instance(&instance, &progress, config.download.clone()).await?;
+    }
+
+    configure_first_launch_language(config, &instance).await;
+
+    save_install_lock(&instance).await
+}
+
+async fn save_install_lock(instance: &Instance) -> Result<()> {
     debug!("Saving lock file");
     async_fs::write(
         DATA_LOCATION
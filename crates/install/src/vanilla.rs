@@ -2,15 +2,19 @@
 // Copyright 2022-2026 ConicMC developers. All rights reserved.
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::str::FromStr;
+use std::{
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
 
-use serde_json::Value;
+use log::{info, warn};
 use shared::HTTP_CLIENT;
 
 use download::{Checksum, DownloadTask, DownloadTaskType};
 use folder::MinecraftLocation;
 use version::{
-    self, AssetIndex, AssetIndexObject, ResolvedLibrary, ResolvedVersion, resolve_version,
+    self, AssetIndex, AssetIndexContent, AssetLayout, ResolvedLibrary, ResolvedVersion,
+    VersionPatch, resolve_version_with_patches,
 };
 
 use serde::{Deserialize, Serialize};
@@ -60,6 +64,7 @@ pub struct VersionInfo {
 ///
 /// * `version_id` - The Minecraft version ID (e.g., `"1.20.1"`).
 /// * `minecraft_location` - The root location of the Minecraft installation.
+/// * `patches` - The patches of the instance, whose libraries are downloaded too.
 ///
 /// # Returns
 ///
@@ -67,12 +72,14 @@ pub struct VersionInfo {
 pub async fn generate_download_info(
     version_id: &str,
     minecraft_location: MinecraftLocation,
+    patches: &[VersionPatch],
 ) -> Result<Vec<DownloadTask>> {
     let raw_version_json = get_version_json(version_id).await?;
-    let resolved_version = resolve_version(
+    let resolved_version = resolve_version_with_patches(
         &version::Version::from_str(&raw_version_json)?,
         &minecraft_location,
         &[],
+        patches,
     )
     .await?;
     let resolved_version_id = &resolved_version.id;
@@ -203,16 +210,10 @@ pub async fn generate_assets_downloads(
     minecraft_location: &MinecraftLocation,
     asset_index: &AssetIndex,
 ) -> Result<Vec<DownloadTask>> {
-    let asset_index_raw = HTTP_CLIENT
-        .get(&asset_index.url)
-        .send()
-        .await?
-        .text()
-        .await?;
-    let asset_index_json: Value = serde_json::from_str(asset_index_raw.as_ref())?;
-    let asset_index_object: AssetIndexObject =
-        serde_json::from_value(asset_index_json["objects"].clone())?;
-    let mut assets: Vec<_> = asset_index_object
+    let asset_index_raw = read_asset_index(minecraft_location, asset_index).await?;
+    let asset_index_content: AssetIndexContent = serde_json::from_str(asset_index_raw.as_ref())?;
+    let mut assets: Vec<_> = asset_index_content
+        .objects
         .into_iter()
         .map(|obj| DownloadTask {
             url: format!(
@@ -234,12 +235,125 @@ pub async fn generate_assets_downloads(
         url: asset_index.url.clone(),
         file: minecraft_location.get_assets_index(&asset_index.id),
         size_bytes: Some(asset_index.size),
-        checksum: Checksum::None,
+        checksum: match asset_index.sha1.clone() {
+            None => Checksum::None,
+            Some(sha1) => Checksum::Sha1(sha1),
+        },
         task_type: DownloadTaskType::Unknown,
     });
     Ok(assets)
 }
 
+/// Reads the asset index from disk when a verified copy is already there,
+/// otherwise fetches it from the URL in the version JSON.
+async fn read_asset_index(
+    minecraft_location: &MinecraftLocation,
+    asset_index: &AssetIndex,
+) -> Result<String> {
+    if let Some(sha1) = &asset_index.sha1
+        && let Ok(content) =
+            async_fs::read(minecraft_location.get_assets_index(&asset_index.id)).await
+        && &sha1_smol::Sha1::from(&content).digest().to_string() == sha1
+        && let Ok(content) = String::from_utf8(content)
+    {
+        return Ok(content);
+    }
+    Ok(HTTP_CLIENT
+        .get(&asset_index.url)
+        .send()
+        .await?
+        .text()
+        .await?)
+}
+
+/// Reads the layout of a downloaded asset index.
+///
+/// A missing or unreadable index is treated as the modern `objects` layout.
+pub async fn read_asset_layout(
+    minecraft_location: &MinecraftLocation,
+    asset_index_id: &str,
+) -> AssetLayout {
+    let Ok(content) =
+        async_fs::read_to_string(minecraft_location.get_assets_index(asset_index_id)).await
+    else {
+        return AssetLayout::Objects;
+    };
+    serde_json::from_str::<AssetIndexContent>(&content)
+        .map(|content| content.layout())
+        .unwrap_or(AssetLayout::Objects)
+}
+
+/// Returns the folder passed to the game as `${game_assets}`.
+pub fn get_game_assets_directory(
+    minecraft_location: &MinecraftLocation,
+    asset_index_id: &str,
+    layout: AssetLayout,
+    game_directory: &Path,
+) -> PathBuf {
+    match layout {
+        AssetLayout::MapToResources => game_directory.join("resources"),
+        AssetLayout::Objects | AssetLayout::Virtual => minecraft_location
+            .assets
+            .join("virtual")
+            .join(asset_index_id),
+    }
+}
+
+/// Copies the hashed asset objects to the named files read by versions using
+/// a `virtual` or `map_to_resources` asset index. Does nothing for the modern
+/// layout.
+///
+/// Files already present with the expected size are kept. The asset index and
+/// objects must already be downloaded.
+pub async fn materialize_legacy_assets(
+    minecraft_location: &MinecraftLocation,
+    asset_index_id: &str,
+    game_directory: &Path,
+) -> Result<AssetLayout> {
+    let content: AssetIndexContent = serde_json::from_str(
+        &async_fs::read_to_string(minecraft_location.get_assets_index(asset_index_id)).await?,
+    )?;
+    let layout = content.layout();
+    if layout == AssetLayout::Objects {
+        return Ok(layout);
+    }
+    let target_root =
+        get_game_assets_directory(minecraft_location, asset_index_id, layout, game_directory);
+    info!(
+        "Materializing {} legacy assets into {}",
+        content.objects.len(),
+        target_root.display()
+    );
+    for (name, object) in content.objects {
+        if !Path::new(&name)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            warn!("Skipped asset with an invalid name: {name}");
+            continue;
+        }
+        let target = target_root.join(&name);
+        if async_fs::metadata(&target)
+            .await
+            .is_ok_and(|metadata| metadata.len() == object.size)
+        {
+            continue;
+        }
+        let source = minecraft_location
+            .assets
+            .join("objects")
+            .join(&object.hash[0..2])
+            .join(&object.hash);
+        if let Some(parent) = target.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        if let Err(error) = async_fs::copy(&source, &target).await {
+            warn!("Failed to copy asset {name}: {error}");
+        }
+    }
+    Ok(layout)
+}
+
 const LOF4J2_CONFIGURATION: &[u8] = include_bytes!("./log4j2.xml");
 
 /// Override the `log4j2.xml` configuration file for the given version.
@@ -256,9 +370,24 @@ pub async fn override_log4j2_configuration_file(
     minecraft_location: &MinecraftLocation,
     version: &ResolvedVersion,
 ) -> Result<()> {
+    let mut configuration = String::from_utf8_lossy(LOF4J2_CONFIGURATION).to_string();
+    // Since 1.21.2 the log4j2 `XMLLayout` no longer prints the legacy event
+    // format, Mojang ships `LegacyXMLLayout` for it instead.
+    let client_config = version
+        .logging
+        .get("client")
+        .map(|logging| logging.file.id.as_str());
+    if !matches!(client_config, Some("client-1.7.xml" | "client-1.12.xml")) {
+        configuration = configuration
+            .replace(
+                "<Configuration status=\"WARN\">",
+                "<Configuration status=\"WARN\" packages=\"com.mojang.util\">",
+            )
+            .replace("<XMLLayout />", "<LegacyXMLLayout />");
+    }
     async_fs::write(
         minecraft_location.get_log_config(version.id.clone()),
-        LOF4J2_CONFIGURATION,
+        configuration,
     )
     .await?;
     Ok(())
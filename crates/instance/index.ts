@@ -2,6 +2,7 @@
 // Copyright 2022-2026 ConicMC developers. All rights reserved.
 // SPDX-License-Identifier: GPL-3.0-only
 
+import { GpuPreference, JavaAgent, LinuxPerformance, NativesMode } from "@conic/config"
 import { invoke } from "@tauri-apps/api/core"
 
 export type InstanceRuntime = {
@@ -9,6 +10,13 @@ export type InstanceRuntime = {
     mod_loader_type?: "Quilt" | "Fabric" | "Neoforge" | "Forge"
     mod_loader_version?: string
 }
+
+/** An account the instance launches with, instead of the account selected in the launcher */
+export type AccountBinding =
+    | { type: "Microsoft"; uuid: string }
+    | { type: "Offline"; uuid: string }
+    | { type: "Yggdrasil"; identifier: string; api_root: string }
+
 export type InstanceConfig = {
     name: string
     icon?: Base64URLString
@@ -32,13 +40,27 @@ export type InstanceConfig = {
         ignore_patch_discrepancies?: boolean
         extra_class_paths?: string
         gc?: "Serial" | "Parallel" | "G1" | "Z"
+        /** An empty id disables the global preset */
+        jvm_preset?: string
         launcher_name?: string
         wrap_command?: string
+        linux_performance?: LinuxPerformance
+        /** The discrete GPU by default */
+        gpu?: GpuPreference
+        natives_mode?: NativesMode
+        /** Loaded after the global agents */
+        java_agents?: JavaAgent[]
         execute_before_launch?: string
+        execute_after_start?: string
         execute_after_launch?: string
+        execute_on_crash?: string
+        hook_timeout_secs?: number
         skip_check_files?: boolean
         quit_app_after_launch?: boolean
+        legacy_proxy?: boolean
     }
+    /** The account selected in the launcher when unset */
+    account?: AccountBinding | null
     use_as_launcher_background?: boolean
 }
 
@@ -72,6 +94,29 @@ export async function deleteInstance(id: string): Promise<void> {
     return await invoke("plugin:instance|cmd_delete_instance", { id })
 }
 
+export type InstanceContent =
+    | "Mods"
+    | "Config"
+    | "Saves"
+    | "Resourcepacks"
+    | "Shaderpacks"
+    | "Screenshots"
+
+export type DuplicateOptions = {
+    /** The name of the copy */
+    name: string
+    /** Copied besides `instance.toml`, the background and the install state. `Config` includes the game options */
+    content: InstanceContent[]
+}
+
+/**
+ * Copies an instance with the selected content, keeping its install state so the copy launches without verifying its files.
+ * @returns The id of the copy.
+ */
+export async function duplicateInstance(id: string, options: DuplicateOptions): Promise<string> {
+    return await invoke("plugin:instance|cmd_duplicate_instance", { id, options })
+}
+
 export async function addBackgroundImage(path: string, id: string): Promise<void> {
     return await invoke("plugin:instance|cmd_add_background_file", { path, id })
 }
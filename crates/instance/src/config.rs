@@ -5,8 +5,13 @@
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use config::launch::{GC, Server};
+use config::launch::{
+    GC, GpuPreference, JavaAgent, LinuxPerformance, NativesMode, Server, find_unknown_placeholder,
+};
+
+use crate::error::*;
 
 /// Represents supported mod loader types.
 #[derive(Deserialize, Serialize)]
@@ -32,6 +37,37 @@ impl fmt::Display for ModLoaderType {
     }
 }
 
+/// An account bound to an instance, which launches with it instead of the
+/// account selected in the launcher.
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum AccountBinding {
+    Microsoft {
+        uuid: Uuid,
+    },
+    Offline {
+        uuid: Uuid,
+    },
+    /// An account of the authentication server at `api_root`.
+    Yggdrasil {
+        identifier: Uuid,
+        api_root: String,
+    },
+}
+
+impl fmt::Display for AccountBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Microsoft { uuid } => write!(f, "Microsoft account {uuid}"),
+            Self::Offline { uuid } => write!(f, "offline account {uuid}"),
+            Self::Yggdrasil {
+                identifier,
+                api_root,
+            } => write!(f, "Yggdrasil account {identifier} of {api_root}"),
+        }
+    }
+}
+
 /// Defines the runtime environment for a Minecraft instance.
 #[derive(Deserialize, Serialize, Default)]
 pub struct InstanceRuntime {
@@ -96,22 +132,71 @@ pub struct InstanceLaunchConfig {
     /// Garbage collection configuration
     pub gc: Option<GC>,
 
+    /// Id of the JVM tuning preset, an empty id disables the global preset
+    pub jvm_preset: Option<String>,
+
     /// Launcher name override
     pub launcher_name: Option<String>,
 
     /// Optional command wrapper (e.g., script or proxy)
     pub wrap_command: Option<String>,
 
+    /// Performance tools and process settings on Linux
+    pub linux_performance: Option<LinuxPerformance>,
+
+    /// The GPU running the game on Linux, the discrete one by default
+    pub gpu: Option<GpuPreference>,
+
+    /// Where the LWJGL natives are loaded from on Linux
+    pub natives_mode: Option<NativesMode>,
+
+    /// Java agents loaded after the global ones
+    pub java_agents: Option<Vec<JavaAgent>>,
+
     /// Script or command to execute before launch
     pub execute_before_launch: Option<String>,
 
-    /// Script or command to execute after launch
+    /// Script or command to execute once the game window is ready
+    pub execute_after_start: Option<String>,
+
+    /// Script or command to execute after the game exits
     pub execute_after_launch: Option<String>,
 
+    /// Script or command to execute after the game exits with an error
+    pub execute_on_crash: Option<String>,
+
+    /// Seconds a hook may run before it is killed
+    pub hook_timeout_secs: Option<u64>,
+
     /// If true, skips integrity checks of the game files.
     pub skip_check_files: Option<bool>,
 
     pub quit_app_after_launch: Option<bool>,
+
+    /// Whether to route legacy versions through the local legacy proxy
+    pub legacy_proxy: Option<bool>,
+}
+
+impl InstanceLaunchConfig {
+    /// Returns [`Error::UnknownPlaceholder`] when an argument, the wrap
+    /// command or a hook uses a placeholder the launcher does not expand.
+    pub fn validate_placeholders(&self) -> Result<()> {
+        match find_unknown_placeholder(
+            [
+                &self.extra_jvm_args,
+                &self.extra_mc_args,
+                &self.wrap_command,
+                &self.execute_before_launch,
+                &self.execute_after_start,
+                &self.execute_after_launch,
+                &self.execute_on_crash,
+            ]
+            .map(|value| value.as_deref().unwrap_or_default()),
+        ) {
+            Some(unknown) => Err(Error::UnknownPlaceholder(unknown)),
+            None => Ok(()),
+        }
+    }
 }
 
 /// Main configuration structure for a Minecraft instance.
@@ -133,6 +218,11 @@ pub struct InstanceConfig {
     #[serde(default)]
     pub launch_config: InstanceLaunchConfig,
 
+    /// The account the instance launches with, the account selected in the
+    /// launcher when unset
+    #[serde(default)]
+    pub account: Option<AccountBinding>,
+
     /// Whether to use this instance's background image as the launcher background
     #[serde(default)]
     pub use_as_launcher_background: bool,
@@ -160,7 +250,34 @@ impl InstanceConfig {
             },
             group: None,
             launch_config: InstanceLaunchConfig::default(),
+            account: None,
             use_as_launcher_background: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_account_bindings() {
+        let mut config = InstanceConfig::new("Bound", "1.21.4");
+        let identifier = Uuid::new_v4();
+        config.account = Some(AccountBinding::Yggdrasil {
+            identifier,
+            api_root: "https://example.com/api/yggdrasil".to_string(),
+        });
+        let serialized = toml::to_string(&config).unwrap();
+        assert!(serialized.contains(&format!(
+            "[account]\ntype = \"Yggdrasil\"\nidentifier = \"{identifier}\"\n\
+             api_root = \"https://example.com/api/yggdrasil\"\n"
+        )));
+        let deserialized: InstanceConfig = toml::from_str(&serialized).unwrap();
+        assert!(deserialized.account == config.account);
+
+        let unbound: InstanceConfig =
+            toml::from_str("name = \"Unbound\"\n[runtime]\nminecraft = \"1.21.4\"\n").unwrap();
+        assert!(unbound.account.is_none());
+    }
+}
@@ -0,0 +1,215 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{fs, io, path::Path};
+
+use folder::DATA_LOCATION;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{create_instance, error::*, get_instance_by_id};
+
+/// The files recording that an instance is installed, its game files were
+/// verified and the bundle it was installed from, so a copy launches without
+/// installing or hashing them again.
+const INSTALL_STATE: [&str; 4] = [
+    ".install.lock",
+    ".conic-integrity.json",
+    ".install-bundle",
+    "patches",
+];
+
+/// The content of an instance a duplicate can copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum InstanceContent {
+    Mods,
+    /// The `config` folder and the game options.
+    Config,
+    Saves,
+    Resourcepacks,
+    Shaderpacks,
+    Screenshots,
+}
+
+impl InstanceContent {
+    /// The files and folders of the content, relative to the instance root.
+    fn paths(self) -> &'static [&'static str] {
+        match self {
+            Self::Mods => &["mods"],
+            Self::Config => &[
+                "config",
+                "options.txt",
+                "optionsof.txt",
+                "optionsshaders.txt",
+            ],
+            Self::Saves => &["saves"],
+            Self::Resourcepacks => &["resourcepacks"],
+            Self::Shaderpacks => &["shaderpacks"],
+            Self::Screenshots => &["screenshots"],
+        }
+    }
+
+    /// Whether the files of the content are replaced rather than written in
+    /// place, so both instances can share them through hard links. Worlds and
+    /// configs are modified in place and always copied.
+    fn can_hard_link(self) -> bool {
+        matches!(
+            self,
+            Self::Mods | Self::Resourcepacks | Self::Shaderpacks | Self::Screenshots
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DuplicateOptions {
+    /// The name of the copy.
+    pub name: String,
+    /// The content copied besides `instance.toml`, the background and the
+    /// install state.
+    #[serde(default)]
+    pub content: Vec<InstanceContent>,
+}
+
+/// Creates a copy of the instance `id` named `options.name`, with the
+/// content selected in `options`, and returns its id.
+///
+/// Files are hard-linked when they can be shared, and copied otherwise. The
+/// copy keeps the install state of the instance, so it launches without
+/// verifying its files again. Returns [`Error::EmptyInstanceName`] when
+/// `options.name` is blank, like [`create_instance`].
+pub async fn duplicate_instance(id: &str, options: DuplicateOptions) -> Result<String> {
+    let instance = get_instance_by_id(id)
+        .await
+        .ok_or_else(|| Error::InstanceNotFound(id.to_string()))?;
+    let mut config = instance.config;
+    config.name = options.name;
+    // Only one instance provides the launcher background.
+    config.use_as_launcher_background = false;
+    let new_id = Uuid::new_v4().to_string();
+    create_instance(config, Some(&new_id)).await?;
+
+    let from = DATA_LOCATION.get_instance_root(id);
+    let to = DATA_LOCATION.get_instance_root(&new_id);
+    let copied = tokio::task::spawn_blocking({
+        let to = to.clone();
+        move || copy_instance_files(&from, &to, &options.content)
+    })
+    .await
+    .unwrap_or_else(|error| Err(io::Error::other(error)));
+    match copied {
+        Ok(files) => {
+            info!("Duplicated {id} to {new_id}, {files} files copied");
+            Ok(new_id)
+        }
+        Err(error) => {
+            warn!("Failed to duplicate {id}: {error}");
+            let _ = async_fs::remove_dir_all(&to).await;
+            Err(error.into())
+        }
+    }
+}
+
+/// Copies the background, the install state and `content` of the instance
+/// at `from` to `to`, returning the number of files copied.
+fn copy_instance_files(from: &Path, to: &Path, content: &[InstanceContent]) -> io::Result<u64> {
+    let mut files = 0;
+    for path in ["background"].iter().chain(&INSTALL_STATE) {
+        files += copy_path(&from.join(path), &to.join(path), false)?;
+    }
+    for content in content {
+        for path in content.paths() {
+            files += copy_path(&from.join(path), &to.join(path), content.can_hard_link())?;
+        }
+    }
+    Ok(files)
+}
+
+/// Copies the file or folder `from` to `to`, doing nothing when it does not
+/// exist. Files are hard-linked when `hard_link` and the file system allows.
+fn copy_path(from: &Path, to: &Path, hard_link: bool) -> io::Result<u64> {
+    let metadata = match fs::metadata(from) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(error),
+    };
+    if !metadata.is_dir() {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if !(hard_link && fs::hard_link(from, to).is_ok()) {
+            fs::copy(from, to)?;
+        }
+        return Ok(1);
+    }
+    fs::create_dir_all(to)?;
+    let mut files = 0;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        files += copy_path(&entry.path(), &to.join(entry.file_name()), hard_link)?;
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Creates an instance folder with a background, install state, mods,
+    /// nested configs and a world.
+    fn instance_fixture() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("conic-duplicate-{}", Uuid::new_v4()));
+        for (path, content) in [
+            ("instance.toml", "name = \"Pack\""),
+            ("background", "png"),
+            (".install.lock", ""),
+            (".install-bundle", "/media/usb/pack.zip"),
+            ("patches/client.json", "{}"),
+            ("mods/sodium.jar", "sodium"),
+            ("config/sodium/options.json", "{}"),
+            ("options.txt", "fov:0.0"),
+            ("saves/World/level.dat", "level"),
+        ] {
+            let file = root.join("from").join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn copies_selected_content() {
+        let root = instance_fixture();
+        let (from, to) = (root.join("from"), root.join("to"));
+        let content = [InstanceContent::Mods, InstanceContent::Config];
+        assert_eq!(copy_instance_files(&from, &to, &content).unwrap(), 7);
+        assert_eq!(
+            fs::read_to_string(to.join("mods/sodium.jar")).unwrap(),
+            "sodium"
+        );
+        assert_eq!(
+            fs::read_to_string(to.join("config/sodium/options.json")).unwrap(),
+            "{}"
+        );
+        assert!(to.join("options.txt").is_file());
+        assert!(to.join("patches/client.json").is_file());
+        assert!(!to.join("saves").exists());
+        assert!(!to.join("instance.toml").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn copies_only_the_install_state_without_content() {
+        let root = instance_fixture();
+        let (from, to) = (root.join("from"), root.join("to"));
+        assert_eq!(copy_instance_files(&from, &to, &[]).unwrap(), 4);
+        assert!(to.join("background").is_file());
+        assert!(to.join(".install.lock").is_file());
+        assert!(to.join(".install-bundle").is_file());
+        assert!(!to.join("mods").exists() && !to.join("config").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
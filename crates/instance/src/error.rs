@@ -28,4 +28,10 @@ pub enum Error {
     ),
     #[error("Invalid instance config")]
     InvalidInstanceConfig,
+    #[error("The instance name is empty")]
+    EmptyInstanceName,
+    #[error("Unknown placeholder {0}")]
+    UnknownPlaceholder(String),
+    #[error("Instance not found: {0}")]
+    InstanceNotFound(String),
 }
@@ -7,6 +7,7 @@
 use std::cmp::Ordering;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use flate2::read::GzDecoder;
 use folder::DATA_LOCATION;
@@ -19,9 +20,11 @@ use tauri::{Runtime, command};
 use uuid::Uuid;
 
 mod config;
+mod duplicate;
 mod error;
 
 pub use config::*;
+pub use duplicate::*;
 pub use error::*;
 
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
@@ -32,6 +35,7 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             cmd_get_instance_by_id,
             cmd_update_instance,
             cmd_delete_instance,
+            cmd_duplicate_instance,
             cmd_add_background_file,
             cmd_get_background_path,
             cmd_remove_background,
@@ -65,6 +69,11 @@ async fn cmd_delete_instance(id: &str) -> Result<()> {
     delete_instance(id).await
 }
 
+#[command]
+async fn cmd_duplicate_instance(id: &str, options: DuplicateOptions) -> Result<String> {
+    duplicate_instance(id, options).await
+}
+
 #[command]
 async fn cmd_add_background_file(path: String, id: &str) -> Result<()> {
     let instance_root = DATA_LOCATION.get_instance_root(id);
@@ -93,9 +102,15 @@ async fn cmd_calculate_playtime(id: &str) -> Result<u64> {
 }
 
 /// Creates a new game instance using the provided configuration.
+///
+/// Returns [`Error::EmptyInstanceName`] when the name is blank.
 pub async fn create_instance(config: InstanceConfig, id: Option<&str>) -> Result<String> {
     let random_uuid = Uuid::new_v4().to_string();
     let id = id.unwrap_or(&random_uuid);
+    if config.name.trim().is_empty() {
+        return Err(Error::EmptyInstanceName);
+    }
+    config.launch_config.validate_placeholders()?;
     let instance_root = DATA_LOCATION.get_instance_root(id);
     let config_file_path = instance_root.join("instance.toml");
     if let Some(parent) = config_file_path.parent() {
@@ -160,7 +175,7 @@ pub async fn list_instances(sort_by: SortBy) -> Result<Vec<Instance>> {
                 Err(_) => continue,
             },
             installed: async_fs::metadata(path.join(".install.lock")).await.is_ok(),
-            last_played: get_launch_script_timestamp(&instance_id),
+            last_played: get_last_played_timestamp(&instance_id),
             id: instance_id,
             has_background: path.join("background").is_file(),
         };
@@ -387,7 +402,7 @@ pub async fn get_instance_by_id(id: &str) -> Option<Instance> {
                 .await
                 .is_ok(),
             id: id.to_string(),
-            last_played: get_launch_script_timestamp(id),
+            last_played: get_last_played_timestamp(id),
             has_background: instance_root.join("background").is_file(),
         })
     } else {
@@ -398,6 +413,7 @@ pub async fn get_instance_by_id(id: &str) -> Option<Instance> {
 /// Updates the configuration file of an existing instance
 /// specified by the given UUID.
 pub async fn update_instance(config: InstanceConfig, id: &str) -> Result<()> {
+    config.launch_config.validate_placeholders()?;
     let instance_root = DATA_LOCATION.get_instance_root(id);
     let config_file = instance_root.join("instance.toml");
     async_fs::write(config_file, toml::to_string_pretty(&config)?).await?;
@@ -457,6 +473,12 @@ impl Instance {
             })
             .unwrap_or(Ok(config.runtime.minecraft.clone()))
     }
+
+    /// The folder holding the component patches of this instance, which are
+    /// merged on top of the version JSON at launch.
+    pub fn get_patches_root(&self) -> PathBuf {
+        DATA_LOCATION.get_instance_root(&self.id).join("patches")
+    }
 }
 
 pub fn calculate_playtime(instance_id: &str) -> Result<u64> {
@@ -558,6 +580,37 @@ fn parse_log_time(line: &str) -> Option<u64> {
     Some(hour * 3600 + minute * 60 + second)
 }
 
+fn get_last_played_path(instance_id: &str) -> PathBuf {
+    DATA_LOCATION
+        .get_instance_root(instance_id)
+        .join(".cache")
+        .join("last-played")
+}
+
+/// Records now as the time the instance was last played.
+pub fn record_last_played(instance_id: &str) -> Result<()> {
+    let path = get_last_played_path(instance_id);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Incorrect system time")
+        .as_millis();
+    std::fs::write(path, timestamp.to_string())?;
+    Ok(())
+}
+
+/// Returns the time the instance was last played, in milliseconds.
+fn get_last_played_timestamp(instance_id: &str) -> Option<u64> {
+    if let Ok(timestamp) = std::fs::read_to_string(get_last_played_path(instance_id)) {
+        return timestamp.trim().parse().ok();
+    }
+    get_launch_script_timestamp(instance_id)
+}
+
+/// Reads the creation time from the launch script written by older versions
+/// of the launcher.
 fn get_launch_script_timestamp(instance_id: &str) -> Option<u64> {
     #[cfg(not(target_os = "windows"))]
     let script_path = DATA_LOCATION
@@ -42,7 +42,7 @@ pub use error::{Error, Result};
 pub use models::{
     JavaArch, JavaRuntime, JavaScanResult, JavaVendor, JavaVersionGroup, ScanOptions,
 };
-pub use scanner::{scan_java_runtimes, scan_java_runtimes_with};
+pub use scanner::{inspect_java_runtime, scan_java_runtimes, scan_java_runtimes_with};
 
 /// How long a scan result is reused before the next `cmd_scan_java` rescans.
 const SCAN_CACHE_TTL: Duration = Duration::from_secs(30);
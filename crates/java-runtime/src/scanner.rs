@@ -83,6 +83,15 @@ pub fn scan_java_runtimes_with(options: &ScanOptions) -> Result<Vec<JavaRuntime>
     Ok(runtimes)
 }
 
+/// Identifies the single Java executable at `executable`, the same way a scan
+/// does.
+///
+/// Returns `None` when no version information could be obtained.
+pub fn inspect_java_runtime(executable: &Path) -> Option<JavaRuntime> {
+    let canonical = canonicalize(executable)?;
+    probe_java(&canonical, &ScanOptions::default())
+}
+
 /// Resolves the canonical (symlink-free) path of `path`.
 fn canonicalize(path: &Path) -> Option<PathBuf> {
     match fs::canonicalize(path) {
@@ -2,10 +2,11 @@
 // Copyright 2022-2026 ConicMC developers. All rights reserved.
 // SPDX-License-Identifier: GPL-3.0-only
 
-import { Config } from "@conic/config"
+import { Config, JvmPreset } from "@conic/config"
 import { DownloadState } from "@conic/download"
 import { Instance } from "@conic/instance"
 import { Channel, invoke } from "@tauri-apps/api/core"
+import { listen, type UnlistenFn } from "@tauri-apps/api/event"
 
 type LaunchProgress =
     | {
@@ -37,6 +38,11 @@ type LaunchProgress =
           job: "LogTextureLoaded"
       }
 
+export type QuickPlay =
+    | { type: "Singleplayer"; world: string }
+    | { type: "Multiplayer"; address: string }
+    | { type: "Realms"; realm_id: string }
+
 /**
  * Usage:
  * ```ts
@@ -53,23 +59,313 @@ export class LaunchTask {
     private _callbacks?: {
         onProgress?: (task: LaunchProgress) => void
     }
-    constructor(config: Config, instance: Instance, callbacks?: typeof this._callbacks) {
+    private _quickPlay?: QuickPlay
+    private _offline: boolean
+    private _profile: boolean
+    /**
+     * @param quickPlay Where the game goes after it starts, overriding the server of the instance.
+     * @param offline Launches with the cached session of the account and without downloading files.
+     * @param profile Records the game with Java Flight Recorder, summarized in a `Profiled` game event once it exits.
+     */
+    constructor(
+        config: Config,
+        instance: Instance,
+        callbacks?: typeof this._callbacks,
+        quickPlay?: QuickPlay,
+        offline = false,
+        profile = false,
+    ) {
         this._config = config
         this._instance = instance
         this._callbacks = callbacks
+        this._quickPlay = quickPlay
+        this._offline = offline
+        this._profile = profile
     }
-    async start() {
+    async start(): Promise<RunningGame> {
         const channel = new Channel<LaunchProgress>()
         channel.onmessage = (message) => {
             this._callbacks?.onProgress?.(message)
         }
-        await invoke("plugin:launch|cmd_spawn_launch_task", {
+        return await invoke("plugin:launch|cmd_spawn_launch_task", {
             config: this._config,
             instance: this._instance,
+            quickPlay: this._quickPlay ?? null,
+            offline: this._offline,
+            profile: this._profile,
             channel,
         })
     }
     async cancel() {
-        await invoke("plugin:launch|cmd_cancel_launch_task")
+        await invoke("plugin:launch|cmd_cancel_launch_task", { instanceId: this._instance.id })
+    }
+}
+
+export type RunningGame = {
+    pid: number
+    instance_id: string
+    instance_name: string
+    account: string
+    /** Unix timestamp in seconds */
+    started_at: number
+    /** The Java executable running the game */
+    java_path: string
+}
+
+export type GameExit = {
+    pid: number
+    instance_id: string
+    /** `null` when the process was terminated by a signal */
+    exit_code: number | null
+    duration_secs: number
+    /** The analysis of the crash, when the game exited with an error */
+    diagnosis: Diagnosis | null
+    /** Peak and average resource usage, `null` when the game was never sampled */
+    usage: ResourceSummary | null
+}
+
+export type ResourceSample = {
+    pid: number
+    /** Unix timestamp in milliseconds */
+    timestamp: number
+    /** Resident memory of the game and its child processes */
+    rss_bytes: number
+    /** 100 is one fully used core */
+    cpu_percent: number
+    threads: number | null
+    children: number
+    /** `null` when the heap cannot be read */
+    heap_used_bytes: number | null
+    /** `null` when the JVM picks the maximum heap size */
+    max_heap_bytes: number | null
+}
+
+export type ResourceSummary = {
+    samples: number
+    peak_rss_bytes: number
+    average_rss_bytes: number
+    peak_cpu_percent: number
+    average_cpu_percent: number
+    peak_threads: number | null
+    peak_children: number
+    peak_heap_used_bytes: number | null
+    max_heap_bytes: number | null
+}
+
+export type Finding =
+    | { kind: "WrongJavaVersion"; required: number | null; current: number | null }
+    | { kind: "MissingModDependency"; mod_id: string; dependency: string }
+    | { kind: "DuplicateMod"; mod_id: string; files: string[] }
+    | { kind: "MixinApplyFailed"; mod_id: string | null; mixin: string }
+    | { kind: "OutOfMemory" }
+    | { kind: "GraphicsDriverCrash"; library: string | null }
+    | { kind: "CorruptedJar"; file: string | null }
+
+export type Diagnosis = {
+    findings: Finding[]
+    /** The files that were analyzed */
+    sources: string[]
+    crash_report: string | null
+}
+
+export type GameEvent =
+    | {
+          type: "Started"
+          payload: RunningGame
+      }
+    | {
+          type: "Exited"
+          payload: GameExit
+      }
+    | {
+          type: "Usage"
+          payload: ResourceSample
+      }
+    | {
+          type: "HeapWarning"
+          payload: ResourceSample
+      }
+    | {
+          type: "Profiled"
+          payload: ProfileReport
+      }
+
+export async function listRunningGames(): Promise<RunningGame[]> {
+    return await invoke("plugin:launch|cmd_list_running_games")
+}
+
+export async function killGame(pid: number) {
+    await invoke("plugin:launch|cmd_kill_game", { pid })
+}
+
+export async function focusGame(pid: number) {
+    await invoke("plugin:launch|cmd_focus_game", { pid })
+}
+
+export type ScriptShell = "Sh" | "Cmd"
+
+/**
+ * Writes a script that launches the instance without the launcher.
+ * The script contains the access token of the selected account.
+ * @param shell Defaults to the shell of the current platform.
+ */
+export async function exportLaunchScript(
+    config: Config,
+    instance: Instance,
+    output: string,
+    shell?: ScriptShell,
+) {
+    await invoke("plugin:launch|cmd_export_launch_script", { config, instance, output, shell })
+}
+
+export type JavaSource =
+    | { kind: "InstanceSetting" }
+    | { kind: "MojangRuntime"; component: string }
+    | { kind: "SystemRuntime"; major_version: number }
+
+export type LaunchPlan = {
+    java_path: string
+    /** Why this Java runtime was chosen */
+    java_source: JavaSource
+    jvm_arguments: string[]
+    main_class: string
+    game_arguments: string[]
+    classpath: string[]
+    natives_directory: string
+    /** The wrapper command the Java command line is passed to */
+    wrapper: string[]
+    env: [string, string][]
+    cwd: string
+}
+
+/**
+ * Resolves the command line of the instance without launching it.
+ * The access token of the selected account is redacted.
+ */
+export async function planLaunch(config: Config, instance: Instance): Promise<LaunchPlan> {
+    return await invoke("plugin:launch|cmd_plan_launch", { config, instance })
+}
+
+export async function listJvmPresets(config: Config): Promise<JvmPreset[]> {
+    return await invoke("plugin:launch|cmd_list_jvm_presets", { config })
+}
+
+export async function importJvmPreset(json: string): Promise<JvmPreset> {
+    return await invoke("plugin:launch|cmd_import_jvm_preset", { json })
+}
+
+export type PerformanceTools = {
+    gamemode: boolean
+    mangohud: boolean
+}
+
+export async function detectPerformanceTools(): Promise<PerformanceTools> {
+    return await invoke("plugin:launch|cmd_detect_performance_tools")
+}
+
+export async function analyzeCrash(instanceId: string): Promise<Diagnosis> {
+    return await invoke("plugin:launch|cmd_analyze_crash", { instanceId })
+}
+
+export type RepairReport = {
+    /** The number of files hashed */
+    checked: number
+    /** The files that were missing or corrupted, and downloaded again */
+    repaired: string[]
+}
+
+/** Hashes every game file of the instance and downloads the missing or corrupted ones. */
+export async function repairInstance(config: Config, instance: Instance): Promise<RepairReport> {
+    return await invoke("plugin:launch|cmd_repair_instance", { config, instance })
+}
+
+export type ProfileReport = {
+    instance_id: string
+    /** The Java Flight Recorder recording */
+    recording: string
+    /** Unix timestamp in seconds */
+    created_at: number
+    duration_secs: number | null
+    gc: {
+        collections: number
+        total_pause_ms: number
+        longest_pause_ms: number
+    }
+    /** `null` when the recording has no allocation events */
+    allocation_bytes_per_sec: number | null
+    /** Hottest first */
+    hot_methods: { method: string; samples: number; percent: number }[]
+    /** Busiest first, in percent of the machine CPU */
+    threads: { name: string; user_percent: number; system_percent: number }[]
+}
+
+/** Starts recording a running game with Java Flight Recorder. The game must run on a JDK. */
+export async function startProfiling(pid: number) {
+    await invoke("plugin:launch|cmd_start_profiling", { pid })
+}
+
+/** Stops the recording of a running game and summarizes it. */
+export async function stopProfiling(pid: number): Promise<ProfileReport> {
+    return await invoke("plugin:launch|cmd_stop_profiling", { pid })
+}
+
+/** Newest first. */
+export async function listProfileReports(instanceId: string): Promise<ProfileReport[]> {
+    return await invoke("plugin:launch|cmd_list_profile_reports", { instanceId })
+}
+
+export async function onGameEvent(callback: (event: GameEvent) => void): Promise<UnlistenFn> {
+    return await listen<GameEvent>("conic-launch://game", (event) => callback(event.payload))
+}
+
+export type LogLevel = "Trace" | "Debug" | "Info" | "Warn" | "Error" | "Fatal"
+
+export type LogEvent = {
+    /** Increases by one for every event of a game */
+    sequence: number
+    /** Unix timestamp in milliseconds */
+    timestamp: number
+    level: LogLevel
+    thread: string | null
+    logger: string | null
+    message: string
+    throwable: string | null
+    stream: "Stdout" | "Stderr"
+}
+
+export type Log = LogEvent & {
+    instance_id: string
+    pid: number
+}
+
+export type LogFilter = {
+    /** Events below this level are skipped */
+    min_level?: LogLevel
+    /** Case-insensitive text searched in the message, throwable, logger and thread */
+    search?: string
+}
+
+export async function getGameLogs(pid: number, filter: LogFilter = {}): Promise<LogEvent[]> {
+    return await invoke("plugin:launch|cmd_get_game_logs", { pid, filter })
+}
+
+/**
+ * Streams the kept and all future log events of a running game to `onLog`.
+ * @returns A function that ends the subscription.
+ */
+export async function subscribeGameLogs(
+    pid: number,
+    filter: LogFilter,
+    onLog: (log: Log) => void,
+): Promise<() => Promise<void>> {
+    const channel = new Channel<Log>()
+    channel.onmessage = onLog
+    const subscription: number = await invoke("plugin:launch|cmd_subscribe_game_logs", {
+        pid,
+        filter,
+        channel,
+    })
+    return async () => {
+        await invoke("plugin:launch|cmd_unsubscribe_game_logs", { subscription })
     }
 }
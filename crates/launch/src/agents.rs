@@ -0,0 +1,182 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Java agents loaded with `-javaagent`.
+//!
+//! authlib-injector comes first when a Yggdrasil account is selected, then
+//! the agents of the global launch config and the agents of the instance,
+//! each in the order of its list.
+
+use std::path::{Path, PathBuf};
+
+use config::{
+    download::DownloadConfig,
+    launch::{JavaAgent, JavaAgentSource},
+};
+use download::{Checksum, DownloadTask, DownloadTaskType, progress::DownloadState};
+use folder::DATA_LOCATION;
+
+use crate::{complete::download_or_verify, error::*};
+
+/// Returns the jar of `agent`. Downloaded agents are stored by hash, so
+/// agents with the same file name do not overwrite each other.
+pub(crate) fn agent_jar(agent: &JavaAgent) -> PathBuf {
+    match &agent.source {
+        JavaAgentSource::Path { path } => PathBuf::from(path),
+        JavaAgentSource::Download { url } => {
+            let name = url
+                .split(['?', '#'])
+                .next()
+                .and_then(|url| url.rsplit('/').next())
+                .filter(|name| !name.is_empty())
+                .unwrap_or("agent.jar");
+            DATA_LOCATION
+                .agents
+                .join(agent.sha256.as_deref().unwrap_or_default().to_lowercase())
+                .join(name)
+        }
+    }
+}
+
+/// Returns the argument loading the agent `jar` with `options`.
+pub(crate) fn javaagent_argument(jar: &Path, options: &str) -> String {
+    if options.is_empty() {
+        format!("-javaagent:{}", jar.to_string_lossy())
+    } else {
+        format!("-javaagent:{}={options}", jar.to_string_lossy())
+    }
+}
+
+/// Returns [`Error::InvalidJavaAgent`] when `agent` is misconfigured.
+pub(crate) fn validate_agent(agent: &JavaAgent) -> Result<()> {
+    let invalid = |reason: &str| Err(Error::InvalidJavaAgent(format!("{}: {reason}", agent.name)));
+    if let Some(sha256) = &agent.sha256
+        && (sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        return invalid("the SHA-256 must be 64 hexadecimal digits");
+    }
+    if agent.options.contains(['\n', '\r', '\0']) {
+        return invalid("the options must fit on one line");
+    }
+    match &agent.source {
+        JavaAgentSource::Path { path } if path.trim().is_empty() => invalid("no jar is set"),
+        JavaAgentSource::Download { url }
+            if !url.starts_with("https://") && !url.starts_with("http://") =>
+        {
+            invalid("the download URL must be HTTP or HTTPS")
+        }
+        JavaAgentSource::Download { .. } if agent.sha256.is_none() => {
+            invalid("a SHA-256 is required to download the agent")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Validates `agents`, checks the jars on disk against their hash and
+/// downloads the missing ones. Downloaded agents are only verified when
+/// `offline`.
+pub(crate) async fn prepare_agents(
+    agents: &[JavaAgent],
+    config: &DownloadConfig,
+    offline: bool,
+) -> Result<()> {
+    let progress = DownloadState::default();
+    let mut downloads = Vec::new();
+    for agent in agents {
+        validate_agent(agent)?;
+        let task = DownloadTask {
+            url: String::new(),
+            file: agent_jar(agent),
+            size_bytes: None,
+            checksum: agent
+                .sha256
+                .as_ref()
+                .map(|sha256| Checksum::Sha256(sha256.to_lowercase()))
+                .unwrap_or(Checksum::None),
+            task_type: DownloadTaskType::Unknown,
+        };
+        match &agent.source {
+            JavaAgentSource::Path { path } => {
+                if !task.file.is_file() {
+                    return Err(Error::InvalidJavaAgent(format!(
+                        "{}: {path} does not exist",
+                        agent.name
+                    )));
+                }
+                if task.checksum != Checksum::None
+                    && !download::filter_existing_and_verified_files(vec![task], &progress)
+                        .is_empty()
+                {
+                    return Err(Error::InvalidJavaAgent(format!(
+                        "{}: {path} does not match its SHA-256",
+                        agent.name
+                    )));
+                }
+            }
+            JavaAgentSource::Download { url } => downloads.push(DownloadTask {
+                url: url.clone(),
+                ..task
+            }),
+        }
+    }
+    if !downloads.is_empty() {
+        download_or_verify(downloads, &progress, config.clone(), offline).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(source: JavaAgentSource, sha256: Option<&str>) -> JavaAgent {
+        JavaAgent {
+            name: "spark".to_string(),
+            source,
+            sha256: sha256.map(str::to_string),
+            options: String::new(),
+        }
+    }
+
+    #[test]
+    fn validates_agents() {
+        let sha256 = "a".repeat(64);
+        let download = |url: &str| JavaAgentSource::Download {
+            url: url.to_string(),
+        };
+        assert!(
+            validate_agent(&agent(
+                download("https://example.com/spark.jar"),
+                Some(&sha256)
+            ))
+            .is_ok()
+        );
+        assert!(validate_agent(&agent(download("https://example.com/spark.jar"), None)).is_err());
+        assert!(validate_agent(&agent(download("file:///spark.jar"), Some(&sha256))).is_err());
+        let path = JavaAgentSource::Path {
+            path: "/opt/spark.jar".to_string(),
+        };
+        assert!(validate_agent(&agent(path.clone(), None)).is_ok());
+        assert!(validate_agent(&agent(path, Some("abc"))).is_err());
+    }
+
+    #[test]
+    fn formats_arguments() {
+        let jar = agent_jar(&agent(
+            JavaAgentSource::Download {
+                url: "https://example.com/agents/spark.jar?version=1".to_string(),
+            },
+            Some(&"B".repeat(64)),
+        ));
+        assert!(jar.ends_with(format!("{}/spark.jar", "b".repeat(64))));
+        assert_eq!(
+            javaagent_argument(Path::new("/opt/spark.jar"), "port=8080"),
+            "-javaagent:/opt/spark.jar=port=8080"
+        );
+        assert_eq!(
+            javaagent_argument(Path::new("/opt/spark.jar"), ""),
+            "-javaagent:/opt/spark.jar"
+        );
+    }
+}
@@ -4,31 +4,60 @@
 
 use std::{
     collections::HashMap,
-    ffi::OsStr,
-    io::{self, Read},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use account::Account;
-use log::info;
+use log::warn;
 use regex::Regex;
-use zip::ZipArchive;
+use serde::Serialize;
 
 use config::launch::GC;
 use folder::DATA_LOCATION;
 use folder::MinecraftLocation;
+use install::vanilla::{get_game_assets_directory, read_asset_layout};
 use instance::Instance;
 use platform::PLATFORM_INFO;
 use platform::{DELIMITER, OsFamily};
 use shared::APP_VERSION;
 use version::{ResolvedLibrary, ResolvedVersion};
 
+use super::agents::{agent_jar, javaagent_argument};
 use super::error::*;
+use super::natives_cache::extract_natives;
 use super::options::LaunchOptions;
+use super::placeholders::expand_shell_words;
+use super::profiling::flight_recording_argument;
+use super::quick_play::get_quick_play_log_path;
 
 const DEFAULT_GAME_ICON: &[u8] = include_bytes!("./minecraft.icns");
 
-/// Generates the full list of command-line arguments to launch Minecraft.
+/// The command line of the game, split into its parts.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LaunchArguments {
+    pub jvm: Vec<String>,
+    pub main_class: String,
+    pub game: Vec<String>,
+    /// The entries of the classpath, which is also passed in `jvm`.
+    pub classpath: Vec<String>,
+}
+
+impl LaunchArguments {
+    /// Joins the parts into the arguments passed to the Java executable.
+    pub fn into_command_line(self) -> Vec<String> {
+        let mut command_line = self.jvm;
+        command_line.push(self.main_class);
+        command_line.extend(self.game);
+        command_line
+    }
+}
+
+/// Generates the command-line arguments to launch Minecraft.
+///
+/// This does not touch the network or write any file, see
+/// [`prepare_launch_files`] for the files the arguments refer to. Window size,
+/// demo mode and Quick Play come from the version JSON, so `version` must be
+/// resolved with [`LaunchOptions::enabled_features`].
 ///
 /// # Arguments
 ///
@@ -36,16 +65,12 @@ const DEFAULT_GAME_ICON: &[u8] = include_bytes!("./minecraft.icns");
 /// * `instance` - The game instance configuration and info.
 /// * `launch_options` - User specified launch options and settings.
 /// * `version` - The resolved Minecraft version data.
-///
-/// # Returns
-///
-/// A vector of strings representing the full command-line arguments to pass to the Java launcher.
 pub async fn generate_command_arguments(
     minecraft_location: &MinecraftLocation,
     instance: &Instance,
     launch_options: &LaunchOptions,
     version: &ResolvedVersion,
-) -> Result<Vec<String>> {
+) -> Result<LaunchArguments> {
     let mut command_arguments = Vec::new();
 
     command_arguments.push(format!(
@@ -54,21 +79,11 @@ pub async fn generate_command_arguments(
             .get_version_jar(&instance.config.runtime.minecraft, None)
             .to_string_lossy()
     ));
-    let game_icon = minecraft_location
-        .assets
-        .join("minecraft.icns")
-        .to_string_lossy()
-        .to_string();
-    async_fs::write(&game_icon, DEFAULT_GAME_ICON).await?;
     if PLATFORM_INFO.os_family == OsFamily::Macos {
         command_arguments.push("-Xdock:name=Minecraft".to_string());
         command_arguments.push(format!(
-            "-Xdock:icon={game_icon}",
-            game_icon = if game_icon.contains(" ") {
-                format!("\"{game_icon}\"")
-            } else {
-                game_icon
-            }
+            "-Xdock:icon={}",
+            get_game_icon_path(minecraft_location).to_string_lossy()
         ));
     }
     if launch_options.xmn_memory > 0 {
@@ -83,75 +98,61 @@ pub async fn generate_command_arguments(
     if launch_options.ignore_patch_discrepancies {
         command_arguments.push("-Dfml.ignorePatchDiscrepancies=true".to_string());
     }
-    match launch_options.gc {
-        GC::G1 => {
-            command_arguments.extend([
-                "-XX:+UseG1GC".to_string(),
-                "-XX:+UnlockExperimentalVMOptions".to_string(),
-                "-XX:G1NewSizePercent=20".to_string(),
-                "-XX:G1ReservePercent=20".to_string(),
-                "-XX:MaxGCPauseMillis=50".to_string(),
-                "-XX:G1HeapRegionSize=16M".to_string(),
-            ]);
-        }
-        GC::Parallel => {
-            command_arguments.extend([
-                "-XX:+UseParallelGC".to_string(),
-                format!(
-                    "-XX:ParallelGCThreads={num}",
-                    num = num_cpus::get_physical()
-                ),
-            ]);
-        }
-        GC::Serial => {
-            command_arguments.push("-XX:+UseSerialGC".to_string());
-        }
-        GC::Z => {
-            command_arguments.push("-XX:+UseZGC".to_string());
+    if let Some(flags) = &launch_options.jvm_preset_flags {
+        command_arguments.extend(flags.iter().cloned());
+    } else {
+        match launch_options.gc {
+            GC::G1 => {
+                command_arguments.extend([
+                    "-XX:+UseG1GC".to_string(),
+                    "-XX:+UnlockExperimentalVMOptions".to_string(),
+                    "-XX:G1NewSizePercent=20".to_string(),
+                    "-XX:G1ReservePercent=20".to_string(),
+                    "-XX:MaxGCPauseMillis=50".to_string(),
+                    "-XX:G1HeapRegionSize=16M".to_string(),
+                ]);
+            }
+            GC::Parallel => {
+                command_arguments.extend([
+                    "-XX:+UseParallelGC".to_string(),
+                    format!(
+                        "-XX:ParallelGCThreads={num}",
+                        num = num_cpus::get_physical()
+                    ),
+                ]);
+            }
+            GC::Serial => {
+                command_arguments.push("-XX:+UseSerialGC".to_string());
+            }
+            GC::Z => {
+                command_arguments.push("-XX:+UseZGC".to_string());
+            }
         }
     }
     if let Account::Yggdrasil(yggdrasil_account) = &launch_options.selected_account {
-        let authlib_injector_path = DATA_LOCATION.clone().authlib_injector;
-        command_arguments.push(format!(
-            "-javaagent:{jar}={server}",
-            jar = authlib_injector_path.to_string_lossy(),
-            server = yggdrasil_account.api_root
+        command_arguments.push(javaagent_argument(
+            &DATA_LOCATION.authlib_injector,
+            &yggdrasil_account.api_root,
         ));
         command_arguments.push("-Dauthlibinjector.side=client".to_string());
-        if let Ok(prefetched_yggdrasil_server_metadata) =
-            account::yggdrasil::yggdrasil_server::get_server_info_base64(
-                &yggdrasil_account.api_root,
-            )
-            .await
-        {
+        if let Some(prefetched) = &launch_options.yggdrasil_prefetched {
             command_arguments.push(format!(
-                "-Dauthlibinjector.yggdrasil.prefetched={prefetched_yggdrasil_server_metadata}"
+                "-Dauthlibinjector.yggdrasil.prefetched={prefetched}"
             ));
         }
     }
-    let mut jvm_options: HashMap<&str, String> = HashMap::new();
-    jvm_options.insert(
-        "natives_directory",
-        minecraft_location
-            .get_natives_root(&version.id)
-            .to_string_lossy()
-            .to_string(),
-    );
-    jvm_options.insert("launcher_name", launch_options.launcher_name.clone());
-    jvm_options.insert("launcher_version", APP_VERSION.to_string());
-    jvm_options.insert(
-        "classpath",
-        resolve_classpath(
-            version,
-            minecraft_location,
-            launch_options.extra_class_paths.clone(),
-        ),
-    );
-    jvm_options.insert("version_name", version.id.clone());
-    jvm_options.insert(
-        "library_directory",
-        DATA_LOCATION.root.join("libraries").display().to_string(),
+    for agent in &launch_options.java_agents {
+        command_arguments.push(javaagent_argument(&agent_jar(agent), &agent.options));
+    }
+    if let Some(recording) = &launch_options.profile_recording {
+        command_arguments.push(flight_recording_argument(recording));
+    }
+    let classpath = resolve_classpath(
+        version,
+        minecraft_location,
+        launch_options.extra_class_paths.clone(),
     );
+    let jvm_options = jvm_placeholders(minecraft_location, launch_options, version, &classpath);
     let mut jvm_arguments = Vec::with_capacity(version.jvm_arguments.len() + 1);
     let log_config_path = minecraft_location.get_log_config(&version.id);
     if let Some(client) = version.logging.get("client")
@@ -161,19 +162,109 @@ pub async fn generate_command_arguments(
         jvm_arguments.push(argument.replace("${path}", log_config_path.to_string_lossy().as_ref()));
     }
     jvm_arguments.extend(version.jvm_arguments.clone());
-    command_arguments.push(launch_options.extra_jvm_args.clone());
+    let mut user_jvm_options = jvm_options.clone();
+    user_jvm_options.extend(launch_options.placeholders.clone());
+    command_arguments.extend(expand_shell_words(
+        &launch_options.extra_jvm_args,
+        &user_jvm_options,
+    )?);
     command_arguments.extend(
         jvm_arguments
             .iter()
             .map(|arg| format(arg, jvm_options.clone())),
     );
-    command_arguments.push(
+    let main_class = version
+        .main_class
+        .clone()
+        .unwrap_or("net.minecraft.client.main.Main".to_string());
+    let mut game_arguments = Vec::new();
+    let mut game_options =
+        game_placeholders(minecraft_location, instance, launch_options, version).await?;
+    let quick_play = launch_options.quick_play.as_ref();
+    let quick_play_supported =
+        quick_play.is_some_and(|quick_play| quick_play.is_supported_by(version));
+    if let Some(quick_play) = quick_play
+        && quick_play_supported
+    {
+        let (placeholder, value) = quick_play.placeholder();
+        game_options.insert(placeholder, value.to_string());
+        game_options.insert(
+            "quickPlayPath",
+            get_quick_play_log_path(&instance.id)
+                .to_string_lossy()
+                .to_string(),
+        );
+    }
+    game_arguments.extend(
         version
-            .main_class
-            .clone()
-            .unwrap_or("net.minecraft.client.main.Main".to_string()),
+            .game_arguments
+            .iter()
+            .map(|arg| format(arg, game_options.clone())),
     );
-    let mut game_options: HashMap<&str, String> = HashMap::with_capacity(13);
+    let mut user_game_options = game_options.clone();
+    user_game_options.extend(launch_options.placeholders.clone());
+    game_arguments.extend(expand_shell_words(
+        &launch_options.extra_mc_args,
+        &user_game_options,
+    )?);
+    if let Some(quick_play) = quick_play
+        && !quick_play_supported
+    {
+        match quick_play.legacy_arguments() {
+            Some(arguments) => game_arguments.extend(arguments),
+            None => warn!(
+                "{} does not support Quick Play, ignoring {quick_play:?}",
+                version.id
+            ),
+        }
+    }
+    if launch_options.fullscreen {
+        game_arguments.push("--fullscreen".to_string());
+    }
+    Ok(LaunchArguments {
+        jvm: command_arguments,
+        main_class,
+        game: game_arguments,
+        classpath,
+    })
+}
+
+/// Returns the values of [`config::launch::JVM_ARGUMENT_PLACEHOLDERS`] for the JVM arguments
+/// of `version`.
+fn jvm_placeholders(
+    minecraft_location: &MinecraftLocation,
+    launch_options: &LaunchOptions,
+    version: &ResolvedVersion,
+    classpath: &[String],
+) -> HashMap<&'static str, String> {
+    HashMap::from([
+        (
+            "natives_directory",
+            get_natives_directory(minecraft_location, launch_options, version)
+                .to_string_lossy()
+                .to_string(),
+        ),
+        ("launcher_name", launch_options.launcher_name.clone()),
+        ("launcher_version", APP_VERSION.to_string()),
+        ("classpath", classpath.join(DELIMITER)),
+        ("classpath_separator", DELIMITER.to_string()),
+        ("version_name", version.id.clone()),
+        (
+            "library_directory",
+            minecraft_location.libraries.to_string_lossy().to_string(),
+        ),
+    ])
+}
+
+/// Returns the values of [`config::launch::GAME_ARGUMENT_PLACEHOLDERS`] for the game arguments
+/// of `version`, without the placeholders of Quick Play.
+async fn game_placeholders(
+    minecraft_location: &MinecraftLocation,
+    instance: &Instance,
+    launch_options: &LaunchOptions,
+    version: &ResolvedVersion,
+) -> Result<HashMap<&'static str, String>> {
+    let mut game_options: HashMap<&'static str, String> = HashMap::with_capacity(15);
     let assets_dir = minecraft_location.assets.clone();
     game_options.insert("version_name", version.id.clone());
     game_options.insert(
@@ -184,18 +275,21 @@ pub async fn generate_command_arguments(
             .unwrap_or("unknown".to_string()),
     );
     game_options.insert("assets_root", assets_dir.to_string_lossy().to_string());
+    let assets_index_name = version
+        .assets
+        .as_ref()
+        .ok_or(Error::InvalidVersionJson("assets".to_string()))?;
+    let asset_layout = read_asset_layout(minecraft_location, assets_index_name).await;
     game_options.insert(
         "game_assets",
-        assets_dir
-            .join("virtual")
-            .join(
-                version
-                    .assets
-                    .as_ref()
-                    .ok_or(Error::InvalidVersionJson("assets".to_string()))?,
-            )
-            .to_string_lossy()
-            .to_string(),
+        get_game_assets_directory(
+            minecraft_location,
+            assets_index_name,
+            asset_layout,
+            &DATA_LOCATION.get_instance_root(&instance.id),
+        )
+        .to_string_lossy()
+        .to_string(),
     );
     game_options.insert(
         "asset_index",
@@ -239,73 +333,30 @@ pub async fn generate_command_arguments(
     game_options.insert("user_type", launch_options.selected_account.get_user_type());
     game_options.insert("resolution_width", launch_options.width.to_string());
     game_options.insert("resolution_height", launch_options.height.to_string());
-    command_arguments.extend(
-        version
-            .game_arguments
-            .iter()
-            .map(|arg| format(arg, game_options.clone())),
-    );
-    command_arguments.push(launch_options.extra_mc_args.clone());
-    if let Some(server) = launch_options.server.clone() {
-        command_arguments.extend(vec!["--server".to_string(), server.ip]);
-        if let Some(port) = server.port {
-            command_arguments.extend(vec!["--port".to_string(), port.to_string()])
-        }
-    }
-    if launch_options.fullscreen {
-        command_arguments.push("--fullscreen".to_string());
-    }
-    let no_width_arguments = !command_arguments
-        .iter()
-        .any(|v| v == &"--width".to_string());
-    if no_width_arguments && !launch_options.fullscreen {
-        command_arguments.extend(vec![
-            "--width".to_string(),
-            launch_options.width.to_string(),
-            "--height".to_string(),
-            launch_options.height.to_string(),
-        ]);
-    }
-    if launch_options.is_demo {
-        command_arguments.push("--demo".to_string());
-    };
-    Ok(command_arguments)
+    Ok(game_options)
 }
 
-/// Resolves the classpath string needed for the Java launch command.
+/// Resolves the classpath entries needed for the Java launch command.
 ///
-/// This includes library paths (unzipping native libraries if needed),
-/// extra classpaths, and the version jar or inheritance jars.
+/// This includes library paths, extra classpaths, and the version jar or
+/// inheritance jars. Native libraries are not part of the classpath, they are
+/// extracted by [`prepare_launch_files`].
 ///
 /// # Arguments
 ///
 /// * `version` - The resolved Minecraft version metadata.
 /// * `minecraft` - Reference to MinecraftLocation for path resolving.
 /// * `extra_class_paths` - Additional class paths as a string.
-///
-/// # Returns
-///
-/// A string with the complete classpath, joined by platform-specific delimiter.
 fn resolve_classpath(
     version: &ResolvedVersion,
     minecraft: &MinecraftLocation,
     extra_class_paths: String,
-) -> String {
+) -> Vec<String> {
     let mut classpath = version
         .libraries
         .iter()
         .filter_map(|lib| match lib {
-            ResolvedLibrary::Native(native_library) => {
-                let path = minecraft.get_library_by_path(&native_library.path);
-                let native_folder = minecraft.get_natives_root(&version.id);
-                info!("Unzip native library {path:#?} to {native_folder:#?}");
-                if let Ok(file) = std::fs::File::open(path)
-                    && let Ok(mut zip_archive) = ZipArchive::new(file)
-                {
-                    decompression_all(&mut zip_archive, &native_folder).unwrap_or(());
-                }
-                None
-            }
+            ResolvedLibrary::Native(_) => None,
             ResolvedLibrary::Common(common_library) => Some(
                 minecraft
                     .get_library_by_path(common_library.path.clone())
@@ -335,7 +386,40 @@ fn resolve_classpath(
         );
     }
 
-    classpath.join(DELIMITER)
+    classpath
+}
+
+fn get_game_icon_path(minecraft: &MinecraftLocation) -> PathBuf {
+    minecraft.assets.join("minecraft.icns")
+}
+
+/// Returns the directory the natives are loaded from.
+pub(crate) fn get_natives_directory(
+    minecraft: &MinecraftLocation,
+    launch_options: &LaunchOptions,
+    version: &ResolvedVersion,
+) -> PathBuf {
+    launch_options
+        .natives_directory
+        .clone()
+        .unwrap_or_else(|| minecraft.get_natives_root(&version.id))
+}
+
+/// Writes the files the arguments of [`generate_command_arguments`] refer to:
+/// the game icon and the extracted native libraries, which are reused while
+/// intact. Returns the directory the natives are in, to be set as the
+/// `natives_directory` of the launch options before generating the arguments.
+pub async fn prepare_launch_files(
+    minecraft: &MinecraftLocation,
+    launch_options: &LaunchOptions,
+    version: &ResolvedVersion,
+) -> Result<PathBuf> {
+    async_fs::create_dir_all(&minecraft.assets).await?;
+    async_fs::write(get_game_icon_path(minecraft), DEFAULT_GAME_ICON).await?;
+    let directory = get_natives_directory(minecraft, launch_options, version);
+    let minecraft = minecraft.clone();
+    let version = version.clone();
+    tokio::task::spawn_blocking(move || extract_natives(&minecraft, &version, &directory)).await?
 }
 
 fn format(template: &str, args: HashMap<&str, String>) -> String {
@@ -348,25 +432,200 @@ fn format(template: &str, args: HashMap<&str, String>) -> String {
         .to_string()
 }
 
-fn decompression_all<R: Read + io::Seek, S: AsRef<OsStr> + ?Sized>(
-    zip_archive: &mut ZipArchive<R>,
-    to: &S,
-) -> Result<()> {
-    let to = Path::new(to).to_path_buf();
-    for i in 0..zip_archive.len() {
-        let mut zip_file = zip_archive.by_index(i)?;
-        let name = zip_file.name().to_string();
-        let path = to.join(&name);
-        let mut entry_content = vec![];
-        zip_file.read_to_end(&mut entry_content)?;
-        if zip_file.is_dir() {
-            std::fs::create_dir_all(zip_file.name())?;
-            continue;
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::{collections::BTreeSet, str::FromStr};
+
+    use account::offline::OfflineAccount;
+    use config::launch::{GAME_ARGUMENT_PLACEHOLDERS, JVM_ARGUMENT_PLACEHOLDERS};
+    use instance::{InstanceConfig, ModLoaderType};
+    use uuid::Uuid;
+    use version::{Version, resolve_version};
+
+    use super::*;
+    use crate::quick_play::QuickPlay;
+
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/fixtures")
+    }
+
+    fn launch_options() -> LaunchOptions {
+        LaunchOptions {
+            selected_account: Account::Offline(OfflineAccount {
+                name: "Steve".to_string(),
+                uuid: Uuid::parse_str("8667ba71-b85a-4004-af54-457a9734eed7").unwrap(),
+                skin: None,
+            }),
+            auto_memory: false,
+            max_memory: 4096,
+            launcher_name: "Conic".to_string(),
+            hook_timeout_secs: 0,
+            legacy_proxy: false,
+            ..Default::default()
         }
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
+    }
+
+    /// Renders the arguments one per line, with the machine-specific paths
+    /// and the launcher version replaced by placeholders.
+    fn render(arguments: &LaunchArguments) -> String {
+        let fixtures = fixtures().to_string_lossy().to_string();
+        let data = DATA_LOCATION.root.to_string_lossy().to_string();
+        let launcher_version = format!("launcher.version={APP_VERSION}");
+        let main_class = [arguments.main_class.clone()];
+        let mut snapshot = String::new();
+        for (section, values) in [
+            ("jvm", arguments.jvm.as_slice()),
+            ("main_class", main_class.as_slice()),
+            ("game", arguments.game.as_slice()),
+            ("classpath", arguments.classpath.as_slice()),
+        ] {
+            snapshot.push_str(&format!("[{section}]\n"));
+            for value in values {
+                let value = value
+                    .replace(&fixtures, "$MINECRAFT")
+                    .replace(&data, "$DATA")
+                    .replace(&launcher_version, "launcher.version=$VERSION");
+                snapshot.push_str(&format!("{value:?}\n"));
+            }
         }
-        std::fs::write(path, entry_content)?;
+        snapshot
+    }
+
+    /// Resolves the fixture version of an instance running `minecraft` with
+    /// `loader`.
+    async fn resolve_fixture(
+        minecraft: &str,
+        loader: Option<(ModLoaderType, &str)>,
+        enabled_features: &[String],
+    ) -> (Instance, ResolvedVersion) {
+        let location = MinecraftLocation::new(&fixtures());
+        let mut config = InstanceConfig::new("Snapshot", minecraft);
+        if let Some((loader_type, loader_version)) = loader {
+            config.runtime.mod_loader_type = Some(loader_type);
+            config.runtime.mod_loader_version = Some(loader_version.to_string());
+        }
+        let instance = Instance {
+            config,
+            installed: true,
+            id: "snapshot".to_string(),
+            last_played: None,
+            has_background: false,
+        };
+        let version_id = instance.get_version_id().unwrap();
+        let raw = std::fs::read_to_string(location.get_version_json(&version_id)).unwrap();
+        let version = resolve_version(
+            &Version::from_str(&raw).unwrap(),
+            &location,
+            enabled_features,
+        )
+        .await
+        .unwrap();
+        (instance, version)
+    }
+
+    /// Compares the arguments of the fixture version with
+    /// `fixtures/snapshots/<name>.txt`. Set `UPDATE_SNAPSHOTS` to rewrite the
+    /// snapshot instead.
+    async fn assert_snapshot(name: &str, minecraft: &str, loader: Option<(ModLoaderType, &str)>) {
+        let (instance, version) =
+            resolve_fixture(minecraft, loader, &launch_options().enabled_features()).await;
+        let location = MinecraftLocation::new(&fixtures());
+        let arguments =
+            generate_command_arguments(&location, &instance, &launch_options(), &version)
+                .await
+                .unwrap();
+        let actual = render(&arguments);
+        let path = fixtures().join("snapshots").join(format!("{name}.txt"));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            actual, expected,
+            "arguments of {name} changed, rerun with UPDATE_SNAPSHOTS=1 to accept them"
+        );
+    }
+
+    #[tokio::test]
+    async fn vanilla_arguments() {
+        assert_snapshot("vanilla-1.20.1", "1.20.1", None).await;
+    }
+
+    #[tokio::test]
+    async fn fabric_arguments() {
+        assert_snapshot(
+            "fabric-0.15.11-1.20.1",
+            "1.20.1",
+            Some((ModLoaderType::Fabric, "0.15.11")),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn forge_arguments() {
+        assert_snapshot(
+            "forge-47.2.0-1.20.1",
+            "1.20.1",
+            Some((ModLoaderType::Forge, "47.2.0")),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn neoforge_arguments() {
+        assert_snapshot(
+            "neoforge-21.1.77-1.21.1",
+            "1.21.1",
+            Some((ModLoaderType::Neoforge, "21.1.77")),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn quick_play_arguments() {
+        let quick_play = QuickPlay::Multiplayer {
+            address: "mc.example.com:25566".to_string(),
+        };
+        let mut launch_options = launch_options();
+        launch_options.quick_play = Some(quick_play);
+        let (instance, version) =
+            resolve_fixture("1.21.1", None, &launch_options.enabled_features()).await;
+        let location = MinecraftLocation::new(&fixtures());
+        let arguments = generate_command_arguments(&location, &instance, &launch_options, &version)
+            .await
+            .unwrap();
+        let game = arguments.game;
+        assert!(
+            game.windows(2)
+                .any(|pair| pair == ["--quickPlayMultiplayer", "mc.example.com:25566"])
+        );
+        assert!(game.iter().any(|argument| argument == "--quickPlayPath"));
+        assert!(
+            !game
+                .iter()
+                .any(|argument| argument == "--server" || argument == "--quickPlaySingleplayer")
+        );
+    }
+
+    #[tokio::test]
+    async fn placeholders_match_the_accepted_names() {
+        let (instance, version) = resolve_fixture("1.20.1", None, &[]).await;
+        let location = MinecraftLocation::new(&fixtures());
+        let keys =
+            |values: HashMap<&'static str, String>| values.into_keys().collect::<BTreeSet<_>>();
+        assert_eq!(
+            keys(jvm_placeholders(
+                &location,
+                &launch_options(),
+                &version,
+                &[]
+            )),
+            BTreeSet::from(JVM_ARGUMENT_PLACEHOLDERS)
+        );
+        let game = game_placeholders(&location, &instance, &launch_options(), &version)
+            .await
+            .unwrap();
+        assert_eq!(keys(game), BTreeSet::from(GAME_ARGUMENT_PLACEHOLDERS));
     }
-    Ok(())
 }
@@ -0,0 +1,322 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The command line of the game process.
+//!
+//! The game is spawned directly from an argument vector, so arguments are
+//! never interpreted by a shell. Launch scripts are only written on request,
+//! with arguments escaped for the target shell.
+
+use std::{
+    path::PathBuf,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::*;
+
+/// The program, arguments, environment and working directory of the game.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GameCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Variables set in addition to the environment of the launcher.
+    pub env: Vec<(String, String)>,
+    pub cwd: PathBuf,
+}
+
+impl GameCommand {
+    /// Creates the command running `java_path` with `java_args` in `cwd`.
+    pub fn new(java_path: PathBuf, java_args: Vec<String>, cwd: PathBuf) -> Self {
+        Self {
+            program: java_path,
+            args: java_args,
+            env: Vec::new(),
+            cwd,
+        }
+    }
+
+    /// Runs the command through `prefix`, a program followed by its
+    /// arguments.
+    pub fn prepend(&mut self, prefix: Vec<String>) {
+        let mut prefix = prefix.into_iter();
+        let Some(program) = prefix.next() else {
+            return;
+        };
+        let mut args: Vec<String> = prefix.collect();
+        args.push(self.program.to_string_lossy().to_string());
+        args.append(&mut self.args);
+        self.program = PathBuf::from(program);
+        self.args = args;
+    }
+
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .current_dir(&self.cwd);
+        #[cfg(target_os = "windows")]
+        command.creation_flags(0x08000000);
+        command
+    }
+
+    /// Renders the command as a script for `shell`.
+    pub fn to_script(&self, shell: ScriptShell) -> String {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Incorrect system time")
+            .as_millis();
+        let command_line = std::iter::once(self.program.to_string_lossy().to_string())
+            .chain(self.args.iter().cloned())
+            .map(|arg| shell.quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut script = String::new();
+        match shell {
+            ScriptShell::Sh => {
+                script.push_str("#!/bin/sh\n");
+                script.push_str("# This file is generated by Conic Launcher.\n");
+                script.push_str(&format!("# Exported at {timestamp}.\n\n"));
+                script.push_str(&format!("cd {} || exit 1\n", shell.quote_path(&self.cwd)));
+                for (key, value) in &self.env {
+                    script.push_str(&format!("export {key}={}\n", shell.quote(value)));
+                }
+                script.push_str(&format!("exec {command_line}\n"));
+            }
+            ScriptShell::Cmd => {
+                script.push_str("@echo off\r\n");
+                script.push_str(":: This file is generated by Conic Launcher.\r\n");
+                script.push_str(&format!(":: Exported at {timestamp}.\r\n\r\n"));
+                script.push_str(&format!("cd /d {}\r\n", shell.quote_path(&self.cwd)));
+                for (key, value) in &self.env {
+                    script.push_str(&format!(
+                        "set {}\r\n",
+                        escape_cmd(&format!("\"{key}={value}\""))
+                    ));
+                }
+                script.push_str(&format!("{command_line}\r\n"));
+            }
+        }
+        script
+    }
+}
+
+/// The shell a launch script is exported for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ScriptShell {
+    /// POSIX `sh`.
+    Sh,
+    /// A Windows batch file.
+    Cmd,
+}
+
+impl ScriptShell {
+    /// The shell of the current platform.
+    pub fn native() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Cmd
+        } else {
+            Self::Sh
+        }
+    }
+
+    /// Escapes `arg`, so the shell passes it to the program unchanged.
+    pub fn quote(self, arg: &str) -> String {
+        match self {
+            Self::Sh => quote_sh(arg),
+            Self::Cmd => quote_cmd(arg),
+        }
+    }
+
+    fn quote_path(self, path: &std::path::Path) -> String {
+        self.quote(&path.to_string_lossy())
+    }
+}
+
+fn is_safe_word(arg: &str, extra: &str) -> bool {
+    !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=+,@".contains(c) || extra.contains(c))
+}
+
+fn quote_sh(arg: &str) -> String {
+    if is_safe_word(arg, "%") {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Quotes an argument for the Microsoft C runtime argument parser used by
+/// Java, then escapes it for batch files with [`escape_cmd`].
+fn quote_cmd(arg: &str) -> String {
+    if is_safe_word(arg, "\\") {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                backslashes = 0;
+                quoted.push(c);
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    escape_cmd(&quoted)
+}
+
+/// Escapes `%` in `text` for batch files, and caret-escapes the metacharacters
+/// of cmd outside of double quotes. cmd toggles its quote state on every `"`,
+/// even one escaped for the C runtime, so an argument with an odd number of
+/// quotes cannot run the rest of the line as another command.
+fn escape_cmd(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut in_quotes = false;
+    for c in text.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '%' => escaped.push('%'),
+            '^' | '&' | '|' | '<' | '>' | '(' | ')' if !in_quotes => escaped.push('^'),
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Splits `input` into words like a POSIX shell, without expanding anything.
+///
+/// Supports single quotes, double quotes and backslash escapes. Returns
+/// [`Error::InvalidShellWords`] when a quote is not closed.
+pub fn split_shell_words(input: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(Error::InvalidShellWords(input.to_string())),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(Error::InvalidShellWords(input.to_string())),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(Error::InvalidShellWords(input.to_string())),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some('\n') => {}
+                    Some(c) => word.push(c),
+                    None => word.push('\\'),
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_shell_words() {
+        assert_eq!(
+            split_shell_words(r#"  prime-run  "a b" 'c "d"' e\ f "g\"h" ''"#).unwrap(),
+            vec!["prime-run", "a b", r#"c "d""#, "e f", r#"g"h"#, ""]
+        );
+        assert!(split_shell_words("gamemoderun \"unclosed").is_err());
+        assert!(split_shell_words("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn wraps_java_command() {
+        let mut command = GameCommand::new(
+            PathBuf::from("/usr/bin/java"),
+            vec!["-Xmx2G".to_string()],
+            PathBuf::from("/tmp"),
+        );
+        command.prepend(Vec::new());
+        assert_eq!(command.program, PathBuf::from("/usr/bin/java"));
+
+        command.prepend(split_shell_words("env 'A=1 2' mangohud").unwrap());
+        assert_eq!(command.program, PathBuf::from("env"));
+        assert_eq!(
+            command.args,
+            vec!["A=1 2", "mangohud", "/usr/bin/java", "-Xmx2G"]
+        );
+
+        command.prepend(vec!["gamemoderun".to_string()]);
+        assert_eq!(command.program, PathBuf::from("gamemoderun"));
+        assert_eq!(command.args[0], "env");
+    }
+
+    #[test]
+    fn quotes_for_shells() {
+        assert_eq!(quote_sh("-Xmx2G"), "-Xmx2G");
+        assert_eq!(quote_sh("it's $HOME"), r#"'it'\''s $HOME'"#);
+        assert_eq!(quote_sh(""), "''");
+        assert_eq!(quote_cmd(r"C:\Games\java.exe"), r"C:\Games\java.exe");
+        assert_eq!(
+            quote_cmd(r#"C:\My Games\ "100%" & more\"#),
+            r#""C:\My Games\ \"100%%\" & more\\""#
+        );
+        assert_eq!(
+            quote_cmd(r#"-Dname=a"b & echo pwned"#),
+            r#""-Dname=a\"b ^& echo pwned""#
+        );
+        assert_eq!(escape_cmd(r#""A=x"&calc""#), r#""A=x"^&calc""#);
+    }
+}
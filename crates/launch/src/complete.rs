@@ -2,134 +2,150 @@
 // Copyright 2022-2026 ConicMC developers. All rights reserved.
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::{
-    path::PathBuf,
-    str::FromStr,
-    time::{SystemTime, UNIX_EPOCH},
-};
-
 use config::download::DownloadConfig;
-use log::info;
+use log::{info, warn};
 
-use download::progress::DownloadState;
-use folder::{DATA_LOCATION, MinecraftLocation};
-use install::vanilla::{generate_assets_downloads, generate_libraries_downloads};
+use download::{Checksum, DownloadTask, progress::DownloadState};
+use folder::MinecraftLocation;
+use install::{
+    bundle::open_instance_bundle_source,
+    vanilla::{generate_assets_downloads, generate_libraries_downloads},
+};
 use instance::Instance;
-use version::{Version, resolve_version};
+use serde::Serialize;
 
-use crate::error::*;
+use crate::{
+    error::*, integrity::IntegrityManifest, offline::verify_local_files, resolve_instance_version,
+};
+
+/// The outcome of [`repair_files`].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RepairReport {
+    /// The number of files hashed.
+    pub checked: usize,
+    /// The files that were missing or corrupted, and downloaded again.
+    pub repaired: Vec<String>,
+}
 
 /// Completes and verifies all assets and libraries files for the given instance and Minecraft location.
 ///
-/// This function checks if lock files exist to skip redundant verification. If lock files are missing,
-/// it will verify and download missing or corrupted assets and libraries, then create the lock files.
-/// > NOTE: If game crashed, the lock file should be delete!
+/// Only the files changed since the last verification are hashed, as told by
+/// the integrity manifest of the instance. Missing or corrupted files are
+/// downloaded again, then the manifest is updated.
 ///
 /// # Arguments
 ///
 /// * `instance` - The Minecraft instance whose files to verify.
 /// * `minecraft_location` - The Minecraft location to resolve file paths.
+/// * `offline` - Only verifies the files against their checksums, failing
+///   with [`Error::MissingFiles`] instead of downloading them.
 pub async fn complete_files(
     instance: &Instance,
     minecraft_location: &MinecraftLocation,
     progress: DownloadState,
     config: &DownloadConfig,
+    offline: bool,
 ) -> Result<()> {
-    let assets_lock_file = DATA_LOCATION
-        .get_instance_root(&instance.id)
-        .join(".conic-assets-ok");
-    let libraries_lock_file = DATA_LOCATION
-        .get_instance_root(&instance.id)
-        .join(".conic-libraries-ok");
-    if try_load_lock_file(&assets_lock_file).await.is_some() {
-        info!("Found file \".conic-assets-ok\", no need to check assets files.");
-    } else {
-        info!("Checking and completing assets files");
-        complete_assets_files(
-            instance,
-            minecraft_location,
-            progress.clone(),
-            config.clone(),
-        )
-        .await?;
-        info!("Saving assets lock file");
-        let _ = save_lock_file(&assets_lock_file).await;
+    let (version_id, tasks) = game_files(instance, minecraft_location, offline).await?;
+    let mut manifest = IntegrityManifest::load(&instance.id, &version_id);
+    let (unchanged, changed) = manifest.partition(tasks);
+    if changed.is_empty() {
+        info!("All {} game files are unchanged", unchanged.len());
+        return Ok(());
     }
-    if try_load_lock_file(&libraries_lock_file).await.is_some() {
-        info!("Found file \".conic-libraries-ok\", no need to check libraries files.");
-    } else {
-        info!("Checking and completing libraries files");
-        complete_libraries_files(instance, minecraft_location, progress, config.clone()).await?;
-        info!("Saving libraries lock file");
-        let _ = save_lock_file(&libraries_lock_file).await;
+    info!(
+        "Verifying {} changed game files, {} unchanged",
+        changed.len(),
+        unchanged.len()
+    );
+    // The bundle is only opened for missing files, changed ones are hashed by
+    // the download step anyway.
+    let (missing, mut remaining): (Vec<_>, Vec<_>) = changed
+        .iter()
+        .cloned()
+        .partition(|task| !task.file.is_file());
+    if !missing.is_empty() {
+        remaining.extend(restore_from_bundle(instance, missing)?);
+    }
+    download_or_verify(remaining, &progress, config.clone(), offline).await?;
+    manifest.record(&changed);
+    if let Err(error) = manifest.save(&instance.id) {
+        warn!("Could not save the integrity manifest: {error}");
     }
     Ok(())
 }
 
-async fn try_load_lock_file(path: &PathBuf) -> Option<()> {
-    let contents = async_fs::read_to_string(path)
-        .await
-        .ok()?
-        .parse::<u64>()
-        .ok()?;
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Incorrect system time")
-        .as_secs();
-    if now - contents > 10 * 24 * 60 * 60 {
-        return None;
-    };
-    Some(())
-}
-
-async fn save_lock_file(path: &PathBuf) -> Result<()> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Incorrect system time")
-        .as_secs();
-    std::fs::write(path, now.to_string())?;
-    Ok(())
-}
-
-/// Completes missing or corrupted asset files for the given instance.
-async fn complete_assets_files(
+/// Hashes every asset and library file of the instance regardless of the
+/// integrity manifest, and downloads the missing or corrupted ones.
+pub async fn repair_files(
     instance: &Instance,
     minecraft_location: &MinecraftLocation,
-    progress: DownloadState,
-    config: DownloadConfig,
-) -> Result<()> {
-    let version_json_path = minecraft_location.get_version_json(instance.get_version_id()?);
-    let raw_version_json = async_fs::read_to_string(version_json_path).await?;
-    let resolved_version = resolve_version(
-        &Version::from_str(&raw_version_json)?,
-        minecraft_location,
-        &[],
-    )
-    .await?;
-    if let Some(asset_index) = resolved_version.asset_index {
-        let assets_downloads = generate_assets_downloads(minecraft_location, &asset_index).await?;
-        download::download_concurrent(assets_downloads, &progress, config).await?;
-    };
-    Ok(())
+    config: &DownloadConfig,
+) -> Result<RepairReport> {
+    let (version_id, tasks) = game_files(instance, minecraft_location, false).await?;
+    let progress = DownloadState::default();
+    // Files without a known hash are only downloaded when missing.
+    let to_hash: Vec<DownloadTask> = tasks
+        .iter()
+        .filter(|task| task.checksum != Checksum::None || !task.file.is_file())
+        .cloned()
+        .collect();
+    let checked = to_hash.len();
+    let broken = download::filter_existing_and_verified_files(to_hash, &progress);
+    let repaired = broken
+        .iter()
+        .map(|task| task.file.to_string_lossy().to_string())
+        .collect();
+    info!("Repairing {} of {checked} game files", broken.len());
+    let missing = restore_from_bundle(instance, broken)?;
+    download::download_concurrent(missing, &progress, config.clone()).await?;
+    let mut manifest = IntegrityManifest::load(&instance.id, &version_id);
+    manifest.record(&tasks);
+    manifest.save(&instance.id)?;
+    Ok(RepairReport { checked, repaired })
 }
 
-/// Completes missing or corrupted library files for the given instance.
-async fn complete_libraries_files(
+/// Returns the id of the resolved version of the instance and the download
+/// tasks of its libraries and assets.
+async fn game_files(
     instance: &Instance,
     minecraft_location: &MinecraftLocation,
-    progress: DownloadState,
+    offline: bool,
+) -> Result<(String, Vec<DownloadTask>)> {
+    let resolved_version = resolve_instance_version(instance, minecraft_location, &[]).await?;
+    let mut tasks = generate_libraries_downloads(minecraft_location, &resolved_version.libraries);
+    if let Some(asset_index) = &resolved_version.asset_index {
+        let index_path = minecraft_location.get_assets_index(&asset_index.id);
+        if offline && !index_path.is_file() {
+            return Err(Error::MissingFiles(vec![
+                index_path.to_string_lossy().to_string(),
+            ]));
+        }
+        tasks.extend(generate_assets_downloads(minecraft_location, asset_index).await?);
+    }
+    Ok((resolved_version.id, tasks))
+}
+
+/// Downloads the missing or corrupted files of `tasks`, or only verifies them
+/// when `offline`.
+pub(crate) async fn download_or_verify(
+    tasks: Vec<DownloadTask>,
+    progress: &DownloadState,
     config: DownloadConfig,
+    offline: bool,
 ) -> Result<()> {
-    let version_json_path = minecraft_location.get_version_json(instance.get_version_id()?);
-    let raw_version_json = async_fs::read_to_string(version_json_path).await?;
-    let resolved_version = resolve_version(
-        &Version::from_str(&raw_version_json)?,
-        minecraft_location,
-        &[],
-    )
-    .await?;
-    let library_downloads =
-        generate_libraries_downloads(minecraft_location, &resolved_version.libraries);
-    download::download_concurrent(library_downloads, &progress, config).await?;
+    if offline {
+        return verify_local_files(tasks, progress);
+    }
+    download::download_concurrent(tasks, progress, config).await?;
     Ok(())
 }
+
+/// Restores files from the install bundle the instance was installed from, if
+/// any, and returns the tasks that still have to be downloaded.
+fn restore_from_bundle(instance: &Instance, tasks: Vec<DownloadTask>) -> Result<Vec<DownloadTask>> {
+    match open_instance_bundle_source(instance) {
+        Some(mut source) => Ok(source.restore(tasks)?),
+        None => Ok(tasks),
+    }
+}
@@ -0,0 +1,424 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Rule-based analysis of crash reports and game logs.
+//!
+//! After the game exits with an error, the latest crash report, `latest.log`
+//! and JVM fatal error logs (`hs_err_pid*.log`) of the instance are matched
+//! against a set of rules, each producing typed [`Finding`]s.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use log::info;
+use regex::Regex;
+use serde::Serialize;
+
+/// Log files larger than this are only read from the end.
+const MAX_LOG_SIZE: usize = 4 * 1024 * 1024;
+
+/// Libraries of graphics drivers, matched against the problematic frame of a
+/// JVM fatal error log.
+const GRAPHICS_DRIVER_LIBRARIES: &[&str] = &[
+    "atio6axx",
+    "atioglxx",
+    "atig6pxx",
+    "amdxc",
+    "ig4icd",
+    "ig7icd",
+    "ig8icd",
+    "ig9icd",
+    "igxelp",
+    "nvoglv",
+    "nvd3dum",
+    "libnvidia-glcore",
+    "libgl",
+    "radeonsi",
+    "iris_dri",
+    "i965_dri",
+    "swrast",
+    "opengl32",
+];
+
+/// A problem identified in the game output.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum Finding {
+    /// The game or a mod was built for another Java version.
+    WrongJavaVersion {
+        required: Option<u32>,
+        current: Option<u32>,
+    },
+    /// `mod_id` depends on `dependency`, which is not installed.
+    MissingModDependency {
+        mod_id: String,
+        dependency: String,
+    },
+    /// Several files provide the same mod id.
+    DuplicateMod {
+        mod_id: String,
+        files: Vec<String>,
+    },
+    /// A mixin could not be applied.
+    MixinApplyFailed {
+        mod_id: Option<String>,
+        mixin: String,
+    },
+    OutOfMemory,
+    /// The JVM crashed inside a graphics driver or OpenGL is not available.
+    GraphicsDriverCrash {
+        library: Option<String>,
+    },
+    /// A jar file could not be read.
+    CorruptedJar {
+        file: Option<String>,
+    },
+}
+
+/// The result of analyzing the output of a crashed game.
+#[derive(Debug, Clone, Serialize, Default, PartialEq)]
+pub struct Diagnosis {
+    pub findings: Vec<Finding>,
+    /// The files that were analyzed.
+    pub sources: Vec<PathBuf>,
+    pub crash_report: Option<PathBuf>,
+}
+
+struct Rule {
+    pattern: Regex,
+    finding: fn(&regex::Captures) -> Finding,
+}
+
+impl Rule {
+    fn new(pattern: &str, finding: fn(&regex::Captures) -> Finding) -> Self {
+        Self {
+            pattern: Regex::new(pattern).expect("Invalid diagnosis rule"),
+            finding,
+        }
+    }
+}
+
+fn capture(captures: &regex::Captures, index: usize) -> Option<String> {
+    captures.get(index).map(|value| value.as_str().to_string())
+}
+
+/// Class file versions are the Java major version plus 44.
+fn java_from_class_version(captures: &regex::Captures, index: usize) -> Option<u32> {
+    captures
+        .get(index)?
+        .as_str()
+        .parse::<u32>()
+        .ok()?
+        .checked_sub(44)
+}
+
+fn mod_id_of_mixin_config(config: &str) -> Option<String> {
+    config
+        .split('.')
+        .next()
+        .filter(|mod_id| !mod_id.is_empty() && *mod_id != "mixins")
+        .map(str::to_string)
+}
+
+static RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![
+        Rule::new(
+            r"class file version (\d+)\.\d+\), this version of the Java Runtime only recognizes class file versions up to (\d+)",
+            |captures| Finding::WrongJavaVersion {
+                required: java_from_class_version(captures, 1),
+                current: java_from_class_version(captures, 2),
+            },
+        ),
+        // Legacy Forge and LaunchWrapper cast the app class loader, which fails after Java 8.
+        Rule::new(
+            r"class jdk\.internal\.loader\.ClassLoaders\$AppClassLoader cannot be cast to class java\.net\.URLClassLoader",
+            |_| Finding::WrongJavaVersion {
+                required: Some(8),
+                current: None,
+            },
+        ),
+        Rule::new(
+            r"Mod '[^'\n]*' \(([\w.-]+)\) \S+ requires [^\n]*? of (?:mod )?(?:'[^'\n]*' \()?([\w.-]+)\)?,? which is missing",
+            |captures| Finding::MissingModDependency {
+                mod_id: capture(captures, 1).unwrap_or_default(),
+                dependency: capture(captures, 2).unwrap_or_default(),
+            },
+        ),
+        Rule::new(
+            r"Mod ID: '([\w.-]+)', Requested by: '([\w.-]+)'",
+            |captures| Finding::MissingModDependency {
+                mod_id: capture(captures, 2).unwrap_or_default(),
+                dependency: capture(captures, 1).unwrap_or_default(),
+            },
+        ),
+        Rule::new(
+            r"MissingModsException: Mod ([\w.-]+) \([^)\n]*\) requires \[([\w.-]+)",
+            |captures| Finding::MissingModDependency {
+                mod_id: capture(captures, 1).unwrap_or_default(),
+                dependency: capture(captures, 2).unwrap_or_default(),
+            },
+        ),
+        Rule::new(
+            r"Mod ID: '([\w.-]+)' from mod files: ([^\n]+)",
+            |captures| Finding::DuplicateMod {
+                mod_id: capture(captures, 1).unwrap_or_default(),
+                files: split_files(captures.get(2).map_or("", |files| files.as_str())),
+            },
+        ),
+        Rule::new(
+            r"Duplicate mod ID:? '?([\w.-]+)'?!? ?\(([^)\n]*)\)",
+            |captures| Finding::DuplicateMod {
+                mod_id: capture(captures, 1).unwrap_or_default(),
+                files: split_files(captures.get(2).map_or("", |files| files.as_str())),
+            },
+        ),
+        Rule::new(
+            r"Mixin apply for mod ([\w.-]+) failed ([\w.-]+\.json:\S+)",
+            |captures| Finding::MixinApplyFailed {
+                mod_id: capture(captures, 1),
+                mixin: capture(captures, 2).unwrap_or_default(),
+            },
+        ),
+        Rule::new(
+            r"Mixin \[(([\w.-]+?)\.mixins\.json:[^\]]+)\][^\n]*FAILED during APPLY",
+            |captures| Finding::MixinApplyFailed {
+                mod_id: captures
+                    .get(2)
+                    .and_then(|config| mod_id_of_mixin_config(config.as_str())),
+                mixin: capture(captures, 1).unwrap_or_default(),
+            },
+        ),
+        Rule::new(
+            r"Critical injection failure[^\n]*? in ([\w.-]+\.json:\S+) from mod ([\w.-]+)",
+            |captures| Finding::MixinApplyFailed {
+                mod_id: capture(captures, 2),
+                mixin: capture(captures, 1).unwrap_or_default(),
+            },
+        ),
+        Rule::new(
+            r"java\.lang\.OutOfMemoryError|There is insufficient memory for the Java Runtime Environment|Could not reserve enough space for",
+            |_| Finding::OutOfMemory,
+        ),
+        Rule::new(
+            r"(?i)Pixel format not accelerated|GLFW error 65542|The driver does not appear to support OpenGL|Couldn't set pixel format",
+            |_| Finding::GraphicsDriverCrash { library: None },
+        ),
+        Rule::new(r"Invalid or corrupt jarfile (\S+)", |captures| {
+            Finding::CorruptedJar {
+                file: capture(captures, 1),
+            }
+        }),
+        Rule::new(
+            r"(?m)java\.util\.zip\.ZipException: (?:zip END header not found|invalid LOC header|invalid CEN header|zip file is empty|error in opening zip file)[^\n]*?(\S+\.jar)?\s*$",
+            |captures| Finding::CorruptedJar {
+                file: capture(captures, 1),
+            },
+        ),
+    ]
+});
+
+static PROBLEMATIC_FRAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Problematic frame:\s*\n#\s*C\s+\[([^+\]\s]+)").expect("Invalid diagnosis rule")
+});
+
+fn split_files(files: &str) -> Vec<String> {
+    files
+        .split(',')
+        .map(|file| file.trim().to_string())
+        .filter(|file| !file.is_empty())
+        .collect()
+}
+
+/// Applies the rule set to a log or crash report.
+pub fn analyze_text(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for rule in RULES.iter() {
+        for captures in rule.pattern.captures_iter(text) {
+            push_unique(&mut findings, (rule.finding)(&captures));
+        }
+    }
+    if let Some(captures) = PROBLEMATIC_FRAME.captures(text) {
+        let library = captures[1].to_string();
+        let lowercase = library.to_lowercase();
+        if GRAPHICS_DRIVER_LIBRARIES
+            .iter()
+            .any(|driver| lowercase.starts_with(driver))
+        {
+            push_unique(
+                &mut findings,
+                Finding::GraphicsDriverCrash {
+                    library: Some(library),
+                },
+            );
+        }
+    }
+    findings
+}
+
+fn push_unique(findings: &mut Vec<Finding>, finding: Finding) {
+    if !findings.contains(&finding) {
+        findings.push(finding);
+    }
+}
+
+/// Analyzes the crash report, `logs/latest.log` and JVM fatal error logs of
+/// the instance folder `instance_dir`.
+///
+/// Only crash reports and fatal error logs written after `since` (a Unix
+/// timestamp in seconds) are considered, so older crashes are not reported
+/// again.
+pub fn analyze_instance(instance_dir: &Path, since: u64) -> Diagnosis {
+    let since = UNIX_EPOCH + Duration::from_secs(since);
+    let is_recent = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified >= since)
+    };
+    let crash_report = latest_file(&instance_dir.join("crash-reports"), |name| {
+        name.ends_with(".txt")
+    })
+    .filter(|path| is_recent(path));
+    let mut sources: Vec<PathBuf> = crash_report.iter().cloned().collect();
+    sources.extend(
+        latest_file(instance_dir, |name| {
+            name.starts_with("hs_err_pid") && name.ends_with(".log")
+        })
+        .filter(|path| is_recent(path)),
+    );
+    let latest_log = instance_dir.join("logs").join("latest.log");
+    if latest_log.is_file() {
+        sources.push(latest_log);
+    }
+
+    let mut findings = Vec::new();
+    for source in &sources {
+        let Some(content) = read_log(source) else {
+            continue;
+        };
+        for finding in analyze_text(&content) {
+            push_unique(&mut findings, finding);
+        }
+    }
+    info!(
+        "Crash analysis found {} problems in {} files",
+        findings.len(),
+        sources.len()
+    );
+    Diagnosis {
+        findings,
+        sources,
+        crash_report,
+    }
+}
+
+/// Returns the most recently modified file of `folder` accepted by `filter`.
+fn latest_file(folder: &Path, filter: impl Fn(&str) -> bool) -> Option<PathBuf> {
+    std::fs::read_dir(folder)
+        .ok()?
+        .flatten()
+        .filter(|entry| filter(&entry.file_name().to_string_lossy()))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+fn read_log(path: &Path) -> Option<String> {
+    let content = std::fs::read(path).ok()?;
+    let start = content.len().saturating_sub(MAX_LOG_SIZE);
+    Some(String::from_utf8_lossy(&content[start..]).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_wrong_java_version() {
+        let log = "java.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main has been \
+                   compiled by a more recent version of the Java Runtime (class file version 65.0), \
+                   this version of the Java Runtime only recognizes class file versions up to 61.0";
+        assert_eq!(
+            analyze_text(log),
+            vec![Finding::WrongJavaVersion {
+                required: Some(21),
+                current: Some(17)
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_mod_problems() {
+        let log = "\
+- Mod 'Sodium Extra' (sodium-extra) 0.5.1 requires any version of sodium, which is missing!
+\tMod ID: 'fabric_api', Requested by: 'create'
+\tMod ID: 'jei' from mod files: jei-1.20.1.jar, jei-1.20.1-copy.jar
+Mixin apply for mod iris failed iris.mixins.json:MixinLevelRenderer from mod iris -> net.minecraft.class_761
+java.lang.OutOfMemoryError: Java heap space
+java.lang.OutOfMemoryError: Java heap space";
+        assert_eq!(
+            analyze_text(log),
+            vec![
+                Finding::MissingModDependency {
+                    mod_id: "sodium-extra".to_string(),
+                    dependency: "sodium".to_string()
+                },
+                Finding::MissingModDependency {
+                    mod_id: "create".to_string(),
+                    dependency: "fabric_api".to_string()
+                },
+                Finding::DuplicateMod {
+                    mod_id: "jei".to_string(),
+                    files: vec![
+                        "jei-1.20.1.jar".to_string(),
+                        "jei-1.20.1-copy.jar".to_string()
+                    ]
+                },
+                Finding::MixinApplyFailed {
+                    mod_id: Some("iris".to_string()),
+                    mixin: "iris.mixins.json:MixinLevelRenderer".to_string()
+                },
+                Finding::OutOfMemory,
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_graphics_driver_crash() {
+        let log = "# Problematic frame:\n# C  [atio6axx.dll+0x1a2b3c]\n";
+        assert_eq!(
+            analyze_text(log),
+            vec![Finding::GraphicsDriverCrash {
+                library: Some("atio6axx.dll".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_corrupted_jars() {
+        assert_eq!(
+            analyze_text(
+                "Error: Invalid or corrupt jarfile /games/.minecraft/versions/1.20.1/1.20.1.jar"
+            ),
+            vec![Finding::CorruptedJar {
+                file: Some("/games/.minecraft/versions/1.20.1/1.20.1.jar".to_string())
+            }]
+        );
+        let log = "\
+java.util.zip.ZipException: error in opening zip file /games/mods/sodium-0.5.3.jar
+\tat java.base/java.util.zip.ZipFile$Source.initCEN(ZipFile.java:1598)";
+        assert_eq!(
+            analyze_text(log),
+            vec![Finding::CorruptedJar {
+                file: Some("/games/mods/sodium-0.5.3.jar".to_string())
+            }]
+        );
+        assert_eq!(
+            analyze_text("java.util.zip.ZipException: zip END header not found"),
+            vec![Finding::CorruptedJar { file: None }]
+        );
+    }
+}
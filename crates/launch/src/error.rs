@@ -14,8 +14,21 @@ pub type Result<T> = result::Result<T, Error>;
 #[derive(Debug, Error, Serialize)]
 #[serde(tag = "kind", content = "message")]
 pub enum Error {
-    #[error("Another instance is launching")]
-    AnothorInstanceLaunching,
+    #[error("The instance is already launching")]
+    AlreadyLaunching,
+
+    #[error("No running game with PID {0}")]
+    GameNotRunning(u32),
+
+    #[error("Could not focus the game window")]
+    FocusWindowFailed,
+
+    #[error("The {0} hook failed")]
+    HookFailed(String),
+
+    #[error("The {0} hook timed out")]
+    HookTimeout(String),
+
     #[error(transparent)]
     Io(
         #[from]
@@ -31,9 +44,33 @@ pub enum Error {
     #[error("Invalid Minecraft version")]
     InvalidMinecraftVersion,
 
+    #[error("Invalid version patch: {0}")]
+    InvalidVersionPatch(String),
+
+    #[error("Invalid JVM preset: {0}")]
+    InvalidJvmPreset(String),
+
+    #[error("{0} is enabled but not installed")]
+    PerformanceToolMissing(String),
+
+    #[error("Invalid performance option: {0}")]
+    InvalidPerformanceOption(String),
+
+    #[error("Invalid Java agent {0}")]
+    InvalidJavaAgent(String),
+
+    #[error("The system {0} library is selected but not installed")]
+    SystemLibraryMissing(String),
+
+    #[error("No natives are available for {0}")]
+    NativesUnavailable(String),
+
     #[error("Invalid Profile")]
     InvalidProfile,
 
+    #[error("The {0} bound to the instance no longer exists")]
+    BoundAccountMissing(String),
+
     #[error("Instance broken: {0}")]
     InvalidInstance(String),
 
@@ -54,6 +91,9 @@ pub enum Error {
     #[error("Chunk length mismatch")]
     ChunkLengthMismatch,
 
+    #[error("Invalid shell words: {0}")]
+    InvalidShellWords(String),
+
     #[error("Unabled to take Minecraft stdout")]
     TakeMinecraftStdoutFailed,
 
@@ -73,10 +113,38 @@ pub enum Error {
     #[error("{0}")]
     ChecksumMissmatch(String),
 
+    #[error("Invalid install bundle: {0}")]
+    InvalidBundle(String),
+
+    #[error("Files missing or corrupted, they cannot be downloaded offline: {}", list_files(.0))]
+    MissingFiles(Vec<String>),
+
+    #[error("{0} was not found next to the Java executable, profiling requires a JDK")]
+    JdkToolMissing(String),
+
+    #[error("Profiling failed: {0}")]
+    ProfilingFailed(String),
+
     #[error("Unhandled Error")]
     Other,
 }
 
+/// Lists the first files of `files`, as an instance can miss thousands of
+/// assets.
+fn list_files(files: &[String]) -> String {
+    const SHOWN: usize = 5;
+    let mut list = files
+        .iter()
+        .take(SHOWN)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if files.len() > SHOWN {
+        list.push_str(&format!(" and {} more", files.len() - SHOWN));
+    }
+    list
+}
+
 impl From<version::Error> for Error {
     fn from(value: version::Error) -> Self {
         match value {
@@ -84,6 +152,7 @@ impl From<version::Error> for Error {
             version::Error::JsonParse(error) => Self::VersionJsonParse(error),
             version::Error::InvalidVersionJson => Self::InvalidVersionJson("".to_string()),
             version::Error::InvalidMinecraftVersion => Self::InvalidMinecraftVersion,
+            version::Error::InvalidPatch(error) => Self::InvalidVersionPatch(error),
         }
     }
 }
@@ -96,6 +165,9 @@ impl From<instance::Error> for Error {
             instance::Error::InvalidInstanceConfig => {
                 Self::InvalidInstance("Invalid instance config".to_string())
             }
+            error @ (instance::Error::UnknownPlaceholder(_)
+            | instance::Error::InstanceNotFound(_)
+            | instance::Error::EmptyInstanceName) => Self::InvalidInstance(error.to_string()),
         }
     }
 }
@@ -108,6 +180,9 @@ impl From<install::Error> for Error {
             install::Error::InstanceBroken => Self::InvalidInstance("".to_string()),
             install::Error::JsonParse(error) => Self::VersionJsonParse(error),
             install::Error::InvalidVersionJson(error) => Self::InvalidVersionJson(error),
+            install::Error::ChecksumMissmatch(error) => Self::ChecksumMissmatch(error),
+            install::Error::Zip(error) => Self::DecompressionFailed(error),
+            install::Error::InvalidBundle(error) => Self::InvalidBundle(error),
             _ => Self::Other,
         }
     }
@@ -0,0 +1,182 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! GPU offloading on Linux hybrid-graphics systems.
+//!
+//! The game renders on the default GPU unless told otherwise, which is the
+//! integrated one on most laptops. Mesa drivers are offloaded with `DRI_PRIME`
+//! and the NVIDIA driver with its PRIME render offload variables. Vulkan is
+//! restricted to the driver of the chosen GPU through `VK_ICD_FILENAMES`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use config::launch::GpuPreference;
+use log::{info, warn};
+use platform::Gpu;
+
+/// Where Vulkan drivers install their ICD manifests.
+const VULKAN_ICD_DIRS: [&str; 2] = ["/usr/share/vulkan/icd.d", "/etc/vulkan/icd.d"];
+
+/// Returns the variables running the game on the GPU of `preference`. Always
+/// empty outside of Linux.
+pub(crate) fn gpu_environment(preference: &GpuPreference) -> Vec<(String, String)> {
+    if !cfg!(target_os = "linux") {
+        return Vec::new();
+    }
+    let gpus = platform::list_gpus();
+    let Some(gpu) = select_gpu(preference, &gpus) else {
+        return Vec::new();
+    };
+    info!(
+        "Running the game on {} ({:?} {:04x}:{:04x})",
+        gpu.card, gpu.vendor, gpu.vendor_id, gpu.device_id
+    );
+    let icd_dirs: Vec<PathBuf> = VULKAN_ICD_DIRS.iter().map(PathBuf::from).collect();
+    offload_environment(gpu, &icd_dirs)
+}
+
+/// Returns the GPU the game must be offloaded to, or `None` when the default
+/// renderer is the right one.
+fn select_gpu<'a>(preference: &GpuPreference, gpus: &'a [Gpu]) -> Option<&'a Gpu> {
+    match preference {
+        GpuPreference::System => None,
+        GpuPreference::Auto => {
+            if gpus.iter().any(|gpu| gpu.boot_vga && gpu.discrete) {
+                return None;
+            }
+            gpus.iter().find(|gpu| gpu.discrete && !gpu.boot_vga)
+        }
+        GpuPreference::Device { pci_address } => {
+            let gpu = gpus
+                .iter()
+                .find(|gpu| gpu.pci_address.as_ref() == Some(pci_address));
+            if gpu.is_none() {
+                warn!("GPU {pci_address} not found, using the default GPU");
+            }
+            gpu.filter(|gpu| !gpu.boot_vga)
+        }
+    }
+}
+
+fn offload_environment(gpu: &Gpu, icd_dirs: &[PathBuf]) -> Vec<(String, String)> {
+    let driver = gpu.driver.as_deref().unwrap_or_default();
+    let mut env = if driver == "nvidia" {
+        vec![
+            ("__NV_PRIME_RENDER_OFFLOAD".to_string(), "1".to_string()),
+            (
+                "__GLX_VENDOR_LIBRARY_NAME".to_string(),
+                "nvidia".to_string(),
+            ),
+            (
+                "__VK_LAYER_NV_optimus".to_string(),
+                "NVIDIA_only".to_string(),
+            ),
+        ]
+    } else {
+        let dri_prime = match &gpu.pci_address {
+            Some(address) => format!("pci-{}", address.replace([':', '.'], "_")),
+            None => "1".to_string(),
+        };
+        vec![("DRI_PRIME".to_string(), dri_prime)]
+    };
+    let icd_prefix = match driver {
+        "nvidia" => Some("nvidia_icd"),
+        "amdgpu" | "radeon" => Some("radeon_icd"),
+        "i915" | "xe" => Some("intel_icd"),
+        "nouveau" => Some("nouveau_icd"),
+        _ => None,
+    };
+    if let Some(icd_prefix) = icd_prefix {
+        let icd_files = find_icd_files(icd_dirs, icd_prefix);
+        if !icd_files.is_empty() {
+            env.push(("VK_ICD_FILENAMES".to_string(), icd_files.join(":")));
+        }
+    }
+    env
+}
+
+/// Lists the ICD manifests starting with `prefix`, for every architecture.
+fn find_icd_files(icd_dirs: &[PathBuf], prefix: &str) -> Vec<String> {
+    let mut files: Vec<String> = icd_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_icd_file(path, prefix))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    files
+}
+
+fn is_icd_file(path: &Path, prefix: &str) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(prefix) && name.ends_with(".json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use platform::GpuVendor;
+
+    use super::*;
+
+    fn gpu(card: &str, vendor: GpuVendor, driver: &str, boot_vga: bool) -> Gpu {
+        Gpu {
+            card: card.to_string(),
+            pci_address: Some(format!("0000:0{}:00.0", &card[4..])),
+            vendor,
+            vendor_id: 0,
+            device_id: 0,
+            driver: Some(driver.to_string()),
+            boot_vga,
+            discrete: vendor != GpuVendor::Intel && !boot_vga,
+        }
+    }
+
+    #[test]
+    fn prefers_discrete_gpu() {
+        let gpus = [
+            gpu("card0", GpuVendor::Intel, "i915", true),
+            gpu("card1", GpuVendor::Amd, "amdgpu", false),
+        ];
+        assert_eq!(
+            select_gpu(&GpuPreference::Auto, &gpus).unwrap().card,
+            "card1"
+        );
+        assert!(select_gpu(&GpuPreference::System, &gpus).is_none());
+        let boot = GpuPreference::Device {
+            pci_address: "0000:00:00.0".to_string(),
+        };
+        assert!(select_gpu(&boot, &gpus).is_none());
+        assert!(select_gpu(&GpuPreference::Auto, &gpus[..1]).is_none());
+    }
+
+    #[test]
+    fn sets_offload_variables() {
+        let icd_dirs = [PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/fixtures/vulkan")];
+        let env = offload_environment(&gpu("card1", GpuVendor::Amd, "amdgpu", false), &icd_dirs);
+        assert_eq!(
+            env[0],
+            ("DRI_PRIME".to_string(), "pci-0000_01_00_0".to_string())
+        );
+        assert_eq!(env[1].0, "VK_ICD_FILENAMES");
+        assert!(env[1].1.ends_with("radeon_icd.x86_64.json"));
+        assert_eq!(env[1].1.split(':').count(), 2);
+
+        let env = offload_environment(&gpu("card1", GpuVendor::Nvidia, "nvidia", false), &[]);
+        assert_eq!(
+            env.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(),
+            vec![
+                "__NV_PRIME_RENDER_OFFLOAD",
+                "__GLX_VENDOR_LIBRARY_NAME",
+                "__VK_LAYER_NV_optimus"
+            ]
+        );
+    }
+}
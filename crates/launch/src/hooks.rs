@@ -0,0 +1,247 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Lifecycle hooks: user commands run before launch, once the game window is
+//! ready, after the game exits and after it crashes.
+//!
+//! Hooks run through the system shell inside the instance folder. Their
+//! output is written to the launcher log, and they are killed when they run
+//! longer than the configured timeout.
+
+use std::{
+    fmt,
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use log::{info, warn};
+
+use crate::error::*;
+
+/// How long the output of a finished hook is still read before returning.
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    PreLaunch,
+    PostStart,
+    PostExit,
+    OnCrash,
+}
+
+impl fmt::Display for HookKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PreLaunch => write!(f, "pre-launch"),
+            Self::PostStart => write!(f, "post-start"),
+            Self::PostExit => write!(f, "post-exit"),
+            Self::OnCrash => write!(f, "on-crash"),
+        }
+    }
+}
+
+/// Information about the game passed to hooks as environment variables.
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    pub instance_id: String,
+    pub instance_name: String,
+    pub instance_dir: PathBuf,
+    pub minecraft_version: String,
+    pub account_name: String,
+    pub java_path: PathBuf,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+}
+
+impl HookContext {
+    /// The environment variables of a hook, all prefixed with `CONIC_`.
+    pub fn environment(&self, kind: HookKind) -> Vec<(&'static str, String)> {
+        let mut environment = vec![
+            ("CONIC_HOOK", kind.to_string()),
+            ("CONIC_INSTANCE_ID", self.instance_id.clone()),
+            ("CONIC_INSTANCE_NAME", self.instance_name.clone()),
+            (
+                "CONIC_INSTANCE_DIR",
+                self.instance_dir.to_string_lossy().to_string(),
+            ),
+            ("CONIC_MINECRAFT_VERSION", self.minecraft_version.clone()),
+            ("CONIC_ACCOUNT_NAME", self.account_name.clone()),
+            (
+                "CONIC_JAVA_PATH",
+                self.java_path.to_string_lossy().to_string(),
+            ),
+        ];
+        if let Some(pid) = self.pid {
+            environment.push(("CONIC_GAME_PID", pid.to_string()));
+        }
+        if let Some(exit_code) = self.exit_code {
+            environment.push(("CONIC_EXIT_CODE", exit_code.to_string()));
+        }
+        environment
+    }
+}
+
+/// Runs `command` and waits for it to finish.
+///
+/// An empty command does nothing. A `timeout` of zero waits forever. Returns
+/// [`Error::HookFailed`] when the hook exits with an error and
+/// [`Error::HookTimeout`] when it is killed because of the timeout.
+pub fn run_hook(
+    kind: HookKind,
+    command: &str,
+    context: &HookContext,
+    timeout: Duration,
+) -> Result<()> {
+    if command.trim().is_empty() {
+        return Ok(());
+    }
+    info!("Running {kind} hook: {command}");
+    let mut hook = shell_command(command);
+    hook.current_dir(&context.instance_dir)
+        .envs(context.environment(kind))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = hook.spawn()?;
+    let readers = [
+        child.stdout.take().map(|out| log_output(kind, out)),
+        child.stderr.take().map(|err| log_output(kind, err)),
+    ];
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if !timeout.is_zero() && start.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            warn!(
+                "The {kind} hook timed out after {} seconds",
+                timeout.as_secs()
+            );
+            return Err(Error::HookTimeout(kind.to_string()));
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    // A background process started by the hook may keep the pipes open, so
+    // the readers are only waited for briefly and then left to finish alone.
+    let output_deadline = Instant::now() + OUTPUT_GRACE_PERIOD;
+    for reader in readers.into_iter().flatten() {
+        while !reader.is_finished() && Instant::now() < output_deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if reader.is_finished() {
+            let _ = reader.join();
+        }
+    }
+    if !status.success() {
+        warn!("The {kind} hook exited with {status}");
+        return Err(Error::HookFailed(kind.to_string()));
+    }
+    Ok(())
+}
+
+/// Runs a hook on a background thread, logging its failure.
+pub fn spawn_hook(kind: HookKind, command: String, context: HookContext, timeout: Duration) {
+    if command.trim().is_empty() {
+        return;
+    }
+    thread::spawn(move || {
+        if let Err(error) = run_hook(kind, &command, &context, timeout) {
+            warn!("{error}");
+        }
+    });
+}
+
+fn shell_command(command: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]).creation_flags(0x08000000);
+        shell
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+fn log_output<R: Read + Send + 'static>(kind: HookKind, output: R) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            info!("[{kind} hook] {line}");
+        }
+    })
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+
+    fn context() -> HookContext {
+        HookContext {
+            instance_id: "test".to_string(),
+            instance_dir: std::env::temp_dir(),
+            exit_code: Some(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn passes_environment_and_reports_failure() {
+        let timeout = Duration::from_secs(10);
+        assert!(
+            run_hook(
+                HookKind::OnCrash,
+                "test \"$CONIC_EXIT_CODE\" = 1 && test \"$CONIC_HOOK\" = on-crash",
+                &context(),
+                timeout,
+            )
+            .is_ok()
+        );
+        assert!(matches!(
+            run_hook(HookKind::PostExit, "exit 3", &context(), timeout),
+            Err(Error::HookFailed(_))
+        ));
+    }
+
+    #[test]
+    fn kills_hooks_after_timeout() {
+        assert!(matches!(
+            run_hook(
+                HookKind::PreLaunch,
+                "sleep 5",
+                &context(),
+                Duration::from_millis(200)
+            ),
+            Err(Error::HookTimeout(_))
+        ));
+    }
+
+    #[test]
+    fn does_not_wait_for_background_processes() {
+        let start = Instant::now();
+        assert!(
+            run_hook(
+                HookKind::PreLaunch,
+                "sleep 5 & echo started",
+                &context(),
+                Duration::from_secs(10)
+            )
+            .is_ok()
+        );
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+}
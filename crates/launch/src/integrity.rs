@@ -0,0 +1,203 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Integrity manifest of the game files of an instance.
+//!
+//! After the libraries and assets of an instance are verified, their size,
+//! modification time and expected hash are recorded. A later launch only
+//! hashes the files whose metadata or expected hash changed since, so files
+//! deleted or modified by hand are caught without hashing every file on every
+//! launch. Deleting the manifest forces a full verification.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use download::{Checksum, DownloadTask};
+use folder::DATA_LOCATION;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::error::*;
+
+const MANIFEST_FILE: &str = ".conic-integrity.json";
+
+/// The time-based lock files replaced by the manifest.
+const LEGACY_LOCK_FILES: [&str; 2] = [".conic-assets-ok", ".conic-libraries-ok"];
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub(crate) struct IntegrityManifest {
+    /// The id of the resolved version the files belong to.
+    version_id: String,
+    files: HashMap<PathBuf, FileRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct FileRecord {
+    size: u64,
+    /// Nanoseconds since the Unix epoch.
+    modified: u64,
+    /// `None` for files without a known hash, which only have to exist.
+    checksum: Option<String>,
+}
+
+impl IntegrityManifest {
+    /// Loads the manifest of `instance_id`, or an empty one when it is
+    /// missing, unreadable or written for another version.
+    pub(crate) fn load(instance_id: &str, version_id: &str) -> Self {
+        let path = manifest_path(instance_id);
+        let manifest = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|manifest| manifest.version_id == version_id);
+        manifest.unwrap_or_else(|| Self {
+            version_id: version_id.to_string(),
+            files: HashMap::new(),
+        })
+    }
+
+    /// Splits `tasks` into the files unchanged since they were recorded and
+    /// the files that must be verified again.
+    pub(crate) fn partition(
+        &self,
+        tasks: Vec<DownloadTask>,
+    ) -> (Vec<DownloadTask>, Vec<DownloadTask>) {
+        tasks
+            .into_iter()
+            .partition(|task| self.is_unchanged(&task.file, &task.checksum))
+    }
+
+    fn is_unchanged(&self, file: &Path, checksum: &Checksum) -> bool {
+        let Some(record) = self.files.get(file) else {
+            return false;
+        };
+        record.checksum == checksum_key(checksum)
+            && stat(file)
+                .is_some_and(|(size, modified)| record.size == size && record.modified == modified)
+    }
+
+    /// Records the metadata of the files of `tasks`, which must have just
+    /// been verified. Missing files are left out.
+    pub(crate) fn record(&mut self, tasks: &[DownloadTask]) {
+        for task in tasks {
+            match stat(&task.file) {
+                Some((size, modified)) => {
+                    self.files.insert(
+                        task.file.clone(),
+                        FileRecord {
+                            size,
+                            modified,
+                            checksum: checksum_key(&task.checksum),
+                        },
+                    );
+                }
+                None => {
+                    self.files.remove(&task.file);
+                }
+            }
+        }
+    }
+
+    /// Writes the manifest of `instance_id`, removing the lock files of older
+    /// versions of the launcher.
+    pub(crate) fn save(&self, instance_id: &str) -> Result<()> {
+        let content = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        fs::write(manifest_path(instance_id), content)?;
+        let instance_root = DATA_LOCATION.get_instance_root(instance_id);
+        for lock_file in LEGACY_LOCK_FILES {
+            let _ = fs::remove_file(instance_root.join(lock_file));
+        }
+        Ok(())
+    }
+}
+
+/// Deletes the integrity manifest of `instance_id`, so every file of the
+/// instance is hashed on the next launch.
+pub(crate) fn invalidate_integrity(instance_id: &str) {
+    match fs::remove_file(manifest_path(instance_id)) {
+        Ok(()) => info!("Invalidated the integrity manifest of {instance_id}"),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => warn!("Could not invalidate the integrity manifest: {error}"),
+    }
+}
+
+fn manifest_path(instance_id: &str) -> PathBuf {
+    DATA_LOCATION
+        .get_instance_root(instance_id)
+        .join(MANIFEST_FILE)
+}
+
+fn stat(file: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(file).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_nanos() as u64;
+    Some((metadata.len(), modified))
+}
+
+fn checksum_key(checksum: &Checksum) -> Option<String> {
+    match checksum {
+        Checksum::Sha1(hash) => Some(format!("sha1:{hash}")),
+        Checksum::Sha256(hash) => Some(format!("sha256:{hash}")),
+        Checksum::Sha512(hash) => Some(format!("sha512:{hash}")),
+        Checksum::None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use download::DownloadTaskType;
+
+    use super::*;
+
+    fn task(file: PathBuf, sha1: &str) -> DownloadTask {
+        DownloadTask {
+            url: String::new(),
+            file,
+            size_bytes: None,
+            checksum: Checksum::Sha1(sha1.to_string()),
+            task_type: DownloadTaskType::Unknown,
+        }
+    }
+
+    #[test]
+    fn detects_changed_files() {
+        let dir = std::env::temp_dir().join(format!("conic-integrity-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("kept"), "conic").unwrap();
+        fs::write(dir.join("modified"), "conic").unwrap();
+        fs::write(dir.join("deleted"), "conic").unwrap();
+        let tasks = vec![
+            task(dir.join("kept"), "a"),
+            task(dir.join("modified"), "a"),
+            task(dir.join("deleted"), "a"),
+            task(dir.join("new"), "a"),
+        ];
+        let mut manifest = IntegrityManifest::default();
+        manifest.record(&tasks);
+        assert_eq!(manifest.files.len(), 3);
+
+        fs::write(dir.join("modified"), "conic launcher").unwrap();
+        fs::remove_file(dir.join("deleted")).unwrap();
+        let mut tasks = tasks;
+        tasks.push(task(dir.join("kept"), "b"));
+        let (unchanged, changed) = manifest.partition(tasks);
+        assert_eq!(unchanged.len(), 1);
+        assert!(unchanged[0].file.ends_with("kept"));
+        assert_eq!(
+            changed
+                .iter()
+                .map(|task| task.file.file_name().unwrap().to_str().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["modified", "deleted", "new", "kept"]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,764 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Local HTTP proxy for legacy versions.
+//!
+//! Alpha, beta and early release versions fetch skins, sounds and join
+//! servers through endpoints that no longer exist. The game is started with
+//! `-Dhttp.proxyHost`/`-Dhttp.proxyPort` pointing at this proxy, which answers
+//! those routes from the modern APIs and the local asset store and forwards
+//! everything else unchanged.
+
+use std::{
+    io,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use account::Account;
+use async_io::Async;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use futures::{AsyncReadExt, AsyncWriteExt};
+use log::{debug, info, warn};
+use serde_json::{Value, json};
+use shared::HTTP_CLIENT;
+use tauri::async_runtime::JoinHandle;
+use url::Url;
+
+use folder::MinecraftLocation;
+use version::{AssetIndexContent, AssetIndexObject, ResolvedVersion};
+
+use crate::error::*;
+
+/// Versions released before 1.7.6 fetch skins from the old skin server.
+const LEGACY_PROXY_CUTOFF: &str = "2014-04-09";
+
+const MAX_HEADER_SIZE: usize = 64 * 1024;
+
+/// Returns whether `version` was released before the legacy cutoff and should
+/// be launched through the proxy.
+pub fn needs_legacy_proxy(version: &ResolvedVersion) -> bool {
+    version
+        .release_time
+        .as_deref()
+        .and_then(|release_time| release_time.get(0..10))
+        .is_some_and(|release_date| release_date < LEGACY_PROXY_CUTOFF)
+}
+
+/// The modern APIs legacy routes are answered from.
+#[derive(Clone)]
+pub struct LegacyProxyUpstream {
+    /// Base URL of the profile API, e.g. `https://api.mojang.com`.
+    pub api: String,
+    /// Base URL of the session server, e.g. `https://sessionserver.mojang.com`.
+    pub session_server: String,
+}
+
+impl Default for LegacyProxyUpstream {
+    fn default() -> Self {
+        Self {
+            api: "https://api.mojang.com".to_string(),
+            session_server: "https://sessionserver.mojang.com".to_string(),
+        }
+    }
+}
+
+impl LegacyProxyUpstream {
+    /// Uses the authentication server of a Yggdrasil account, Mojang otherwise.
+    pub fn for_account(account: &Account) -> Self {
+        match account {
+            Account::Yggdrasil(account) => {
+                let api_root = account.api_root.trim_end_matches('/');
+                Self {
+                    api: format!("{api_root}/api"),
+                    session_server: format!("{api_root}/sessionserver"),
+                }
+            }
+            _ => Self::default(),
+        }
+    }
+}
+
+/// The player the game is launched as.
+#[derive(Clone, Default)]
+pub struct LegacyPlayer {
+    pub name: String,
+    /// Whether the player owns the game, i.e. is not an offline account.
+    pub paid: bool,
+}
+
+impl LegacyPlayer {
+    pub fn for_account(account: &Account) -> Self {
+        Self {
+            name: account.get_profile_name(),
+            paid: !matches!(account, Account::Offline(_)),
+        }
+    }
+}
+
+/// The local asset store served on the old resource routes.
+#[derive(Clone, Default)]
+pub struct LegacyAssets {
+    objects_root: PathBuf,
+    objects: AssetIndexObject,
+}
+
+impl LegacyAssets {
+    /// Reads the downloaded asset index `asset_index_id`.
+    pub fn load(minecraft_location: &MinecraftLocation, asset_index_id: &str) -> Result<Self> {
+        let content: AssetIndexContent = serde_json::from_str(&std::fs::read_to_string(
+            minecraft_location.get_assets_index(asset_index_id),
+        )?)
+        .map_err(Error::VersionJsonParse)?;
+        Ok(Self {
+            objects_root: minecraft_location.assets.join("objects"),
+            objects: content.objects,
+        })
+    }
+}
+
+struct ProxyState {
+    upstream: LegacyProxyUpstream,
+    player: LegacyPlayer,
+    assets: LegacyAssets,
+}
+
+/// A running legacy proxy, stopped when dropped.
+pub struct LegacyProxy {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl LegacyProxy {
+    /// Starts the proxy on a random local port.
+    pub fn start(
+        upstream: LegacyProxyUpstream,
+        player: LegacyPlayer,
+        assets: LegacyAssets,
+    ) -> Result<Self> {
+        let listener = Async::<TcpListener>::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let port = listener.get_ref().local_addr()?.port();
+        let state = Arc::new(ProxyState {
+            upstream,
+            player,
+            assets,
+        });
+        let task = tauri::async_runtime::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(error) => {
+                        warn!("Legacy proxy stopped accepting connections: {error}");
+                        break;
+                    }
+                };
+                let state = state.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(error) = handle_connection(stream, &state).await {
+                        debug!("Legacy proxy connection failed: {error}");
+                    }
+                });
+            }
+        });
+        info!("Legacy proxy listening on 127.0.0.1:{port}");
+        Ok(Self { port, task })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// JVM arguments pointing the game at the proxy.
+    pub fn jvm_arguments(&self) -> Vec<String> {
+        vec![
+            "-Dhttp.proxyHost=127.0.0.1".to_string(),
+            format!("-Dhttp.proxyPort={}", self.port),
+        ]
+    }
+}
+
+impl Drop for LegacyProxy {
+    fn drop(&mut self) {
+        info!("Stopping legacy proxy on port {}", self.port);
+        self.task.abort();
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    target: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+struct HttpResponse {
+    status: u16,
+    content_type: String,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type: content_type.to_string(),
+            body: body.into(),
+        }
+    }
+
+    fn text(body: &str) -> Self {
+        Self::new(200, "text/plain", body)
+    }
+
+    fn not_found() -> Self {
+        Self::new(404, "text/plain", "Not Found")
+    }
+}
+
+/// A legacy endpoint answered by the proxy itself.
+#[derive(Debug, PartialEq)]
+enum LegacyRoute {
+    Skin(String),
+    Cape(String),
+    /// The resource listing, as S3 XML or as the older plain text format.
+    ResourceList {
+        xml: bool,
+    },
+    Resource(String),
+    JoinServer,
+    CheckServer,
+    Session,
+    HasPaid(String),
+}
+
+fn legacy_route(url: &Url) -> Option<LegacyRoute> {
+    let host = url.host_str()?;
+    let path = url.path();
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.to_string())
+    };
+    let texture_name = |name: &str| name.strip_suffix(".png").unwrap_or(name).to_string();
+    match host {
+        "s3.amazonaws.com" | "skins.minecraft.net" => {
+            if let Some(name) = path.strip_prefix("/MinecraftSkins/") {
+                Some(LegacyRoute::Skin(texture_name(name)))
+            } else if let Some(name) = path.strip_prefix("/MinecraftCloaks/") {
+                Some(LegacyRoute::Cape(texture_name(name)))
+            } else if path == "/MinecraftResources/" || path == "/MinecraftResources" {
+                Some(LegacyRoute::ResourceList { xml: true })
+            } else {
+                path.strip_prefix("/MinecraftResources/")
+                    .map(|name| LegacyRoute::Resource(name.to_string()))
+            }
+        }
+        "www.minecraft.net" | "minecraft.net" => match path {
+            "/game/joinserver.jsp" => Some(LegacyRoute::JoinServer),
+            "/game/checkserver.jsp" => Some(LegacyRoute::CheckServer),
+            "/haspaid.jsp" => query("user").map(LegacyRoute::HasPaid),
+            "/resources/" => Some(LegacyRoute::ResourceList { xml: false }),
+            "/cloak/get.jsp" => query("user").map(LegacyRoute::Cape),
+            _ => {
+                if let Some(name) = path.strip_prefix("/skin/") {
+                    Some(LegacyRoute::Skin(texture_name(name)))
+                } else {
+                    path.strip_prefix("/resources/")
+                        .map(|name| LegacyRoute::Resource(name.to_string()))
+                }
+            }
+        },
+        "login.minecraft.net" if path == "/session" => Some(LegacyRoute::Session),
+        _ => None,
+    }
+}
+
+async fn handle_connection(mut stream: Async<TcpStream>, state: &ProxyState) -> io::Result<()> {
+    let request = read_request(&mut stream).await?;
+    let response = match Url::parse(&request.target) {
+        Ok(url) => match legacy_route(&url) {
+            Some(route) => {
+                debug!("Legacy proxy: {} {url}", request.method);
+                answer(state, route, &url).await
+            }
+            None => forward(&request, url).await,
+        },
+        Err(_) => HttpResponse::new(400, "text/plain", "Bad Request"),
+    };
+    write_response(&mut stream, response).await
+}
+
+async fn answer(state: &ProxyState, route: LegacyRoute, url: &Url) -> HttpResponse {
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.to_string())
+            .unwrap_or_default()
+    };
+    match route {
+        LegacyRoute::Skin(name) => fetch_texture(&state.upstream, &name, "SKIN")
+            .await
+            .map(|texture| HttpResponse::new(200, "image/png", legacy_skin(texture)))
+            .unwrap_or_else(HttpResponse::not_found),
+        LegacyRoute::Cape(name) => fetch_texture(&state.upstream, &name, "CAPE")
+            .await
+            .map(|texture| HttpResponse::new(200, "image/png", texture))
+            .unwrap_or_else(HttpResponse::not_found),
+        LegacyRoute::ResourceList { xml: true } => {
+            HttpResponse::new(200, "application/xml", resource_list_xml(&state.assets))
+        }
+        LegacyRoute::ResourceList { xml: false } => {
+            HttpResponse::text(&resource_list_text(&state.assets))
+        }
+        LegacyRoute::Resource(name) => match state.assets.objects.get(&name) {
+            Some(object) => {
+                let path = state
+                    .assets
+                    .objects_root
+                    .join(&object.hash[0..2])
+                    .join(&object.hash);
+                match async_fs::read(path).await {
+                    Ok(content) => HttpResponse::new(200, "application/octet-stream", content),
+                    Err(_) => HttpResponse::not_found(),
+                }
+            }
+            None => HttpResponse::not_found(),
+        },
+        LegacyRoute::JoinServer => {
+            let joined =
+                join_server(&state.upstream, &query("sessionId"), &query("serverId")).await;
+            HttpResponse::text(if joined { "OK" } else { "Bad login" })
+        }
+        LegacyRoute::CheckServer => {
+            let joined = check_server(&state.upstream, &query("user"), &query("serverId")).await;
+            HttpResponse::text(if joined { "YES" } else { "NO" })
+        }
+        LegacyRoute::Session => HttpResponse::text("OK"),
+        LegacyRoute::HasPaid(name) => {
+            let paid = if name.eq_ignore_ascii_case(&state.player.name) {
+                state.player.paid
+            } else {
+                fetch_profile_id(&state.upstream, &name).await.is_some()
+            };
+            HttpResponse::text(if paid { "true" } else { "false" })
+        }
+    }
+}
+
+/// Looks up the profile id of player `name`, `None` when no such player owns
+/// the game.
+async fn fetch_profile_id(upstream: &LegacyProxyUpstream, name: &str) -> Option<String> {
+    let profile: Value = HTTP_CLIENT
+        .get(format!("{}/users/profiles/minecraft/{name}", upstream.api))
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    profile["id"].as_str().map(str::to_string)
+}
+
+/// Looks up the texture of player `name` through the profile and session APIs.
+async fn fetch_texture(upstream: &LegacyProxyUpstream, name: &str, kind: &str) -> Option<Vec<u8>> {
+    let profile_id = fetch_profile_id(upstream, name).await?;
+    let profile: Value = HTTP_CLIENT
+        .get(format!(
+            "{}/session/minecraft/profile/{profile_id}",
+            upstream.session_server
+        ))
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    let textures = profile["properties"]
+        .as_array()?
+        .iter()
+        .find(|property| property["name"] == "textures")?["value"]
+        .as_str()?;
+    let textures: Value = serde_json::from_slice(&BASE64_STANDARD.decode(textures).ok()?).ok()?;
+    let texture_url = textures["textures"][kind]["url"].as_str()?;
+    let texture = HTTP_CLIENT
+        .get(texture_url)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .bytes()
+        .await
+        .ok()?;
+    Some(texture.to_vec())
+}
+
+/// Converts a 64x64 skin to the 64x32 layout of legacy clients by keeping its
+/// top half, which holds the head, body, right arm and right leg. Other skins
+/// are returned unchanged.
+fn legacy_skin(skin: Vec<u8>) -> Vec<u8> {
+    match crop_skin(&skin) {
+        Ok(Some(cropped)) => cropped,
+        Ok(None) => skin,
+        Err(error) => {
+            warn!("Could not convert a skin for a legacy client: {error}");
+            skin
+        }
+    }
+}
+
+fn crop_skin(skin: &[u8]) -> std::result::Result<Option<Vec<u8>>, String> {
+    let mut decoder = png::Decoder::new(io::Cursor::new(skin));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|error| error.to_string())?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    if width != height {
+        return Ok(None);
+    }
+    let (color_type, bit_depth) = reader.output_color_type();
+    let mut pixels = vec![0; reader.output_buffer_size().ok_or("skin too large")?];
+    let frame = reader
+        .next_frame(&mut pixels)
+        .map_err(|error| error.to_string())?;
+    pixels.truncate(frame.line_size * (height / 2) as usize);
+
+    let mut cropped = Vec::new();
+    let mut encoder = png::Encoder::new(&mut cropped, width, height / 2);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|error| error.to_string())?;
+    writer.finish().map_err(|error| error.to_string())?;
+    Ok(Some(cropped))
+}
+
+/// Legacy clients send `token:<access token>:<profile id>` as the session id.
+async fn join_server(upstream: &LegacyProxyUpstream, session_id: &str, server_id: &str) -> bool {
+    let mut parts = session_id.split(':');
+    let (Some("token"), Some(access_token), Some(profile_id)) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    HTTP_CLIENT
+        .post(format!(
+            "{}/session/minecraft/join",
+            upstream.session_server
+        ))
+        .json(&json!({
+            "accessToken": access_token,
+            "selectedProfile": profile_id,
+            "serverId": server_id,
+        }))
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+async fn check_server(upstream: &LegacyProxyUpstream, user: &str, server_id: &str) -> bool {
+    HTTP_CLIENT
+        .get(format!(
+            "{}/session/minecraft/hasJoined",
+            upstream.session_server
+        ))
+        .query(&[("username", user), ("serverId", server_id)])
+        .send()
+        .await
+        .is_ok_and(|response| response.status() == 200)
+}
+
+fn resource_list_xml(assets: &LegacyAssets) -> String {
+    let mut names: Vec<_> = assets.objects.iter().collect();
+    names.sort_by(|a, b| a.0.cmp(b.0));
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <ListBucketResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\">\
+         <Name>MinecraftResources</Name><Prefix></Prefix><Marker></Marker>\
+         <MaxKeys>1000</MaxKeys><IsTruncated>false</IsTruncated>",
+    );
+    for (name, object) in names {
+        xml.push_str(&format!(
+            "<Contents><Key>{}</Key><LastModified>2013-01-01T00:00:00.000Z</LastModified>\
+             <ETag>\"{}\"</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+            escape_xml(name),
+            object.hash,
+            object.size
+        ));
+    }
+    xml.push_str("</ListBucketResult>");
+    xml
+}
+
+fn resource_list_text(assets: &LegacyAssets) -> String {
+    let mut names: Vec<_> = assets.objects.iter().collect();
+    names.sort_by(|a, b| a.0.cmp(b.0));
+    names
+        .into_iter()
+        .map(|(name, object)| format!("{name},{},1\n", object.size))
+        .collect()
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Passes requests that are not legacy routes through to their destination.
+async fn forward(request: &HttpRequest, url: Url) -> HttpResponse {
+    let Ok(method) = reqwest::Method::from_bytes(request.method.as_bytes()) else {
+        return HttpResponse::new(405, "text/plain", "Method Not Allowed");
+    };
+    if method == reqwest::Method::CONNECT {
+        return HttpResponse::new(405, "text/plain", "Method Not Allowed");
+    }
+    let mut builder = HTTP_CLIENT.request(method, url);
+    for (name, value) in &request.headers {
+        let name = name.to_ascii_lowercase();
+        if !matches!(
+            name.as_str(),
+            "host" | "connection" | "proxy-connection" | "content-length" | "user-agent"
+        ) {
+            builder = builder.header(name, value);
+        }
+    }
+    let response = match builder.body(request.body.clone()).send().await {
+        Ok(response) => response,
+        Err(error) => {
+            debug!("Legacy proxy could not forward request: {error}");
+            return HttpResponse::new(502, "text/plain", "Bad Gateway");
+        }
+    };
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    match response.bytes().await {
+        Ok(body) => HttpResponse {
+            status,
+            content_type,
+            body: body.to_vec(),
+        },
+        Err(_) => HttpResponse::new(502, "text/plain", "Bad Gateway"),
+    }
+}
+
+async fn read_request(stream: &mut Async<TcpStream>) -> io::Result<HttpRequest> {
+    let mut buffer = Vec::with_capacity(4096);
+    let mut chunk = [0; 4096];
+    let header_end = loop {
+        let size = stream.read(&mut chunk).await?;
+        if size == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buffer.extend_from_slice(&chunk[..size]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if buffer.len() > MAX_HEADER_SIZE {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(io::ErrorKind::InvalidData.into());
+    };
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let size = stream.read(&mut chunk).await?;
+        if size == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..size]);
+    }
+    body.truncate(content_length);
+    Ok(HttpRequest {
+        method: method.to_string(),
+        target: target.to_string(),
+        headers,
+        body,
+    })
+}
+
+async fn write_response(stream: &mut Async<TcpStream>, response: HttpResponse) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        502 => "Bad Gateway",
+        _ => "",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves the profile, session and texture routes of the upstream skin API.
+    fn start_stand_in_upstream() -> (u16, JoinHandle<()>) {
+        let listener = Async::<TcpListener>::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.get_ref().local_addr().unwrap().port();
+        let task = tauri::async_runtime::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let request = read_request(&mut stream).await.unwrap();
+                let textures = BASE64_STANDARD.encode(
+                    json!({
+                        "textures": {
+                            "SKIN": { "url": format!("http://127.0.0.1:{port}/texture/skin") }
+                        }
+                    })
+                    .to_string(),
+                );
+                let response = match request.target.as_str() {
+                    "/users/profiles/minecraft/Notch" => HttpResponse::new(
+                        200,
+                        "application/json",
+                        json!({ "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch" })
+                            .to_string(),
+                    ),
+                    "/session/minecraft/profile/069a79f444e94726a5befca90e38aaf5" => {
+                        HttpResponse::new(
+                            200,
+                            "application/json",
+                            json!({
+                                "id": "069a79f444e94726a5befca90e38aaf5",
+                                "name": "Notch",
+                                "properties": [{ "name": "textures", "value": textures }]
+                            })
+                            .to_string(),
+                        )
+                    }
+                    "/texture/skin" => HttpResponse::new(200, "image/png", b"skin".to_vec()),
+                    _ => HttpResponse::not_found(),
+                };
+                write_response(&mut stream, response).await.unwrap();
+            }
+        });
+        (port, task)
+    }
+
+    async fn proxy_get(proxy: &LegacyProxy, url: &str) -> String {
+        let mut stream = Async::<TcpStream>::connect((Ipv4Addr::LOCALHOST, proxy.port()))
+            .await
+            .unwrap();
+        stream
+            .write_all(format!("GET {url} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[test]
+    fn routes_legacy_endpoints() {
+        let route = |url: &str| legacy_route(&Url::parse(url).unwrap());
+        assert_eq!(
+            route("http://s3.amazonaws.com/MinecraftSkins/Notch.png"),
+            Some(LegacyRoute::Skin("Notch".to_string()))
+        );
+        assert_eq!(
+            route("http://www.minecraft.net/cloak/get.jsp?user=Notch"),
+            Some(LegacyRoute::Cape("Notch".to_string()))
+        );
+        assert_eq!(
+            route("http://s3.amazonaws.com/MinecraftResources/"),
+            Some(LegacyRoute::ResourceList { xml: true })
+        );
+        assert_eq!(
+            route("http://www.minecraft.net/resources/sound/step/grass1.ogg"),
+            Some(LegacyRoute::Resource("sound/step/grass1.ogg".to_string()))
+        );
+        assert_eq!(
+            route("http://www.minecraft.net/haspaid.jsp?user=Notch"),
+            Some(LegacyRoute::HasPaid("Notch".to_string()))
+        );
+        assert_eq!(route("http://example.com/MinecraftSkins/Notch.png"), None);
+    }
+
+    #[tokio::test]
+    async fn serves_skins_from_upstream() {
+        let _ = shared::SHOULD_USE_SYSTEM_PROXY.set(false);
+        let (upstream_port, upstream_task) = start_stand_in_upstream();
+        let upstream = LegacyProxyUpstream {
+            api: format!("http://127.0.0.1:{upstream_port}"),
+            session_server: format!("http://127.0.0.1:{upstream_port}"),
+        };
+        let player = LegacyPlayer {
+            name: "Steve".to_string(),
+            paid: false,
+        };
+        let proxy = LegacyProxy::start(upstream, player, LegacyAssets::default()).unwrap();
+
+        let response = proxy_get(&proxy, "http://s3.amazonaws.com/MinecraftSkins/Notch.png").await;
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("\r\n\r\nskin"));
+
+        let response =
+            proxy_get(&proxy, "http://skins.minecraft.net/MinecraftSkins/jeb_.png").await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        let response = proxy_get(&proxy, "http://www.minecraft.net/haspaid.jsp?user=Notch").await;
+        assert!(response.ends_with("\r\n\r\ntrue"));
+        let response = proxy_get(&proxy, "http://www.minecraft.net/haspaid.jsp?user=Steve").await;
+        assert!(response.ends_with("\r\n\r\nfalse"));
+        upstream_task.abort();
+    }
+
+    #[test]
+    fn crops_modern_skins() {
+        let mut skin = Vec::new();
+        let mut encoder = png::Encoder::new(&mut skin, 64, 64);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        let pixels: Vec<u8> = (0..64 * 64 * 4).map(|index| (index / 256) as u8).collect();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+
+        let cropped = legacy_skin(skin);
+        let mut reader = png::Decoder::new(io::Cursor::new(&cropped))
+            .read_info()
+            .unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (64, 32));
+        let mut cropped_pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut cropped_pixels).unwrap();
+        assert_eq!(cropped_pixels, pixels[..64 * 32 * 4]);
+
+        assert_eq!(legacy_skin(b"skin".to_vec()), b"skin");
+    }
+}
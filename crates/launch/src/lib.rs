@@ -3,61 +3,121 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use std::{
-    io::BufRead,
+    collections::HashMap,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
+    process::Stdio,
     str::FromStr,
     sync::{
-        Arc, Mutex,
+        Arc, LazyLock, Mutex,
         atomic::{AtomicBool, Ordering},
     },
     thread,
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime},
 };
 
 use account::Account;
-use arguments::generate_command_arguments;
-use complete::complete_files;
-use config::Config;
+use agents::{prepare_agents, validate_agent};
+use arguments::{LaunchArguments, generate_command_arguments, prepare_launch_files};
+use command::{GameCommand, ScriptShell};
+use complete::{RepairReport, complete_files, repair_files};
+use config::{Config, launch::JvmPreset};
+use diagnosis::{Diagnosis, Finding, analyze_instance};
 use download::progress::DownloadState;
 use folder::{DATA_LOCATION, MinecraftLocation};
+use hooks::{HookContext, HookKind, run_hook, spawn_hook};
 use instance::Instance;
-use java_runtime::JavaArch;
+use integrity::invalidate_integrity;
+use java_runtime::{JavaArch, JavaRuntime, JavaVendor};
+use legacy_proxy::{
+    LegacyAssets, LegacyPlayer, LegacyProxy, LegacyProxyUpstream, needs_legacy_proxy,
+};
 use log::{debug, error, info, warn};
-use options::LaunchOptions;
-use platform::{OsArch, OsFamily, PLATFORM_INFO};
+use logs::{Log, Log4jParser, LogEvent, LogFilter, LogStream, for_each_line};
+use natives::{download_replacements, setup_natives};
+use natives_cache::natives_directory;
+use options::{LaunchOptions, refresh_bound_account};
+use plan::{LaunchPlan, plan_launch};
+use platform::{OsArch, PLATFORM_INFO};
 use serde::Serialize;
 use statistics::{StatisticsProfile, log_launch};
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
 use tauri::{
-    Manager, Runtime, State, command,
+    Emitter, Manager, Runtime, State, command,
     ipc::Channel,
     plugin::{Builder, TauriPlugin},
 };
-use uuid::Uuid;
-use version::{ResolvedVersion, Version, resolve_version};
+use version::{
+    ResolvedLibrary, ResolvedVersion, Version, load_patches, resolve_version_with_patches,
+};
 
+mod agents;
 mod arguments;
+pub mod command;
 mod complete;
+pub mod diagnosis;
 pub mod error;
+mod gpu;
+pub mod hooks;
+mod integrity;
+pub mod legacy_proxy;
+pub mod logs;
+pub mod monitor;
+mod natives;
+mod natives_cache;
+mod offline;
 mod options;
+mod performance;
+mod placeholders;
+pub mod plan;
+pub mod process;
+pub mod profiling;
+pub mod quick_play;
+pub mod tuning;
 
 use error::*;
+use performance::PerformanceTools;
+use placeholders::{expand_shell_placeholders, expand_shell_words, launcher_placeholders};
+use process::{GAME_EVENT_CHANNEL, GameRegistry, RunningGame};
+use profiling::{ProfileReport, new_recording_path};
+use quick_play::QuickPlay;
+use tuning::{import_preset, list_presets};
 
 #[derive(Clone, Default)]
 struct PluginState {
-    task: Arc<Mutex<Option<tokio::task::AbortHandle>>>,
+    /// Launch tasks in progress, by instance id.
+    tasks: Arc<Mutex<HashMap<String, tokio::task::AbortHandle>>>,
+    games: GameRegistry,
 }
 
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
     Builder::new("launch")
         .invoke_handler(tauri::generate_handler![
             cmd_spawn_launch_task,
-            cmd_cancel_launch_task
+            cmd_cancel_launch_task,
+            cmd_list_running_games,
+            cmd_kill_game,
+            cmd_focus_game,
+            cmd_analyze_crash,
+            cmd_repair_instance,
+            cmd_start_profiling,
+            cmd_stop_profiling,
+            cmd_list_profile_reports,
+            cmd_get_game_logs,
+            cmd_subscribe_game_logs,
+            cmd_unsubscribe_game_logs,
+            cmd_export_launch_script,
+            cmd_plan_launch,
+            cmd_list_jvm_presets,
+            cmd_import_jvm_preset,
+            cmd_detect_performance_tools,
         ])
         .setup(|app, _| {
-            app.manage(PluginState::default());
+            let app_handle = app.clone();
+            app.manage(PluginState {
+                tasks: Arc::default(),
+                games: GameRegistry::new(move |event| {
+                    let _ = app_handle.emit(GAME_EVENT_CHANNEL, event);
+                }),
+            });
             Ok(())
         })
         .build()
@@ -78,30 +138,53 @@ pub enum LaunchEvent {
     LogTextureLoaded,
 }
 
+/// Launches the instance, joining `quick_play` instead of the server
+/// configured for the instance when given. Nothing is downloaded when
+/// `offline`, and the game is recorded with Java Flight Recorder when
+/// `profile`.
 #[command]
 async fn cmd_spawn_launch_task(
     state: State<'_, PluginState>,
     config: Config,
     instance: Instance,
+    quick_play: Option<QuickPlay>,
+    offline: Option<bool>,
+    profile: Option<bool>,
     channel: Channel<LaunchEvent>,
-) -> Result<u32> {
-    if state.task.lock().expect("Internal error").is_some() {
-        return Err(Error::AnothorInstanceLaunching);
+) -> Result<RunningGame> {
+    let instance_id = instance.id.clone();
+    if state
+        .tasks
+        .lock()
+        .expect("Internal error")
+        .contains_key(&instance_id)
+    {
+        return Err(Error::AlreadyLaunching);
     }
     let task_status = Arc::new(Mutex::new(LaunchEvent::Prepare));
     let finished = Arc::new(AtomicBool::new(false));
     let handle = tokio::spawn({
         let task_status_cloned = task_status.clone();
         let finished = finished.clone();
+        let games = state.games.clone();
         async move {
-            let result = launch(config, instance, task_status_cloned).await;
+            let result = launch(
+                config,
+                instance,
+                quick_play,
+                offline.unwrap_or_default(),
+                profile.unwrap_or_default(),
+                games,
+                task_status_cloned,
+            )
+            .await;
             finished.store(true, Ordering::SeqCst);
             result
         }
     });
     {
-        let mut current_task = state.task.lock().expect("Internal error");
-        *current_task = Some(handle.abort_handle());
+        let mut tasks = state.tasks.lock().expect("Internal error");
+        tasks.insert(instance_id.clone(), handle.abort_handle());
     }
     let event_sender_thread = {
         let status_cloned = task_status.clone();
@@ -122,32 +205,157 @@ async fn cmd_spawn_launch_task(
     };
     let _ = event_sender_thread.join();
     {
-        let mut current_task = state.task.lock().expect("Internal error");
-        *current_task = None;
+        let mut tasks = state.tasks.lock().expect("Internal error");
+        tasks.remove(&instance_id);
     }
     result
 }
 
 #[command]
-async fn cmd_cancel_launch_task(state: State<'_, PluginState>) -> Result<()> {
-    let mut current_task = state.task.lock().expect("Internal error");
-    if let Some(handle) = current_task.clone() {
+async fn cmd_cancel_launch_task(state: State<'_, PluginState>, instance_id: String) -> Result<()> {
+    let mut tasks = state.tasks.lock().expect("Internal error");
+    if let Some(handle) = tasks.remove(&instance_id) {
         handle.abort();
         warn!("Cancelling launch!");
     }
-    *current_task = None;
     Ok(())
 }
 
-/// Represents a log message associated with a specific instance.
-#[derive(Clone, Serialize)]
-pub struct Log {
-    /// The UUID of the instance this log belongs to.
-    #[serde(rename = "instanceName")]
-    pub instance_id: Uuid,
+#[command]
+fn cmd_list_running_games(state: State<'_, PluginState>) -> Vec<RunningGame> {
+    state.games.list()
+}
+
+#[command]
+fn cmd_kill_game(state: State<'_, PluginState>, pid: u32) -> Result<()> {
+    state.games.kill(pid)
+}
+
+#[command]
+fn cmd_focus_game(state: State<'_, PluginState>, pid: u32) -> Result<()> {
+    state.games.focus(pid)
+}
+
+/// Returns the kept log events of the game `pid` matching `filter`.
+#[command]
+fn cmd_get_game_logs(state: State<'_, PluginState>, pid: u32, filter: LogFilter) -> Vec<LogEvent> {
+    state.games.logs().history(pid, &filter)
+}
+
+/// Streams the kept and all future log events of the game `pid` matching
+/// `filter` to `channel`, until the game exits or the subscription is removed.
+#[command]
+fn cmd_subscribe_game_logs(
+    state: State<'_, PluginState>,
+    pid: u32,
+    filter: LogFilter,
+    channel: Channel<Log>,
+) -> Result<u64> {
+    state
+        .games
+        .logs()
+        .subscribe(pid, filter, move |log| {
+            let _ = channel.send(log.clone());
+        })
+        .ok_or(Error::GameNotRunning(pid))
+}
+
+#[command]
+fn cmd_unsubscribe_game_logs(state: State<'_, PluginState>, subscription: u64) {
+    state.games.logs().unsubscribe(subscription);
+}
+
+/// Writes a launch script of the instance to `output`, for the shell of the
+/// current platform unless `shell` is given.
+#[command]
+async fn cmd_export_launch_script(
+    config: Config,
+    instance: Instance,
+    output: PathBuf,
+    shell: Option<ScriptShell>,
+) -> Result<()> {
+    export_launch_script(
+        &config,
+        &instance,
+        &output,
+        shell.unwrap_or_else(ScriptShell::native),
+    )
+    .await
+}
+
+/// Resolves the full command line of the instance without launching it.
+#[command]
+async fn cmd_plan_launch(config: Config, instance: Instance) -> Result<LaunchPlan> {
+    plan_launch(&config, &instance).await
+}
+
+/// Lists the built-in and user JVM tuning presets.
+#[command]
+fn cmd_list_jvm_presets(config: Config) -> Vec<JvmPreset> {
+    list_presets(&config)
+}
 
-    /// The content of the log message.
-    pub content: String,
+/// Parses a shared JVM tuning preset, so it can be added to the user presets.
+#[command]
+fn cmd_import_jvm_preset(json: String) -> Result<JvmPreset> {
+    import_preset(&json)
+}
+
+/// Detects the performance tools installed on the system.
+#[command]
+fn cmd_detect_performance_tools() -> PerformanceTools {
+    PerformanceTools::detect()
+}
+
+/// Analyzes the latest crash report and logs of an instance.
+#[command]
+fn cmd_analyze_crash(instance_id: String) -> Diagnosis {
+    analyze_instance(&DATA_LOCATION.get_instance_root(&instance_id), 0)
+}
+
+/// Hashes every game file of the instance and downloads the missing or
+/// corrupted ones, which launches only do for files that changed.
+#[command]
+async fn cmd_repair_instance(config: Config, instance: Instance) -> Result<RepairReport> {
+    let minecraft_location = MinecraftLocation::new(&DATA_LOCATION.root);
+    repair_files(&instance, &minecraft_location, &config.download).await
+}
+
+/// Starts recording the running game `pid` with Java Flight Recorder.
+#[command]
+async fn cmd_start_profiling(state: State<'_, PluginState>, pid: u32) -> Result<()> {
+    let game = state.games.get(pid).ok_or(Error::GameNotRunning(pid))?;
+    tokio::task::spawn_blocking(move || profiling::start_recording(&game)).await?
+}
+
+/// Stops the recording of the running game `pid` and summarizes it.
+#[command]
+async fn cmd_stop_profiling(state: State<'_, PluginState>, pid: u32) -> Result<ProfileReport> {
+    let game = state.games.get(pid).ok_or(Error::GameNotRunning(pid))?;
+    tokio::task::spawn_blocking(move || profiling::stop_recording(&game)).await?
+}
+
+/// Lists the profile reports of an instance, newest first.
+#[command]
+fn cmd_list_profile_reports(instance_id: String) -> Vec<ProfileReport> {
+    profiling::list_reports(&instance_id)
+}
+
+/// Advances the launch progress when the game logs a known startup message.
+fn update_launch_status(status: &Mutex<LaunchEvent>, message: &str) {
+    let event = if message.contains("Setting user:") {
+        LaunchEvent::LogSettingUser
+    } else if message.to_lowercase().contains("lwjgl version") {
+        info!("Found LWJGL version, the game seems to have started successfully.");
+        LaunchEvent::LogLwjglVersion
+    } else if message.contains("OpenAL initialized") {
+        LaunchEvent::LogOpenALLoaded
+    } else if message.contains("Created") {
+        LaunchEvent::LogTextureLoaded
+    } else {
+        return;
+    };
+    *status.lock().expect("Internal error") = event;
 }
 
 /// Launches a Minecraft instance asynchronously via the Tauri command system.
@@ -155,9 +363,14 @@ pub struct Log {
 /// # Arguments
 /// * `storage` - Application state that holds shared configuration and data.
 /// * `instance` - The Minecraft instance to launch.
+/// * `quick_play` - Where the game goes after it starts, overriding the server
+///   configured for the instance.
+/// * `offline` - Launches without network: the cached session of the account
+///   is used and files are verified locally instead of being downloaded.
+/// * `games` - The registry the started game process is tracked in.
 ///
 /// # Returns
-/// * `Ok(())` - If the instance was successfully launched.
+/// * `Ok(RunningGame)` - If the instance was successfully launched.
 /// * `Err(())` - If there was an error during launch (e.g., account not found).
 ///
 /// # Side Effects
@@ -166,12 +379,19 @@ pub struct Log {
 pub async fn launch(
     config: Config,
     instance: Instance,
+    quick_play: Option<QuickPlay>,
+    offline: bool,
+    profile: bool,
+    games: GameRegistry,
     status: Arc<Mutex<LaunchEvent>>,
-) -> Result<u32> {
+) -> Result<RunningGame> {
     info!(
         "Starting Minecraft client, instance: {}",
         instance.config.name
     );
+    if offline {
+        info!("Launching offline, no file will be downloaded");
+    }
     print_instance_info(&instance);
     let minecraft_location = MinecraftLocation::new(&DATA_LOCATION.root);
 
@@ -188,41 +408,160 @@ pub async fn launch(
             let mut status = status.lock().expect("Internal error");
             *status = LaunchEvent::CompleteFiles(progress.clone());
         }
-        complete_files(&instance, &minecraft_location, progress, &config.download).await?;
+        complete_files(
+            &instance,
+            &minecraft_location,
+            progress,
+            &config.download,
+            offline,
+        )
+        .await?;
     }
 
     info!("Generating startup parameters");
-    let version_json_path = minecraft_location.get_version_json(instance.get_version_id()?);
-    let raw_version_json = async_fs::read_to_string(version_json_path).await?;
-    let resolved_version = resolve_version(
-        &Version::from_str(&raw_version_json)?,
+    let mut launch_options = LaunchOptions::new(&config, &instance).await?;
+    if quick_play.is_some() {
+        launch_options.quick_play = quick_play;
+    }
+    if offline {
+        launch_options.selected_account =
+            offline::cached_account(launch_options.selected_account).await;
+    } else if !config.launch.skip_refresh_account && instance.config.account.is_some() {
+        {
+            let mut status = status.lock().expect("Internal error");
+            *status = LaunchEvent::RefreshAccount;
+        }
+        launch_options.selected_account =
+            refresh_bound_account(launch_options.selected_account).await?;
+    }
+    let mut resolved_version = resolve_instance_version(
+        &instance,
         &minecraft_location,
-        &[],
+        &launch_options.enabled_features(),
     )
     .await?;
-    let resolved_java = resolve_java_executable(&config, &instance, &resolved_version).await?;
+    if let Some(assets) = &resolved_version.assets
+        && let Err(error) = install::vanilla::materialize_legacy_assets(
+            &minecraft_location,
+            assets,
+            &DATA_LOCATION.get_instance_root(&instance.id),
+        )
+        .await
+    {
+        warn!("Failed to prepare legacy assets, the game may have no sounds: {error}");
+    }
+    let resolved_java =
+        resolve_java_executable(&config, &instance, &resolved_version, true).await?;
     {
         let mut status = status.lock().expect("Internal error");
         *status = LaunchEvent::GenerateScriptlet;
     }
-    let launch_options = LaunchOptions::new(&config, &instance, resolved_java.arch)?;
-    if let Account::Yggdrasil(_) = launch_options.selected_account {
-        let progress = DownloadState::default();
-        install::authlib_injector::ensure_latest(&progress).await?;
+    launch_options.placeholders = launcher_placeholders(
+        &instance,
+        launch_options.selected_account.get_profile_name(),
+        &resolved_java.path,
+    );
+    launch_options.allocate_memory(&instance, resolved_java.arch);
+    launch_options.select_jvm_flags(resolved_java.major_version, resolved_java.vendor);
+    let natives = setup_natives(
+        &mut resolved_version,
+        &launch_options.natives_mode,
+        resolved_java.arch,
+    )?;
+    launch_options.natives_directory = Some(natives_directory(
+        &minecraft_location,
+        &resolved_version,
+        resolved_java.arch,
+    ));
+    download_replacements(
+        &minecraft_location,
+        &natives.replaced_libraries,
+        &config.download,
+        offline,
+    )
+    .await?;
+    if let Account::Yggdrasil(account) = &launch_options.selected_account {
+        if offline {
+            offline::ensure_authlib_injector()?;
+            launch_options.yggdrasil_prefetched =
+                offline::cached_yggdrasil_metadata(&account.api_root);
+        } else {
+            let progress = DownloadState::default();
+            install::authlib_injector::ensure_latest(&progress).await?;
+            launch_options.yggdrasil_prefetched =
+                account::yggdrasil::yggdrasil_server::get_server_info_base64(&account.api_root)
+                    .await
+                    .ok();
+            if let Some(metadata) = &launch_options.yggdrasil_prefetched {
+                offline::save_yggdrasil_metadata(&account.api_root, metadata);
+            }
+        }
+    }
+    prepare_agents(&launch_options.java_agents, &config.download, offline).await?;
+    if profile {
+        if resolved_java.major_version.is_some_and(|major| major < 11) {
+            warn!("Java Flight Recorder requires Java 11 or 8u262, the game may fail to start");
+        }
+        let recording = new_recording_path(&instance.id)?;
+        info!("Recording the game to {}", recording.display());
+        launch_options.profile_recording = Some(recording);
     }
-    let command_arguments = generate_command_arguments(
+    launch_options.natives_directory =
+        Some(prepare_launch_files(&minecraft_location, &launch_options, &resolved_version).await?);
+    let mut arguments = generate_command_arguments(
         &minecraft_location,
         &instance,
         &launch_options,
         &resolved_version,
     )
     .await?;
+    arguments.jvm.extend(natives.jvm_arguments);
+    let legacy_proxy = if launch_options.legacy_proxy && needs_legacy_proxy(&resolved_version) {
+        let assets = resolved_version
+            .assets
+            .as_deref()
+            .and_then(|assets| LegacyAssets::load(&minecraft_location, assets).ok())
+            .unwrap_or_default();
+        let proxy = LegacyProxy::start(
+            LegacyProxyUpstream::for_account(&launch_options.selected_account),
+            LegacyPlayer::for_account(&launch_options.selected_account),
+            assets,
+        )?;
+        arguments.jvm.splice(0..0, proxy.jvm_arguments());
+        Some(proxy)
+    } else {
+        None
+    };
+
+    let hook_context = hook_context(&instance, &launch_options, &resolved_java.path);
+    let pre_launch = expand_shell_placeholders(
+        &launch_options.execute_before_launch,
+        &launch_options.placeholders,
+    );
+    let hook_timeout = Duration::from_secs(launch_options.hook_timeout_secs);
+    tokio::task::spawn_blocking(move || {
+        run_hook(
+            HookKind::PreLaunch,
+            &pre_launch,
+            &hook_context,
+            hook_timeout,
+        )
+    })
+    .await??;
 
+    let game_command = game_command(
+        resolved_java.path.clone(),
+        arguments,
+        &launch_options,
+        &instance,
+    )?;
     let result = spawn_minecraft_process(
-        command_arguments,
+        game_command,
         launch_options,
         instance,
         resolved_java.path,
+        legacy_proxy,
+        games,
         status,
     )
     .await;
@@ -232,6 +571,199 @@ pub async fn launch(
     result
 }
 
+/// Writes a script to `output` that launches the instance the way the launcher
+/// does, so the game can be started without the launcher.
+///
+/// Files are not checked. The script contains the access token of the selected
+/// account, and neither the legacy proxy nor the lifecycle hooks are part of it.
+pub async fn export_launch_script(
+    config: &Config,
+    instance: &Instance,
+    output: &Path,
+    shell: ScriptShell,
+) -> Result<()> {
+    let minecraft_location = MinecraftLocation::new(&DATA_LOCATION.root);
+    let prepared = prepare_launch(config, instance, &minecraft_location, false).await?;
+    download_replacements(
+        &minecraft_location,
+        &prepared.replaced_libraries,
+        &config.download,
+        false,
+    )
+    .await?;
+    let game_command = game_command(
+        prepared.java.path,
+        prepared.arguments,
+        &prepared.launch_options,
+        instance,
+    )?;
+    if let Some(parent) = output.parent() {
+        async_fs::create_dir_all(parent).await?;
+    }
+    async_fs::write(output, game_command.to_script(shell)).await?;
+    #[cfg(unix)]
+    if shell == ScriptShell::Sh {
+        use std::os::unix::fs::PermissionsExt;
+        async_fs::set_permissions(output, std::fs::Permissions::from_mode(0o755)).await?;
+    }
+    info!("Exported the launch script to {}", output.display());
+    Ok(())
+}
+
+/// Creates the command running the game, wrapped by the wrap command of the
+/// user and then by the performance tools.
+fn game_command(
+    java_path: PathBuf,
+    arguments: LaunchArguments,
+    launch_options: &LaunchOptions,
+    instance: &Instance,
+) -> Result<GameCommand> {
+    let performance = performance::compose(
+        &launch_options.linux_performance,
+        PerformanceTools::detect(),
+    )?;
+    let mut game_command = GameCommand::new(
+        java_path,
+        arguments.into_command_line(),
+        DATA_LOCATION.get_instance_root(&instance.id),
+    );
+    // The wrap command is split before its placeholders are expanded.
+    game_command.prepend(expand_shell_words(
+        &launch_options.wrap_command,
+        &launch_options.placeholders,
+    )?);
+    game_command.prepend(performance.prefix);
+    game_command.env = game_environment(launch_options, performance.env);
+    Ok(game_command)
+}
+
+/// Joins the GPU offloading variables and `performance_env`, which takes
+/// precedence.
+pub(crate) fn game_environment(
+    launch_options: &LaunchOptions,
+    performance_env: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let mut env = gpu::gpu_environment(&launch_options.gpu);
+    env.retain(|(key, _)| !performance_env.iter().any(|(name, _)| name == key));
+    env.extend(performance_env);
+    env
+}
+
+/// The resolved version, Java runtime, options and arguments of a launch.
+pub(crate) struct PreparedLaunch {
+    resolved_version: ResolvedVersion,
+    java: ResolvedJava,
+    launch_options: LaunchOptions,
+    arguments: LaunchArguments,
+    /// The libraries replacing the natives of the version, not downloaded yet.
+    replaced_libraries: Vec<ResolvedLibrary>,
+}
+
+/// Resolves everything needed to launch the instance, without touching the
+/// network. A `dry_run` writes no file, so the game icon and the natives are
+/// not written, and spawns no Java runtime, see [`resolve_java_executable`].
+pub(crate) async fn prepare_launch(
+    config: &Config,
+    instance: &Instance,
+    minecraft_location: &MinecraftLocation,
+    dry_run: bool,
+) -> Result<PreparedLaunch> {
+    let mut launch_options = LaunchOptions::new(config, instance).await?;
+    let mut resolved_version = resolve_instance_version(
+        instance,
+        minecraft_location,
+        &launch_options.enabled_features(),
+    )
+    .await?;
+    let java = resolve_java_executable(config, instance, &resolved_version, !dry_run).await?;
+    launch_options.placeholders = launcher_placeholders(
+        instance,
+        launch_options.selected_account.get_profile_name(),
+        &java.path,
+    );
+    launch_options.allocate_memory(instance, java.arch);
+    launch_options.select_jvm_flags(java.major_version, java.vendor);
+    let natives = setup_natives(
+        &mut resolved_version,
+        &launch_options.natives_mode,
+        java.arch,
+    )?;
+    launch_options.natives_directory = Some(natives_directory(
+        minecraft_location,
+        &resolved_version,
+        java.arch,
+    ));
+    for agent in &launch_options.java_agents {
+        validate_agent(agent)?;
+    }
+    if !dry_run {
+        launch_options.natives_directory = Some(
+            prepare_launch_files(minecraft_location, &launch_options, &resolved_version).await?,
+        );
+    }
+    let mut arguments = generate_command_arguments(
+        minecraft_location,
+        instance,
+        &launch_options,
+        &resolved_version,
+    )
+    .await?;
+    arguments.jvm.extend(natives.jvm_arguments);
+    Ok(PreparedLaunch {
+        resolved_version,
+        java,
+        launch_options,
+        arguments,
+        replaced_libraries: natives.replaced_libraries,
+    })
+}
+
+/// Reads the version JSON of the instance and resolves it together with the
+/// component patches stored in the instance folder.
+pub(crate) async fn resolve_instance_version(
+    instance: &Instance,
+    minecraft_location: &MinecraftLocation,
+    enabled_features: &[String],
+) -> Result<ResolvedVersion> {
+    let version_json_path = minecraft_location.get_version_json(instance.get_version_id()?);
+    let raw_version_json = async_fs::read_to_string(version_json_path).await?;
+    let patches = load_patches(instance.get_patches_root())?;
+    if !patches.is_empty() {
+        info!(
+            "Applying version patches: {}",
+            patches
+                .iter()
+                .map(|patch| patch.uid.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    Ok(resolve_version_with_patches(
+        &Version::from_str(&raw_version_json)?,
+        minecraft_location,
+        enabled_features,
+        &patches,
+    )
+    .await?)
+}
+
+fn hook_context(
+    instance: &Instance,
+    launch_options: &LaunchOptions,
+    java_path: &Path,
+) -> HookContext {
+    HookContext {
+        instance_id: instance.id.clone(),
+        instance_name: instance.config.name.clone(),
+        instance_dir: DATA_LOCATION.get_instance_root(&instance.id),
+        minecraft_version: instance.config.runtime.minecraft.clone(),
+        account_name: launch_options.selected_account.get_profile_name(),
+        java_path: java_path.to_path_buf(),
+        pid: None,
+        exit_code: None,
+    }
+}
+
 fn print_instance_info(instance: &Instance) {
     info!("------------- Instance runtime config -------------");
     info!("-> Minecraft: {}", instance.config.runtime.minecraft);
@@ -245,10 +777,28 @@ fn print_instance_info(instance: &Instance) {
     };
 }
 
-/// A resolved Java runtime: the executable path together with its architecture.
+/// A resolved Java runtime: the executable path together with what is known
+/// of the runtime.
 struct ResolvedJava {
     path: PathBuf,
     arch: JavaArch,
+    major_version: Option<u32>,
+    vendor: JavaVendor,
+    source: JavaSource,
+}
+
+/// Why a Java runtime was chosen to launch the game.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum JavaSource {
+    /// The `java_path` set for the instance.
+    InstanceSetting,
+    /// The runtime provided by Mojang for the version, as
+    /// `config.prefer_mojang_java` is enabled.
+    MojangRuntime { component: String },
+    /// A runtime installed on the system with the major version required by
+    /// the version.
+    SystemRuntime { major_version: u32 },
 }
 
 /// Resolves the Java executable used to launch the game.
@@ -263,19 +813,31 @@ struct ResolvedJava {
 /// The returned [`ResolvedJava::arch`] carries the bitness of the runtime:
 /// Mojang-provided Java always matches the OS architecture, while scanned
 /// system runtimes report their own parsed architecture. The instance-specific
-/// path is reported as [`JavaArch::Unknown`] since it is not parsed again.
+/// path is probed like a scanned runtime, and reported as [`JavaArch::Unknown`]
+/// when it cannot be identified.
+///
+/// Without `probe`, no runtime is run: only the runtimes inspected or scanned
+/// by earlier launches are known, see [`INSPECTED_JAVA`].
 ///
 /// Returns [`Error::NoSuitableJavaRuntime`] when no usable runtime is found.
 async fn resolve_java_executable(
     config: &Config,
     instance: &Instance,
     resolved_version: &ResolvedVersion,
+    probe: bool,
 ) -> Result<ResolvedJava> {
     if let Some(java_path) = &instance.config.launch_config.java_path {
         info!("Using instance-specific Java: {java_path}");
+        let path = PathBuf::from(java_path);
+        let runtime = find_java(&path, probe).await;
         return Ok(ResolvedJava {
-            path: PathBuf::from(java_path),
-            arch: JavaArch::Unknown,
+            path,
+            arch: runtime
+                .as_ref()
+                .map_or(JavaArch::Unknown, |runtime| runtime.arch),
+            major_version: runtime.as_ref().map(|runtime| runtime.major_version),
+            vendor: runtime.map_or(JavaVendor::Unknown, |runtime| runtime.vendor),
+            source: JavaSource::InstanceSetting,
         });
     }
 
@@ -285,9 +847,18 @@ async fn resolve_java_executable(
     {
         if mojang_path.is_file() {
             info!("Using Mojang-provided Java: {}", mojang_path.display());
+            let runtime = find_java(&mojang_path, probe).await;
             return Ok(ResolvedJava {
                 path: mojang_path,
                 arch: mojang_java_arch(),
+                major_version: Some(runtime.as_ref().map_or(
+                    resolved_version.java_version.major_version as u32,
+                    |runtime| runtime.major_version,
+                )),
+                vendor: runtime.map_or(JavaVendor::Unknown, |runtime| runtime.vendor),
+                source: JavaSource::MojangRuntime {
+                    component: resolved_version.java_version.component.clone(),
+                },
             });
         }
         info!(
@@ -297,9 +868,26 @@ async fn resolve_java_executable(
     }
 
     let required_major_version = resolved_version.java_version.major_version;
-    let runtimes = tokio::task::spawn_blocking(java_runtime::scan_java_runtimes)
-        .await
-        .map_err(|_| Error::Other)??;
+    let runtimes = if probe {
+        let runtimes = tokio::task::spawn_blocking(java_runtime::scan_java_runtimes)
+            .await
+            .map_err(|_| Error::Other)??;
+        for runtime in &runtimes {
+            cache_java(&runtime.path, Some(runtime.clone()));
+        }
+        runtimes
+    } else {
+        let paths: Vec<PathBuf> = INSPECTED_JAVA
+            .lock()
+            .expect("Internal error")
+            .keys()
+            .cloned()
+            .collect();
+        paths
+            .iter()
+            .filter_map(|path| cached_java(path).flatten())
+            .collect()
+    };
     let system_java = runtimes.into_iter().find(|runtime| {
         runtime.major_version == required_major_version as u32
             && runtime.is_valid
@@ -314,11 +902,72 @@ async fn resolve_java_executable(
         return Ok(ResolvedJava {
             path: runtime.path,
             arch: runtime.arch,
+            major_version: Some(runtime.major_version),
+            vendor: runtime.vendor,
+            source: JavaSource::SystemRuntime {
+                major_version: runtime.major_version,
+            },
         });
     }
     Err(Error::NoSuitableJavaRuntime)
 }
 
+/// The inspected and scanned Java executables with their modification time,
+/// so a runtime is only run again once it was replaced.
+static INSPECTED_JAVA: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, Option<JavaRuntime>)>>> =
+    LazyLock::new(Default::default);
+
+/// Returns the runtime of the Java executable at `path`, only looking it up in
+/// [`INSPECTED_JAVA`] without `probe`.
+async fn find_java(path: &Path, probe: bool) -> Option<JavaRuntime> {
+    if probe {
+        inspect_java(path).await
+    } else {
+        cached_java(path).flatten()
+    }
+}
+
+/// Probes the Java executable at `path` for its version, vendor and
+/// architecture.
+async fn inspect_java(path: &Path) -> Option<JavaRuntime> {
+    if let Some(runtime) = cached_java(path) {
+        return runtime;
+    }
+    let runtime = tokio::task::spawn_blocking({
+        let path = path.to_path_buf();
+        move || java_runtime::inspect_java_runtime(&path)
+    })
+    .await
+    .ok()
+    .flatten();
+    cache_java(path, runtime.clone());
+    runtime
+}
+
+/// Returns what was found when the Java executable at `path` was last
+/// inspected, unless it was replaced since.
+fn cached_java(path: &Path) -> Option<Option<JavaRuntime>> {
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    INSPECTED_JAVA
+        .lock()
+        .expect("Internal error")
+        .get(path)
+        .filter(|(inspected_at, _)| *inspected_at == modified)
+        .map(|(_, runtime)| runtime.clone())
+}
+
+/// Records `runtime` as what the Java executable at `path` is.
+fn cache_java(path: &Path, runtime: Option<JavaRuntime>) {
+    if let Ok(modified) = std::fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        INSPECTED_JAVA
+            .lock()
+            .expect("Internal error")
+            .insert(path.to_path_buf(), (modified, runtime));
+    }
+}
+
 /// Maps the current OS architecture to the architecture of the Mojang-provided
 /// Java runtime, which is always built for the host platform.
 fn mojang_java_arch() -> JavaArch {
@@ -331,96 +980,45 @@ fn mojang_java_arch() -> JavaArch {
     }
 }
 
-/// Spawns the Minecraft process by generating and executing a launch script,
-/// customized per operating system and instance configuration.
+/// Spawns the Minecraft process from `game_command` and tracks it until it
+/// exits.
 ///
 /// # Arguments
-/// * `command_arguments` - A list of parsed arguments.
-/// * `minecraft_location` - Path to the Minecraft game files.
-/// * `launch_options` - Launch customization options (pre/post-execution hooks, wrappers, etc.).
-/// * `version_id` - The Minecraft version to launch.
+/// * `game_command` - The command line of the game.
+/// * `launch_options` - Launch customization options (hooks, etc.).
 /// * `instance` - The instance metadata and configuration.
+/// * `java_path` - The Java executable the game runs with.
+/// * `legacy_proxy` - The legacy proxy, kept running until the game exits.
+/// * `games` - The registry the game process is tracked in until it exits.
 ///
 /// # Behavior
-/// * Creates a platform-specific shell script/batch file for launching the game.
-/// * Runs the generated script using a subprocess.
-/// * Streams stdout to detect key launch indicators and forward logs to the frontend.
-/// * Emits `launch_success` event once LWJGL is detected.
-/// * Handles cleanup of native libraries after game launch completes.
+/// * Spawns the game directly, without a shell.
+/// * Streams stdout and stderr into the game logs and detects key launch indicators.
+/// * Samples the resource usage of the game until it exits.
+/// * Runs the exit hooks and analyzes crashes once the game exits.
 async fn spawn_minecraft_process(
-    command_arguments: Vec<String>,
+    game_command: GameCommand,
     launch_options: LaunchOptions,
     instance: Instance,
     java_path: PathBuf,
+    legacy_proxy: Option<LegacyProxy>,
+    games: GameRegistry,
     status: Arc<Mutex<LaunchEvent>>,
-) -> Result<u32> {
-    // TODO: 要求 Java 使用高性能显卡
-    let instance_root = DATA_LOCATION.get_instance_root(&instance.id);
-    let mut commands = String::new();
-    if PLATFORM_INFO.os_family != OsFamily::Windows {
-        commands.push_str("#!/bin/sh\n\n");
-    }
-    let comment_prefix = if PLATFORM_INFO.os_family == OsFamily::Windows {
-        "::"
-    } else {
-        "#"
-    };
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Internal error")
-        .as_millis();
-    commands.push_str(&format!(
-        "{comment_prefix} This file is automatically generated by Conic Launcher.\n"
-    ));
-    commands.push_str(&format!(
-        "{comment_prefix} Conic Launcher has created this file at {timestamp}.\n"
-    ));
-    commands.push_str(&format!(
-        "{comment_prefix} NOTE: Don't use this file to launch game.\n\n"
-    ));
-    commands.push_str(&format!("cd \"{}\"\n", instance_root.to_string_lossy()));
-    commands.push_str(&format!("{}\n", launch_options.execute_before_launch));
-    if !launch_options.wrap_command.trim().is_empty() {
-        commands.push_str(&format!("{} ", launch_options.wrap_command));
-    }
-    // todo(after java exec): add -Dfile.encoding=encoding.name() and other
-    let mut launch_command = match PLATFORM_INFO.os_family {
-        OsFamily::Windows => String::new(),
-        _ => "exec ".to_string(),
-    };
-    launch_command.push_str(&format!("\"{}\"", java_path.to_string_lossy()));
-    for arg in command_arguments.clone() {
-        let arg = if arg.contains(" ") {
-            format!("\"{arg}\"")
-        } else {
-            arg
-        };
-        launch_command.push_str(&format!(" {arg}"));
-    }
-    commands.push_str(&launch_command);
-    let script_path = match PLATFORM_INFO.os_family {
-        OsFamily::Windows => instance_root.join(".cache").join("conic-launch.bat"),
-        _ => instance_root.join(".cache").join("conic-launch.sh"),
-    };
-    if let Some(script_path_parent) = script_path.parent() {
-        std::fs::create_dir_all(script_path_parent)?;
-    }
-    std::fs::write(&script_path, commands)?;
-    info!("The startup script is written to {}", script_path.display());
-    let mut minecraft_process = match PLATFORM_INFO.os_family {
-        OsFamily::Windows => std::process::Command::new(script_path),
-        _ => {
-            info!("Running chmod +x {}", script_path.display());
-            let mut chmod = Command::new("chmod");
-            chmod.args(["+x", script_path.to_string_lossy().to_string().as_ref()]);
-            chmod.status()?;
-            let mut command = std::process::Command::new("bash");
-            command.arg(script_path);
-            command
-        }
+) -> Result<RunningGame> {
+    info!(
+        "Launching {} in {}",
+        game_command.program.display(),
+        game_command.cwd.display()
+    );
+    if let Err(error) = instance::record_last_played(&instance.id) {
+        warn!("Could not record the last played time: {error}");
     }
-    .stdout(Stdio::piped())
-    .spawn()?;
+    let mut minecraft_process = game_command
+        .to_command()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
     {
         let mut status = status.lock().expect("Internal error");
         *status = LaunchEvent::WaitForLaunch;
@@ -430,55 +1028,109 @@ async fn spawn_minecraft_process(
         .stdout
         .take()
         .ok_or(Error::TakeMinecraftStdoutFailed)?;
-    let mut out = std::io::BufReader::new(out);
     let pid = minecraft_process.id();
+    let running_game = RunningGame {
+        pid,
+        instance_id: instance.id.clone(),
+        instance_name: instance.config.name.clone(),
+        account: launch_options.selected_account.get_profile_name(),
+        started_at: process::unix_now(),
+        java_path: java_path.clone(),
+    };
+    games.register(running_game.clone());
+    monitor::spawn_monitor(games.clone(), pid, &java_path, launch_options.max_memory);
+    let err_reader = minecraft_process.stderr.take().map(|err| {
+        let logs = games.logs().clone();
+        thread::spawn(move || {
+            let mut parser = Log4jParser::default();
+            for_each_line(err, |line| {
+                if let Some(event) = parser.push_line(line) {
+                    logs.push(pid, event, LogStream::Stderr);
+                }
+            });
+            if let Some(event) = parser.finish() {
+                logs.push(pid, event, LogStream::Stderr);
+            }
+        })
+    });
+    let mut hook_context = hook_context(&instance, &launch_options, &java_path);
+    hook_context.pid = Some(pid);
+    let hook_timeout = Duration::from_secs(launch_options.hook_timeout_secs);
+    let post_exit = expand_shell_placeholders(
+        &launch_options.execute_after_launch,
+        &launch_options.placeholders,
+    );
+    let on_crash = expand_shell_placeholders(
+        &launch_options.execute_on_crash,
+        &launch_options.placeholders,
+    );
+    let games_cloned = games.clone();
     let status_cloned = status.clone();
-    let mut buf = String::new();
+    let exit_hook_context = hook_context.clone();
+    let instance_id = instance.id.clone();
+    let started_at = running_game.started_at;
+    let profile_recording = launch_options.profile_recording.clone();
+    let profile_java_path = java_path.clone();
     thread::spawn(move || {
-        loop {
-            buf.clear();
-            let size = match out.read_line(&mut buf) {
-                Ok(size) => size,
-                Err(_) => break,
-            };
-            if size == 0 {
-                break;
-            }
-            let line = buf.trim();
-            debug!("[{pid}] {line}");
-            if line.contains("Setting user:") {
-                let mut status = status.lock().expect("Internal error");
-                *status = LaunchEvent::LogSettingUser;
-            }
-            if line.to_lowercase().contains("lwjgl version") {
-                info!("Found LWJGL version, the game seems to have started successfully.");
-                let mut status = status.lock().expect("Internal error");
-                *status = LaunchEvent::LogLwjglVersion;
-            }
-            if line.contains("OpenAL initialized") {
-                let mut status = status.lock().expect("Internal error");
-                *status = LaunchEvent::LogOpenALLoaded;
-            }
-            if line.contains("Created") {
-                let mut status = status.lock().expect("Internal error");
-                *status = LaunchEvent::LogTextureLoaded;
+        let mut hook_context = exit_hook_context;
+        let logs = games_cloned.logs();
+        let mut parser = Log4jParser::default();
+        for_each_line(out, |line| {
+            if let Some(event) = parser.push_line(line) {
+                debug!("[{pid}] {}", event.message);
+                update_launch_status(&status, &event.message);
+                logs.push(pid, event, LogStream::Stdout);
             }
+        });
+        if let Some(event) = parser.finish() {
+            logs.push(pid, event, LogStream::Stdout);
+        }
+        if let Some(err_reader) = err_reader {
+            let _ = err_reader.join();
         }
 
-        let output = match minecraft_process.wait_with_output() {
-            Ok(output) => output,
+        let exit_status = match minecraft_process.wait() {
+            Ok(exit_status) => exit_status,
             Err(_) => {
                 error!("Could not get Minecrafr exit code");
+                games_cloned.finish(pid, None, None);
                 return;
             }
         };
-        if !output.status.success() {
-            // TODO: log analysis and remove libraries lock file
-            // NOTE: Should use tauri global event here
-            // WARN: When failed, frontend should stop all "launching" animation
-            error!("Minecraft exits with error code {}", output.status);
+        let diagnosis = (!exit_status.success()).then(|| {
+            let diagnosis = analyze_instance(&hook_context.instance_dir, started_at);
+            if diagnosis
+                .findings
+                .iter()
+                .any(|finding| matches!(finding, Finding::CorruptedJar { .. }))
+            {
+                // Hash every file on the next launch, as the metadata of a
+                // corrupted jar may not have changed.
+                invalidate_integrity(&instance_id);
+            }
+            diagnosis
+        });
+        games_cloned.finish(pid, exit_status.code(), diagnosis);
+        drop(legacy_proxy);
+        // The recording is gone when it was stopped while the game ran.
+        if let Some(recording) = profile_recording.filter(|recording| recording.is_file()) {
+            match profiling::summarize_recording(&profile_java_path, &instance_id, &recording) {
+                Ok(report) => games_cloned.record_profile(report),
+                Err(error) => warn!("Could not summarize the recording: {error}"),
+            }
+        }
+        hook_context.exit_code = exit_status.code();
+        if let Err(error) = run_hook(HookKind::PostExit, &post_exit, &hook_context, hook_timeout) {
+            warn!("{error}");
+        }
+        if !exit_status.success() {
+            error!("Minecraft exits with error code {}", exit_status);
+            if let Err(error) = run_hook(HookKind::OnCrash, &on_crash, &hook_context, hook_timeout)
+            {
+                warn!("{error}");
+            }
         } else {
-            info!("Minecraft exits with error code {}", output.status);
+            info!("Minecraft exits with error code {}", exit_status);
         }
     });
     let start = Instant::now();
@@ -487,19 +1139,16 @@ async fn spawn_minecraft_process(
     {
         async_io::Timer::after(Duration::from_secs(1)).await;
     }
-    match PLATFORM_INFO.os_family {
-        OsFamily::Windows => {
-            #[cfg(target_os = "windows")]
-            let _ = Command::new("cmd")
-                .args(["/C", &launch_options.execute_after_launch])
-                .creation_flags(0x08000000)
-                .spawn();
-        }
-        _ => {
-            let _ = Command::new("sh")
-                .args(["-c", &launch_options.execute_after_launch])
-                .spawn();
-        }
+    if games.get(pid).is_some() {
+        spawn_hook(
+            HookKind::PostStart,
+            expand_shell_placeholders(
+                &launch_options.execute_after_start,
+                &launch_options.placeholders,
+            ),
+            hook_context,
+            hook_timeout,
+        );
     }
     let statistics_profile = match launch_options.selected_account {
         Account::Microsoft(account) => StatisticsProfile::Microsoft(account.profile.uuid),
@@ -507,5 +1156,5 @@ async fn spawn_minecraft_process(
         Account::Yggdrasil(account) => StatisticsProfile::Yggdrasil(account.identifier),
     };
     log_launch(statistics_profile, instance.id).await.unwrap();
-    Ok(pid)
+    Ok(running_game)
 }
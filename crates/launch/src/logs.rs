@@ -0,0 +1,507 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Live game logs.
+//!
+//! The log configuration written by the launcher prints every event to stdout
+//! with `XMLLayout`, or `LegacyXMLLayout` since 1.21.2, like the client
+//! configurations of Mojang. Those `<log4j:Event>` elements are parsed back
+//! into [`LogEvent`]s here. Lines that are not XML events, such as the output
+//! of versions launched without a log configuration or anything printed to
+//! stderr, become plain events.
+//!
+//! The latest events of every game are kept in a bounded ring, and new events
+//! are pushed to subscribers as they arrive.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, Read},
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// The number of events kept for each game.
+const LOG_RING_CAPACITY: usize = 5000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_ascii_uppercase().as_str() {
+            "TRACE" | "ALL" => Some(Self::Trace),
+            "DEBUG" => Some(Self::Debug),
+            "INFO" => Some(Self::Info),
+            "WARN" | "WARNING" => Some(Self::Warn),
+            "ERROR" | "SEVERE" => Some(Self::Error),
+            "FATAL" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// A single log event of a game.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LogEvent {
+    /// Increases by one for every event of a game.
+    pub sequence: u64,
+    /// Unix timestamp in milliseconds, or the time the event was received
+    /// when it carries none.
+    pub timestamp: u64,
+    pub level: LogLevel,
+    pub thread: Option<String>,
+    pub logger: Option<String>,
+    pub message: String,
+    pub throwable: Option<String>,
+    pub stream: LogStream,
+}
+
+/// A log event of a game, as sent to subscribers.
+#[derive(Debug, Clone, Serialize)]
+pub struct Log {
+    /// The id of the instance this log belongs to.
+    pub instance_id: String,
+    pub pid: u32,
+    #[serde(flatten)]
+    pub event: LogEvent,
+}
+
+/// Selects the events sent to a subscriber.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    /// Events below this level are skipped.
+    pub min_level: Option<LogLevel>,
+    /// Case-insensitive text searched in the message, throwable, logger and
+    /// thread of an event.
+    pub search: Option<String>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, event: &LogEvent) -> bool {
+        if let Some(min_level) = self.min_level
+            && event.level < min_level
+        {
+            return false;
+        }
+        let Some(search) = self.search.as_deref().filter(|search| !search.is_empty()) else {
+            return true;
+        };
+        let search = search.to_lowercase();
+        [
+            Some(event.message.as_str()),
+            event.throwable.as_deref(),
+            event.logger.as_deref(),
+            event.thread.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|text| text.to_lowercase().contains(&search))
+    }
+}
+
+/// An event before it is numbered and stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedEvent {
+    pub timestamp: Option<u64>,
+    pub level: LogLevel,
+    pub thread: Option<String>,
+    pub logger: Option<String>,
+    pub message: String,
+    pub throwable: Option<String>,
+}
+
+static XML_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(\w+)="([^"]*)""#).expect("Invalid log pattern"));
+
+static PATTERN_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[[\d:. -]+\] \[([^\]]*)/([A-Z]+)\](?: \[([^\]]*)\])?: ?(.*)$")
+        .expect("Invalid log pattern")
+});
+
+/// Turns the lines of a game output into events.
+///
+/// XML events span several lines, so lines are buffered until the event is
+/// complete.
+#[derive(Debug, Default)]
+pub struct Log4jParser {
+    buffer: Option<String>,
+}
+
+impl Log4jParser {
+    /// Consumes one line of output, returning an event when one is complete.
+    pub fn push_line(&mut self, line: &str) -> Option<ParsedEvent> {
+        if let Some(buffer) = &mut self.buffer {
+            buffer.push('\n');
+            buffer.push_str(line);
+        } else if line.trim_start().starts_with("<log4j:Event") {
+            self.buffer = Some(line.trim_start().to_string());
+        } else {
+            return Some(parse_plain_line(line));
+        }
+        if line.contains("</log4j:Event>") {
+            let event = self.buffer.take()?;
+            return Some(parse_xml_event(&event));
+        }
+        None
+    }
+
+    /// Returns the incomplete event left when the output ends.
+    pub fn finish(&mut self) -> Option<ParsedEvent> {
+        self.buffer
+            .take()
+            .map(|event| parse_plain_line(event.trim()))
+    }
+}
+
+fn parse_xml_event(event: &str) -> ParsedEvent {
+    let header_end = event.find('>').unwrap_or(event.len());
+    let attributes: HashMap<&str, String> = XML_ATTRIBUTE
+        .captures_iter(&event[..header_end])
+        .filter_map(|captures| {
+            Some((
+                captures.get(1)?.as_str(),
+                unescape_xml(captures.get(2)?.as_str()),
+            ))
+        })
+        .collect();
+    ParsedEvent {
+        timestamp: attributes
+            .get("timestamp")
+            .and_then(|timestamp| timestamp.parse().ok()),
+        level: attributes
+            .get("level")
+            .and_then(|level| LogLevel::parse(level))
+            .unwrap_or(LogLevel::Info),
+        thread: attributes.get("thread").cloned(),
+        logger: attributes
+            .get("logger")
+            .cloned()
+            .filter(|logger| !logger.is_empty()),
+        message: xml_element(event, "log4j:Message").unwrap_or_default(),
+        throwable: xml_element(event, "log4j:Throwable")
+            .filter(|throwable| !throwable.trim().is_empty()),
+    }
+}
+
+/// Returns the text of the element `name`, which may be wrapped in CDATA.
+fn xml_element(event: &str, name: &str) -> Option<String> {
+    let open = format!("<{name}>");
+    let start = event.find(&open)? + open.len();
+    let end = start + event[start..].find(&format!("</{name}>"))?;
+    let content = event[start..end].trim();
+    Some(
+        match content
+            .strip_prefix("<![CDATA[")
+            .and_then(|content| content.strip_suffix("]]>"))
+        {
+            Some(content) => content.trim_end().to_string(),
+            None => unescape_xml(content),
+        },
+    )
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parses a line of the `[%d{HH:mm:ss}] [%t/%level]: %msg` pattern, or keeps
+/// the line as the message of an info event.
+fn parse_plain_line(line: &str) -> ParsedEvent {
+    if let Some(captures) = PATTERN_LINE.captures(line)
+        && let Some(level) = LogLevel::parse(&captures[2])
+    {
+        return ParsedEvent {
+            timestamp: None,
+            level,
+            thread: Some(captures[1].to_string()),
+            logger: captures.get(3).map(|logger| logger.as_str().to_string()),
+            message: captures[4].to_string(),
+            throwable: None,
+        };
+    }
+    ParsedEvent {
+        timestamp: None,
+        level: LogLevel::Info,
+        thread: None,
+        logger: None,
+        message: line.to_string(),
+        throwable: None,
+    }
+}
+
+/// Calls `on_line` for every line of `output` until it is closed.
+///
+/// Invalid UTF-8, which the game prints on systems with a legacy code page, is
+/// replaced instead of ending the output.
+pub(crate) fn for_each_line(output: impl Read, mut on_line: impl FnMut(&str)) {
+    let mut output = BufReader::new(output);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        match output.read_until(b'\n', &mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => on_line(String::from_utf8_lossy(&buffer).trim_end()),
+        }
+    }
+}
+
+fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Incorrect system time")
+        .as_millis() as u64
+}
+
+type LogSink = Box<dyn Fn(&Log) + Send + Sync>;
+
+struct Subscriber {
+    pid: u32,
+    filter: LogFilter,
+    sink: LogSink,
+}
+
+struct GameLogs {
+    instance_id: String,
+    events: VecDeque<LogEvent>,
+    next_sequence: u64,
+}
+
+/// The log rings of the games and the subscribers to them.
+#[derive(Clone, Default)]
+pub struct LogHub {
+    games: Arc<Mutex<HashMap<u32, GameLogs>>>,
+    subscribers: Arc<Mutex<HashMap<u64, Subscriber>>>,
+    next_subscription: Arc<AtomicU64>,
+}
+
+impl LogHub {
+    /// Creates the ring of a new game, dropping the logs kept from earlier runs
+    /// of the same instance.
+    pub fn start(&self, pid: u32, instance_id: &str) {
+        let mut games = self.games.lock().expect("Internal error");
+        games.retain(|_, game| game.instance_id != instance_id);
+        games.insert(
+            pid,
+            GameLogs {
+                instance_id: instance_id.to_string(),
+                events: VecDeque::new(),
+                next_sequence: 0,
+            },
+        );
+    }
+
+    /// Stores an event of the game `pid` and sends it to its subscribers.
+    pub fn push(&self, pid: u32, event: ParsedEvent, stream: LogStream) {
+        // Hold the ring while dispatching, so subscribing cannot send an event twice.
+        let mut games = self.games.lock().expect("Internal error");
+        let Some(game) = games.get_mut(&pid) else {
+            return;
+        };
+        let event = LogEvent {
+            sequence: game.next_sequence,
+            timestamp: event.timestamp.unwrap_or_else(unix_now_millis),
+            level: event.level,
+            thread: event.thread,
+            logger: event.logger,
+            message: event.message,
+            throwable: event.throwable,
+            stream,
+        };
+        game.next_sequence += 1;
+        if game.events.len() == LOG_RING_CAPACITY {
+            game.events.pop_front();
+        }
+        game.events.push_back(event.clone());
+        let log = Log {
+            instance_id: game.instance_id.clone(),
+            pid,
+            event,
+        };
+        for subscriber in self.subscribers.lock().expect("Internal error").values() {
+            if subscriber.pid == pid && subscriber.filter.matches(&log.event) {
+                (subscriber.sink)(&log);
+            }
+        }
+    }
+
+    /// Returns the kept events of the game `pid` that match `filter`.
+    pub fn history(&self, pid: u32, filter: &LogFilter) -> Vec<LogEvent> {
+        self.games
+            .lock()
+            .expect("Internal error")
+            .get(&pid)
+            .map(|game| {
+                game.events
+                    .iter()
+                    .filter(|event| filter.matches(event))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sends the kept and all future events of the game `pid` matching
+    /// `filter` to `sink`. Returns the id of the subscription.
+    pub fn subscribe(
+        &self,
+        pid: u32,
+        filter: LogFilter,
+        sink: impl Fn(&Log) + Send + Sync + 'static,
+    ) -> Option<u64> {
+        // Hold the ring while subscribing, so no event is missed.
+        let games = self.games.lock().expect("Internal error");
+        let game = games.get(&pid)?;
+        for event in game.events.iter().filter(|event| filter.matches(event)) {
+            sink(&Log {
+                instance_id: game.instance_id.clone(),
+                pid,
+                event: event.clone(),
+            });
+        }
+        let id = self.next_subscription.fetch_add(1, Ordering::SeqCst);
+        self.subscribers.lock().expect("Internal error").insert(
+            id,
+            Subscriber {
+                pid,
+                filter,
+                sink: Box::new(sink),
+            },
+        );
+        Some(id)
+    }
+
+    pub fn unsubscribe(&self, id: u64) {
+        self.subscribers.lock().expect("Internal error").remove(&id);
+    }
+
+    /// Drops the subscribers of a game that has exited. Its logs are kept until
+    /// the instance is launched again.
+    pub fn finish(&self, pid: u32) {
+        self.subscribers
+            .lock()
+            .expect("Internal error")
+            .retain(|_, subscriber| subscriber.pid != pid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_xml_events() {
+        let mut parser = Log4jParser::default();
+        let lines = [
+            r#"<log4j:Event logger="net.minecraft.client.Minecraft" timestamp="1700000000000" level="ERROR" thread="Render thread">"#,
+            "  <log4j:Message><![CDATA[Failed to load <texture> & model]]></log4j:Message>",
+            "  <log4j:Throwable><![CDATA[java.io.IOException: broken",
+            "\tat net.minecraft.Foo.bar(Foo.java:1)]]></log4j:Throwable>",
+            "</log4j:Event>",
+        ];
+        let events: Vec<_> = lines
+            .iter()
+            .filter_map(|line| parser.push_line(line))
+            .collect();
+        assert_eq!(
+            events,
+            vec![ParsedEvent {
+                timestamp: Some(1700000000000),
+                level: LogLevel::Error,
+                thread: Some("Render thread".to_string()),
+                logger: Some("net.minecraft.client.Minecraft".to_string()),
+                message: "Failed to load <texture> & model".to_string(),
+                throwable: Some(
+                    "java.io.IOException: broken\n\tat net.minecraft.Foo.bar(Foo.java:1)"
+                        .to_string()
+                ),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_plain_lines() {
+        let mut parser = Log4jParser::default();
+        let event = parser
+            .push_line("[12:00:01] [main/WARN]: Something happened")
+            .unwrap();
+        assert_eq!(event.level, LogLevel::Warn);
+        assert_eq!(event.thread.as_deref(), Some("main"));
+        assert_eq!(event.message, "Something happened");
+        let event = parser.push_line("Exception in thread main").unwrap();
+        assert_eq!(event.level, LogLevel::Info);
+        assert_eq!(event.message, "Exception in thread main");
+    }
+
+    #[test]
+    fn keeps_a_bounded_ring_and_filters_subscribers() {
+        let hub = LogHub::default();
+        hub.start(1, "instance");
+        for index in 0..LOG_RING_CAPACITY + 10 {
+            hub.push(
+                1,
+                parse_plain_line(&format!("[00:00:00] [main/INFO]: line {index}")),
+                LogStream::Stdout,
+            );
+        }
+        let history = hub.history(1, &LogFilter::default());
+        assert_eq!(history.len(), LOG_RING_CAPACITY);
+        assert_eq!(history[0].sequence, 10);
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let filter = LogFilter {
+            min_level: Some(LogLevel::Warn),
+            search: Some("DISK".to_string()),
+        };
+        let id = hub
+            .subscribe(1, filter, {
+                let received = received.clone();
+                move |log| received.lock().unwrap().push(log.event.message.clone())
+            })
+            .unwrap();
+        hub.push(
+            1,
+            parse_plain_line("[00:00:00] [main/ERROR]: disk full"),
+            LogStream::Stdout,
+        );
+        hub.push(
+            1,
+            parse_plain_line("[00:00:00] [main/INFO]: disk ok"),
+            LogStream::Stdout,
+        );
+        hub.unsubscribe(id);
+        hub.push(
+            1,
+            parse_plain_line("[00:00:00] [main/ERROR]: disk full"),
+            LogStream::Stdout,
+        );
+        assert_eq!(*received.lock().unwrap(), vec!["disk full".to_string()]);
+
+        hub.start(2, "instance");
+        assert!(hub.history(1, &LogFilter::default()).is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Resource monitoring of the running games.
+//!
+//! Every game gets a thread sampling its process tree, which includes the
+//! processes of a wrapper command. The heap usage is read with `jstat` when
+//! the game runs on a JDK, as the memory of the process also counts the
+//! metaspace, code cache and native allocations of the JVM.
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::info;
+use platform::ProcessSampler;
+use serde::Serialize;
+
+use crate::process::GameRegistry;
+
+/// How often the game processes are sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The heap is only read every that many samples, as `jstat` starts a JVM.
+const HEAP_SAMPLE_EVERY: u64 = 5;
+
+/// The share of `-Xmx` from which the heap usage is reported.
+const HEAP_WARNING_RATIO: f64 = 0.9;
+
+/// The share of `-Xmx` the heap usage must fall under before it is reported
+/// again.
+const HEAP_REARM_RATIO: f64 = 0.8;
+
+/// The resource usage of a game at one point in time.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ResourceSample {
+    pub pid: u32,
+    /// Unix timestamp in milliseconds.
+    pub timestamp: u64,
+    /// Resident memory of the game and its child processes, in bytes.
+    pub rss_bytes: u64,
+    /// CPU usage of the game and its child processes, where 100 is one fully
+    /// used core.
+    pub cpu_percent: f32,
+    /// `None` where the platform does not report threads.
+    pub threads: Option<usize>,
+    pub children: usize,
+    /// The last heap usage read, `None` when the heap cannot be read.
+    pub heap_used_bytes: Option<u64>,
+    /// The `-Xmx` of the game, `None` when the JVM picks it.
+    pub max_heap_bytes: Option<u64>,
+}
+
+/// Peak and average usage of a game session.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ResourceSummary {
+    pub samples: u64,
+    pub peak_rss_bytes: u64,
+    pub average_rss_bytes: u64,
+    pub peak_cpu_percent: f32,
+    pub average_cpu_percent: f32,
+    pub peak_threads: Option<usize>,
+    pub peak_children: usize,
+    pub peak_heap_used_bytes: Option<u64>,
+    pub max_heap_bytes: Option<u64>,
+}
+
+/// Accumulates the samples of a game session.
+#[derive(Debug, Default)]
+pub(crate) struct ResourceStats {
+    samples: u64,
+    rss_total: u64,
+    cpu_total: f64,
+    peak_rss_bytes: u64,
+    peak_cpu_percent: f32,
+    peak_threads: Option<usize>,
+    peak_children: usize,
+    peak_heap_used_bytes: Option<u64>,
+    max_heap_bytes: Option<u64>,
+    heap_warned: bool,
+}
+
+impl ResourceStats {
+    /// Adds `sample` to the session.
+    ///
+    /// Returns `true` when the heap usage has just come close to `-Xmx`.
+    pub(crate) fn push(&mut self, sample: &ResourceSample) -> bool {
+        self.samples += 1;
+        self.rss_total += sample.rss_bytes;
+        self.cpu_total += f64::from(sample.cpu_percent);
+        self.peak_rss_bytes = self.peak_rss_bytes.max(sample.rss_bytes);
+        self.peak_cpu_percent = self.peak_cpu_percent.max(sample.cpu_percent);
+        self.peak_threads = self.peak_threads.max(sample.threads);
+        self.peak_children = self.peak_children.max(sample.children);
+        self.peak_heap_used_bytes = self.peak_heap_used_bytes.max(sample.heap_used_bytes);
+        self.max_heap_bytes = sample.max_heap_bytes;
+
+        let (Some(used), Some(max)) = (sample.heap_used_bytes, sample.max_heap_bytes) else {
+            return false;
+        };
+        let ratio = used as f64 / max as f64;
+        if ratio < HEAP_REARM_RATIO {
+            self.heap_warned = false;
+        } else if ratio >= HEAP_WARNING_RATIO && !self.heap_warned {
+            self.heap_warned = true;
+            return true;
+        }
+        false
+    }
+
+    /// Returns `None` before the first sample.
+    pub(crate) fn summary(&self) -> Option<ResourceSummary> {
+        (self.samples > 0).then(|| ResourceSummary {
+            samples: self.samples,
+            peak_rss_bytes: self.peak_rss_bytes,
+            average_rss_bytes: self.rss_total / self.samples,
+            peak_cpu_percent: self.peak_cpu_percent,
+            average_cpu_percent: (self.cpu_total / self.samples as f64) as f32,
+            peak_threads: self.peak_threads,
+            peak_children: self.peak_children,
+            peak_heap_used_bytes: self.peak_heap_used_bytes,
+            max_heap_bytes: self.max_heap_bytes,
+        })
+    }
+}
+
+/// Samples the game `pid` until it exits or leaves `games`.
+///
+/// `max_memory` is the `-Xmx` of the game in MiB, `0` when unset.
+pub(crate) fn spawn_monitor(games: GameRegistry, pid: u32, java_path: &Path, max_memory: usize) {
+    let jstat = find_jdk_tool(java_path, "jstat");
+    if jstat.is_none() {
+        info!("jstat not found next to the Java executable, heap usage will not be monitored");
+    }
+    let max_heap_bytes = (max_memory > 0).then(|| max_memory as u64 * 1024 * 1024);
+    thread::spawn(move || {
+        let mut sampler = ProcessSampler::new(pid);
+        let mut heap_used_bytes = None;
+        for round in 0.. {
+            let Some(usage) = sampler.sample() else {
+                break;
+            };
+            if games.get(pid).is_none() {
+                break;
+            }
+            if let Some(jstat) = &jstat
+                && round % HEAP_SAMPLE_EVERY == 0
+            {
+                heap_used_bytes = read_heap_usage(jstat, pid);
+            }
+            games.record_usage(ResourceSample {
+                pid,
+                timestamp: unix_now_millis(),
+                rss_bytes: usage.rss_bytes,
+                cpu_percent: usage.cpu_percent,
+                threads: usage.threads,
+                children: usage.children,
+                heap_used_bytes,
+                max_heap_bytes,
+            });
+            thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+}
+
+/// Returns the tool `name` of the JDK `java_path` belongs to, e.g. `jstat`.
+pub(crate) fn find_jdk_tool(java_path: &Path, name: &str) -> Option<PathBuf> {
+    let name = if cfg!(target_os = "windows") {
+        format!("{name}.exe")
+    } else {
+        name.to_string()
+    };
+    Some(java_path.parent()?.join(name)).filter(|tool| tool.is_file())
+}
+
+fn read_heap_usage(jstat: &Path, pid: u32) -> Option<u64> {
+    let mut command = Command::new(jstat);
+    command
+        .args(["-gc", &pid.to_string()])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_jstat_gc(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Sums the used survivor, eden and old spaces in the output of `jstat -gc`,
+/// which are in KiB.
+///
+/// Returns `None` when none of them is reported, e.g. when the game disables
+/// the shared performance memory.
+fn parse_jstat_gc(output: &str) -> Option<u64> {
+    let mut lines = output.lines();
+    let header: Vec<&str> = lines.next()?.split_whitespace().collect();
+    let values: Vec<&str> = lines.next()?.split_whitespace().collect();
+    let used_kib: Vec<f64> = header
+        .iter()
+        .zip(&values)
+        .filter(|(column, _)| matches!(**column, "S0U" | "S1U" | "EU" | "OU"))
+        .filter_map(|(_, value)| value.parse().ok())
+        .collect();
+    if used_kib.is_empty() {
+        return None;
+    }
+    Some((used_kib.iter().sum::<f64>() * 1024.0) as u64)
+}
+
+fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Incorrect system time")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(rss_mib: u64, cpu_percent: f32, heap_mib: Option<u64>) -> ResourceSample {
+        ResourceSample {
+            pid: 1,
+            timestamp: 0,
+            rss_bytes: rss_mib << 20,
+            cpu_percent,
+            threads: Some(40),
+            children: 0,
+            heap_used_bytes: heap_mib.map(|heap| heap << 20),
+            max_heap_bytes: Some(1000 << 20),
+        }
+    }
+
+    #[test]
+    fn summarizes_session() {
+        let mut stats = ResourceStats::default();
+        assert!(stats.summary().is_none());
+        assert!(!stats.push(&sample(1000, 50.0, Some(500))));
+        assert!(stats.push(&sample(3000, 150.0, Some(950))));
+        assert!(!stats.push(&sample(2000, 100.0, Some(920))));
+        assert!(!stats.push(&sample(2000, 100.0, Some(700))));
+        assert!(stats.push(&sample(2000, 100.0, Some(990))));
+        let summary = stats.summary().unwrap();
+        assert_eq!(summary.samples, 5);
+        assert_eq!(summary.peak_rss_bytes, 3000 << 20);
+        assert_eq!(summary.average_rss_bytes, 2000 << 20);
+        assert_eq!(summary.peak_cpu_percent, 150.0);
+        assert_eq!(summary.average_cpu_percent, 100.0);
+        assert_eq!(summary.peak_heap_used_bytes, Some(990 << 20));
+    }
+
+    #[test]
+    fn parses_jstat_output() {
+        let output = " S0C    S1C    S0U    S1U      EC       EU        OC         OU       MC     MU    CCSC   CCSU   YGC     YGCT    FGC    FGCT     CGC    CGCT     GCT   \n\
+                      0.0   4096.0  0.0   4096.0 126976.0  20480.0  131072.0   65536.5  98304.0 96000.0 12288.0 11000.0     10    0.050     0    0.000     4    0.010    0.060\n";
+        assert_eq!(
+            parse_jstat_gc(output),
+            Some(((4096.0 + 20480.0 + 65536.5) * 1024.0) as u64)
+        );
+        assert_eq!(parse_jstat_gc("12345 not found\n"), None);
+    }
+}
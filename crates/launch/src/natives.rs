@@ -0,0 +1,445 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! LWJGL natives on Linux.
+//!
+//! Mojang only ships x86 natives for Linux, and its GLFW is built for X11.
+//! Depending on [`NativesMode`], the natives of the version are either
+//! replaced by the LWJGL builds of Maven Central for the architecture of the
+//! Java runtime, or GLFW and OpenAL are loaded from the system, with the
+//! other natives still replaced. What can be done for an LWJGL version is
+//! listed in [`COMPATIBILITY`].
+
+use std::{collections::HashSet, env, path::PathBuf};
+
+use config::{download::DownloadConfig, launch::NativesMode};
+use download::progress::DownloadState;
+use folder::MinecraftLocation;
+use install::vanilla::generate_libraries_downloads;
+use java_runtime::JavaArch;
+use log::{info, warn};
+use shared::HTTP_CLIENT;
+use version::{LibraryDownloadInfo, ResolvedLibrary, ResolvedVersion};
+
+use crate::{complete::download_or_verify, error::*};
+
+const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2/";
+
+/// What can be done for the LWJGL versions starting with `lwjgl`.
+struct NativesCompatibility {
+    lwjgl: &'static str,
+    /// Whether the GLFW and OpenAL of the system can be loaded.
+    system_libraries: bool,
+    replacements: &'static [NativesReplacement],
+}
+
+/// LWJGL natives published for an architecture.
+struct NativesReplacement {
+    arch: JavaArch,
+    /// The LWJGL version to switch to, when the version itself has no natives
+    /// for the architecture.
+    version: Option<&'static str>,
+    classifier: &'static str,
+}
+
+/// The first matching row applies.
+const COMPATIBILITY: &[NativesCompatibility] = &[
+    NativesCompatibility {
+        lwjgl: "3.3.",
+        system_libraries: true,
+        replacements: &[
+            NativesReplacement {
+                arch: JavaArch::Aarch64,
+                version: None,
+                classifier: "natives-linux-arm64",
+            },
+            NativesReplacement {
+                arch: JavaArch::Arm,
+                version: None,
+                classifier: "natives-linux-arm32",
+            },
+        ],
+    },
+    NativesCompatibility {
+        lwjgl: "3.2.",
+        system_libraries: true,
+        replacements: &[
+            NativesReplacement {
+                arch: JavaArch::Aarch64,
+                version: Some("3.3.1"),
+                classifier: "natives-linux-arm64",
+            },
+            NativesReplacement {
+                arch: JavaArch::Arm,
+                version: Some("3.3.1"),
+                classifier: "natives-linux-arm32",
+            },
+        ],
+    },
+    NativesCompatibility {
+        lwjgl: "3.",
+        system_libraries: true,
+        replacements: &[],
+    },
+    // LWJGL 2 has no GLFW, and no natives for ARM in Maven Central.
+    NativesCompatibility {
+        lwjgl: "2.",
+        system_libraries: false,
+        replacements: &[],
+    },
+];
+
+/// Directories searched for the system libraries, after `LD_LIBRARY_PATH`.
+const SYSTEM_LIBRARY_DIRS: &[&str] = &[
+    "/usr/local/lib",
+    "/usr/lib64",
+    "/usr/lib",
+    "/usr/lib/x86_64-linux-gnu",
+    "/usr/lib/aarch64-linux-gnu",
+    "/usr/lib/arm-linux-gnueabihf",
+    "/lib64",
+    "/lib",
+];
+
+/// The changes made to a launch by [`setup_natives`].
+#[derive(Default)]
+pub(crate) struct NativesSetup {
+    /// Properties pointing LWJGL to the system libraries.
+    pub jvm_arguments: Vec<String>,
+    /// The libraries replacing the natives of the version, which may need to
+    /// be downloaded.
+    pub replaced_libraries: Vec<ResolvedLibrary>,
+}
+
+/// Applies `mode` to the libraries of `version`, for a Java runtime of
+/// `java_arch`. Nothing changes outside of Linux.
+///
+/// Returns [`Error::SystemLibraryMissing`] when the system libraries are
+/// selected but not installed.
+pub(crate) fn setup_natives(
+    version: &mut ResolvedVersion,
+    mode: &NativesMode,
+    java_arch: JavaArch,
+) -> Result<NativesSetup> {
+    if !cfg!(target_os = "linux") || *mode == NativesMode::Bundled {
+        return Ok(NativesSetup::default());
+    }
+    let Some(lwjgl_version) = lwjgl_version(version) else {
+        return Ok(NativesSetup::default());
+    };
+    let Some(compatibility) = COMPATIBILITY
+        .iter()
+        .find(|row| lwjgl_version.starts_with(row.lwjgl))
+    else {
+        return Ok(NativesSetup::default());
+    };
+    match mode {
+        NativesMode::Bundled => Ok(NativesSetup::default()),
+        NativesMode::Auto => {
+            if matches!(java_arch, JavaArch::X64 | JavaArch::X86 | JavaArch::Unknown) {
+                return Ok(NativesSetup::default());
+            }
+            let Some(replacement) = compatibility
+                .replacements
+                .iter()
+                .find(|replacement| replacement.arch == java_arch)
+            else {
+                warn!(
+                    "No LWJGL {lwjgl_version} natives for {}, the game may not start",
+                    java_arch.display_name()
+                );
+                return Ok(NativesSetup::default());
+            };
+            info!(
+                "Replacing the LWJGL {lwjgl_version} natives with {} {}",
+                replacement.version.unwrap_or(lwjgl_version.as_str()),
+                replacement.classifier
+            );
+            Ok(NativesSetup {
+                jvm_arguments: Vec::new(),
+                replaced_libraries: replace_natives(version, replacement),
+            })
+        }
+        NativesMode::System => {
+            if !compatibility.system_libraries {
+                warn!("LWJGL {lwjgl_version} cannot load the system libraries, using its natives");
+                return Ok(NativesSetup::default());
+            }
+            // Only GLFW and OpenAL come from the system, the other LWJGL
+            // modules still need natives for the architecture.
+            let replaced_libraries =
+                if matches!(java_arch, JavaArch::X64 | JavaArch::X86 | JavaArch::Unknown) {
+                    Vec::new()
+                } else {
+                    let replacement = compatibility
+                        .replacements
+                        .iter()
+                        .find(|replacement| replacement.arch == java_arch)
+                        .ok_or_else(|| {
+                            Error::NativesUnavailable(format!(
+                                "LWJGL {lwjgl_version} on {}",
+                                java_arch.display_name()
+                            ))
+                        })?;
+                    replace_natives(version, replacement)
+                };
+            let glfw = find_system_library(&["libglfw.so.3", "libglfw.so"])
+                .ok_or(Error::SystemLibraryMissing("GLFW".to_string()))?;
+            let openal = find_system_library(&["libopenal.so.1", "libopenal.so"])
+                .ok_or(Error::SystemLibraryMissing("OpenAL".to_string()))?;
+            info!(
+                "Using the system GLFW {} and OpenAL {}",
+                glfw.display(),
+                openal.display()
+            );
+            Ok(NativesSetup {
+                jvm_arguments: vec![
+                    format!("-Dorg.lwjgl.glfw.libname={}", glfw.display()),
+                    format!("-Dorg.lwjgl.openal.libname={}", openal.display()),
+                ],
+                replaced_libraries,
+            })
+        }
+    }
+}
+
+/// Downloads `libraries`, the replacements of the natives of a version, and
+/// checks them against the `.sha1` files published next to them. The
+/// checksums are kept next to the jars, so they can be verified offline.
+pub(crate) async fn download_replacements(
+    minecraft_location: &MinecraftLocation,
+    libraries: &[ResolvedLibrary],
+    config: &DownloadConfig,
+    offline: bool,
+) -> Result<()> {
+    if libraries.is_empty() {
+        return Ok(());
+    }
+    let mut verified = Vec::with_capacity(libraries.len());
+    for library in libraries {
+        let mut library = library.clone();
+        let (ResolvedLibrary::Common(info) | ResolvedLibrary::Native(info)) = &mut library;
+        info.sha1 = Some(maven_sha1(minecraft_location, info, offline).await?);
+        verified.push(library);
+    }
+    let tasks = generate_libraries_downloads(minecraft_location, &verified);
+    download_or_verify(tasks, &DownloadState::default(), config.clone(), offline).await
+}
+
+/// Returns the SHA-1 of the Maven artifact `info`, read from its `.sha1` file.
+async fn maven_sha1(
+    minecraft_location: &MinecraftLocation,
+    info: &LibraryDownloadInfo,
+    offline: bool,
+) -> Result<String> {
+    let checksum_file = minecraft_location.get_library_by_path(format!("{}.sha1", info.path));
+    if let Ok(sha1) = async_fs::read_to_string(&checksum_file).await
+        && is_sha1(sha1.trim())
+    {
+        return Ok(sha1.trim().to_string());
+    }
+    if offline {
+        return Err(Error::MissingFiles(vec![
+            checksum_file.to_string_lossy().to_string(),
+        ]));
+    }
+    let response = HTTP_CLIENT
+        .get(format!("{}.sha1", info.url))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    // Some `.sha1` files are followed by the file name.
+    let sha1 = response
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if !is_sha1(&sha1) {
+        return Err(Error::ChecksumMissmatch(format!(
+            "Invalid checksum published for {}",
+            info.path
+        )));
+    }
+    if let Some(parent) = checksum_file.parent() {
+        async_fs::create_dir_all(parent).await?;
+    }
+    async_fs::write(&checksum_file, &sha1).await?;
+    Ok(sha1)
+}
+
+fn is_sha1(value: &str) -> bool {
+    value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Reads the LWJGL version from the path of the `lwjgl` library, which is
+/// `org/lwjgl/lwjgl/<version>` for LWJGL 3 and
+/// `org/lwjgl/lwjgl/lwjgl/<version>` for LWJGL 2.
+pub(crate) fn lwjgl_version(version: &ResolvedVersion) -> Option<String> {
+    version.libraries.iter().find_map(|library| {
+        let path = &library.download_info().path;
+        let mut segments = path.strip_prefix("org/lwjgl/lwjgl/")?.split('/');
+        let version = match segments.next()? {
+            "lwjgl" => segments.next()?,
+            version => version,
+        };
+        version
+            .starts_with(|c: char| c.is_ascii_digit())
+            .then(|| version.to_string())
+    })
+}
+
+/// Replaces the LWJGL natives of `version` with the natives of
+/// `replacement`, and returns the new libraries.
+fn replace_natives(
+    version: &mut ResolvedVersion,
+    replacement: &NativesReplacement,
+) -> Vec<ResolvedLibrary> {
+    let mut seen = HashSet::new();
+    let mut replaced = Vec::new();
+    let libraries = std::mem::take(&mut version.libraries);
+    for library in libraries {
+        let Some((artifact, lwjgl_version, classifier)) =
+            parse_lwjgl_path(&library.download_info().path)
+        else {
+            version.libraries.push(library);
+            continue;
+        };
+        let target_version = replacement.version.unwrap_or(lwjgl_version);
+        let classifier = match classifier {
+            Some(classifier) if classifier.starts_with("natives-") => Some(replacement.classifier),
+            classifier => classifier,
+        };
+        if classifier == Some(replacement.classifier) || target_version != lwjgl_version {
+            let library = lwjgl_library(artifact, target_version, classifier);
+            if seen.insert(library.download_info().path.clone()) {
+                replaced.push(library.clone());
+                version.libraries.push(library);
+            }
+        } else {
+            version.libraries.push(library);
+        }
+    }
+    replaced
+}
+
+/// Splits `org/lwjgl/<artifact>/<version>/<artifact>-<version>[-<classifier>].jar`.
+fn parse_lwjgl_path(path: &str) -> Option<(&str, &str, Option<&str>)> {
+    let mut parts = path.strip_prefix("org/lwjgl/")?.split('/');
+    let (artifact, version, file) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let rest = file
+        .strip_prefix(&format!("{artifact}-{version}"))?
+        .strip_suffix(".jar")?;
+    let classifier = match rest {
+        "" => None,
+        rest => Some(rest.strip_prefix('-')?),
+    };
+    Some((artifact, version, classifier))
+}
+
+fn lwjgl_library(artifact: &str, version: &str, classifier: Option<&str>) -> ResolvedLibrary {
+    let (file, name) = match classifier {
+        Some(classifier) => (
+            format!("{artifact}-{version}-{classifier}.jar"),
+            format!("org.lwjgl:{artifact}:{version}:{classifier}"),
+        ),
+        None => (
+            format!("{artifact}-{version}.jar"),
+            format!("org.lwjgl:{artifact}:{version}"),
+        ),
+    };
+    let path = format!("org/lwjgl/{artifact}/{version}/{file}");
+    // LWJGL 3 loads its natives from the classpath.
+    ResolvedLibrary::Common(LibraryDownloadInfo {
+        sha1: None,
+        size: None,
+        url: format!("{MAVEN_CENTRAL}{path}"),
+        path,
+        name: Some(name),
+    })
+}
+
+fn find_system_library(names: &[&str]) -> Option<PathBuf> {
+    let library_path = env::var_os("LD_LIBRARY_PATH").unwrap_or_default();
+    env::split_paths(&library_path)
+        .chain(SYSTEM_LIBRARY_DIRS.iter().map(PathBuf::from))
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(path: &str, native: bool) -> ResolvedLibrary {
+        let info = LibraryDownloadInfo {
+            sha1: Some("0".repeat(40)),
+            size: Some(1),
+            url: format!("https://libraries.minecraft.net/{path}"),
+            path: path.to_string(),
+            name: None,
+        };
+        if native {
+            ResolvedLibrary::Native(info)
+        } else {
+            ResolvedLibrary::Common(info)
+        }
+    }
+
+    fn paths(libraries: &[ResolvedLibrary]) -> Vec<&str> {
+        libraries
+            .iter()
+            .map(|library| library.download_info().path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_lwjgl_paths() {
+        assert_eq!(
+            parse_lwjgl_path("org/lwjgl/lwjgl-glfw/3.3.1/lwjgl-glfw-3.3.1-natives-linux.jar"),
+            Some(("lwjgl-glfw", "3.3.1", Some("natives-linux")))
+        );
+        assert_eq!(
+            parse_lwjgl_path("org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1.jar"),
+            Some(("lwjgl", "3.3.1", None))
+        );
+        assert_eq!(
+            parse_lwjgl_path("org/lwjgl/lwjgl/lwjgl/2.9.4/lwjgl-2.9.4.jar"),
+            None
+        );
+    }
+
+    #[test]
+    fn replaces_natives_for_arm64() {
+        let replacement = &COMPATIBILITY[1].replacements[0];
+        let mut libraries = vec![
+            library("com/mojang/brigadier/1.0.18/brigadier-1.0.18.jar", false),
+            library("org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2.jar", false),
+            library("org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2-natives-linux.jar", true),
+            library("org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2-natives-linux.jar", true),
+        ];
+        let mut version = ResolvedVersion::default();
+        version.libraries.append(&mut libraries);
+        assert_eq!(lwjgl_version(&version).as_deref(), Some("3.2.2"));
+        let replaced = replace_natives(&mut version, replacement);
+        assert_eq!(
+            paths(&version.libraries),
+            vec![
+                "com/mojang/brigadier/1.0.18/brigadier-1.0.18.jar",
+                "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1.jar",
+                "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux-arm64.jar",
+            ]
+        );
+        assert_eq!(replaced.len(), 2);
+        assert!(matches!(replaced[1], ResolvedLibrary::Common(_)));
+        assert_eq!(
+            replaced[1].download_info().url,
+            "https://repo1.maven.org/maven2/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux-arm64.jar"
+        );
+    }
+}
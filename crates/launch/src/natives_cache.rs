@@ -0,0 +1,392 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Cache of the extracted native libraries.
+//!
+//! Natives are extracted once per version, platform and LWJGL version, into
+//! `versions/<id>/conic-natives/<platform>-lwjgl-<version>`. The instances of
+//! a version share them, and they are removed together with the version.
+//!
+//! A manifest lists the native jars and the extracted files with their size
+//! and modification time, and the natives are extracted again when they no
+//! longer match it. The extraction writes to a staging directory moved into
+//! place, so a game never loads natives another launch is still writing. When
+//! the natives to replace are still loaded by a running game, which prevents
+//! moving them on Windows, the new natives go to a fresh directory instead.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use folder::MinecraftLocation;
+use java_runtime::JavaArch;
+use log::{debug, info, warn};
+use platform::{OsFamily, PLATFORM_INFO};
+use serde::{Deserialize, Serialize};
+use version::{LibraryDownloadInfo, ResolvedLibrary, ResolvedVersion};
+use zip::ZipArchive;
+
+use crate::{error::*, natives::lwjgl_version};
+
+const MANIFEST_FILE: &str = ".conic-natives.json";
+
+/// Staging directories older than this are left over by a crashed launch.
+const STAGING_EXPIRY: Duration = Duration::from_secs(60 * 60);
+
+/// Serializes the extractions of this process. Other processes are kept apart
+/// by the staging directories.
+static EXTRACTION: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct NativesManifest {
+    /// The native jars, by path in the libraries folder.
+    sources: Vec<NativeSource>,
+    files: Vec<ExtractedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct NativeSource {
+    path: String,
+    sha1: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct ExtractedFile {
+    /// Relative to the natives directory.
+    path: String,
+    size: u64,
+    /// Modification time in nanoseconds since the Unix epoch.
+    modified: u128,
+}
+
+/// Returns the directory the natives of `version` are extracted to, for a
+/// Java runtime of `java_arch`.
+pub(crate) fn natives_directory(
+    minecraft: &MinecraftLocation,
+    version: &ResolvedVersion,
+    java_arch: JavaArch,
+) -> PathBuf {
+    let os = match PLATFORM_INFO.os_family {
+        OsFamily::Windows => "windows",
+        OsFamily::Linux => "linux",
+        OsFamily::Macos => "osx",
+    };
+    let lwjgl = lwjgl_version(version).unwrap_or_else(|| "none".to_string());
+    minecraft
+        .get_natives_root(&version.id)
+        .join(format!("{os}-{}-lwjgl-{lwjgl}", java_arch.display_name()))
+}
+
+/// Extracts the native libraries of `version` to `directory`, unless the
+/// natives cached there are intact, and returns the directory the natives are
+/// in. It differs from `directory` when the natives there could not be
+/// replaced.
+///
+/// Native jars that are missing or unreadable are skipped, as not every
+/// version ships natives for every platform.
+pub(crate) fn extract_natives(
+    minecraft: &MinecraftLocation,
+    version: &ResolvedVersion,
+    directory: &Path,
+) -> Result<PathBuf> {
+    let libraries: Vec<&LibraryDownloadInfo> = version
+        .libraries
+        .iter()
+        .filter_map(|library| match library {
+            ResolvedLibrary::Native(native) => Some(native),
+            ResolvedLibrary::Common(_) => None,
+        })
+        .collect();
+    let sources: Vec<NativeSource> = libraries
+        .iter()
+        .map(|library| NativeSource {
+            path: library.path.clone(),
+            sha1: library.sha1.clone(),
+        })
+        .collect();
+
+    let _guard = EXTRACTION.lock().unwrap_or_else(PoisonError::into_inner);
+    let parent = directory
+        .parent()
+        .expect("Internal error: natives directory has no parent");
+    let name = directory
+        .file_name()
+        .expect("Internal error: natives directory has no name")
+        .to_string_lossy();
+    let cached = std::iter::once(directory.to_path_buf())
+        .chain(fallback_directories(parent, &name))
+        .find(|candidate| {
+            read_manifest(candidate).is_some_and(|manifest| {
+                manifest.sources == sources && is_intact(candidate, &manifest)
+            })
+        });
+    if let Some(cached) = cached {
+        debug!("Using the natives cached in {}", cached.display());
+        return Ok(cached);
+    }
+
+    fs::create_dir_all(parent)?;
+    remove_leftovers(parent);
+    let staging = parent.join(format!(".{name}.{}.tmp", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&staging)?;
+    let result = extract_to(minecraft, &libraries, &staging).and_then(|files| {
+        let manifest = NativesManifest { sources, files };
+        fs::write(
+            staging.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest).map_err(io::Error::other)?,
+        )?;
+        replace_directory(&staging, directory)
+    });
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+/// Returns the directories the natives named `name` were extracted to when
+/// the natives there could not be replaced.
+fn fallback_directories(parent: &Path, name: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let prefix = format!(".{name}.");
+    entries
+        .flatten()
+        .filter(|entry| {
+            let entry_name = entry.file_name().to_string_lossy().to_string();
+            entry_name.starts_with(&prefix) && entry_name.ends_with(".fallback")
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+fn extract_to(
+    minecraft: &MinecraftLocation,
+    libraries: &[&LibraryDownloadInfo],
+    staging: &Path,
+) -> Result<Vec<ExtractedFile>> {
+    // Later jars overwrite the files of earlier ones.
+    let mut files = BTreeMap::new();
+    for library in libraries {
+        let path = minecraft.get_library_by_path(&library.path);
+        info!("Unzip native library {path:#?} to {staging:#?}");
+        let archive = File::open(&path)
+            .map_err(Error::from)
+            .and_then(|file| Ok(ZipArchive::new(file)?));
+        match archive {
+            Ok(mut archive) => files.extend(
+                extract_archive(&mut archive, staging)?
+                    .into_iter()
+                    .map(|file| (file.path.clone(), file)),
+            ),
+            Err(error) => warn!("Skipping native library {}: {error}", path.display()),
+        }
+    }
+    Ok(files.into_values().collect())
+}
+
+fn extract_archive<R: Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    to: &Path,
+) -> Result<Vec<ExtractedFile>> {
+    let mut files = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(name) = entry.enclosed_name() else {
+            warn!(
+                "Skipping native entry with an unsafe path: {}",
+                entry.name()
+            );
+            continue;
+        };
+        let path = to.join(&name);
+        if entry.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+        let mut content = vec![];
+        entry.read_to_end(&mut content)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, &content)?;
+        files.push(ExtractedFile {
+            path: name.to_string_lossy().replace('\\', "/"),
+            size: content.len() as u64,
+            modified: modified_nanos(&fs::metadata(&path)?),
+        });
+    }
+    Ok(files)
+}
+
+fn read_manifest(directory: &Path) -> Option<NativesManifest> {
+    serde_json::from_str(&fs::read_to_string(directory.join(MANIFEST_FILE)).ok()?).ok()
+}
+
+fn modified_nanos(metadata: &fs::Metadata) -> u128 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |modified| modified.as_nanos())
+}
+
+/// Checks the size and the modification time of every file of `manifest`.
+fn is_intact(directory: &Path, manifest: &NativesManifest) -> bool {
+    manifest.files.iter().all(|file| {
+        fs::metadata(directory.join(&file.path)).is_ok_and(|metadata| {
+            metadata.len() == file.size && modified_nanos(&metadata) == file.modified
+        })
+    })
+}
+
+/// Moves `staging` to `directory`, replacing the natives extracted there, and
+/// returns the directory the natives ended up in.
+///
+/// The replaced natives may still be loaded by a running game, which keeps
+/// them from being moved or deleted on Windows. The new natives are then moved
+/// to a fresh fallback directory, and the old ones are removed by a later
+/// launch.
+fn replace_directory(staging: &Path, directory: &Path) -> Result<PathBuf> {
+    let name = directory.file_name().unwrap_or_default().to_string_lossy();
+    if directory.exists() {
+        let stale = directory.with_file_name(format!(".{name}.{}.stale", uuid::Uuid::new_v4()));
+        if let Err(error) = fs::rename(directory, &stale) {
+            let fallback =
+                directory.with_file_name(format!(".{name}.{}.fallback", uuid::Uuid::new_v4()));
+            warn!(
+                "Could not replace the natives in {}, they may be in use: {error}",
+                directory.display()
+            );
+            fs::rename(staging, &fallback)?;
+            return Ok(fallback);
+        }
+        let _ = fs::remove_dir_all(&stale);
+    }
+    if let Err(error) = fs::rename(staging, directory) {
+        // Another launcher process may have moved its natives in first.
+        if read_manifest(directory).is_some() {
+            let _ = fs::remove_dir_all(staging);
+            return Ok(directory.to_path_buf());
+        }
+        return Err(error.into());
+    }
+    Ok(directory.to_path_buf())
+}
+
+/// Removes the replaced natives, the fallback directories, the staging
+/// directories of crashed launches and the files and folders extracted
+/// directly to the natives root by older versions of the launcher.
+///
+/// Natives still in use by a running game cannot be removed on Windows, and
+/// are tried again by a later launch.
+fn remove_leftovers(natives_root: &Path) {
+    let Ok(entries) = fs::read_dir(natives_root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let expired = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|elapsed| elapsed > STAGING_EXPIRY);
+        // The natives of every platform and LWJGL version are named
+        // `<platform>-lwjgl-<version>`.
+        let is_cache = !name.starts_with('.') && name.contains("-lwjgl-");
+        let removed = if !metadata.is_dir() {
+            fs::remove_file(&path).is_ok()
+        } else if name.ends_with(".stale")
+            || name.ends_with(".fallback")
+            || (name.ends_with(".tmp") && expired)
+            || (!is_cache && !name.starts_with('.'))
+        {
+            fs::remove_dir_all(&path).is_ok()
+        } else {
+            false
+        };
+        if removed {
+            debug!("Removed {}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+
+    fn native_version(minecraft: &MinecraftLocation) -> ResolvedVersion {
+        let path = "org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-linux.jar";
+        let jar = minecraft.get_library_by_path(path);
+        fs::create_dir_all(jar.parent().unwrap()).unwrap();
+        let mut writer = ZipWriter::new(File::create(&jar).unwrap());
+        writer
+            .start_file("linux/x64/liblwjgl.so", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"native").unwrap();
+        writer.finish().unwrap();
+        let mut version = ResolvedVersion {
+            id: "1.20.1".to_string(),
+            ..Default::default()
+        };
+        version
+            .libraries
+            .push(ResolvedLibrary::Native(LibraryDownloadInfo {
+                sha1: None,
+                size: None,
+                url: String::new(),
+                path: path.to_string(),
+                name: None,
+            }));
+        version
+    }
+
+    #[test]
+    fn reuses_intact_natives() {
+        let root = std::env::temp_dir().join(format!("conic-natives-{}", uuid::Uuid::new_v4()));
+        let minecraft = MinecraftLocation::new(&root);
+        let version = native_version(&minecraft);
+        let directory = natives_directory(&minecraft, &version, JavaArch::X64);
+        let name = directory.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.ends_with("-x86_64-lwjgl-3.3.3"));
+        // Left by the extraction of an older launcher.
+        fs::create_dir_all(directory.parent().unwrap().join("META-INF")).unwrap();
+
+        assert_eq!(
+            extract_natives(&minecraft, &version, &directory).unwrap(),
+            directory
+        );
+        let library = directory.join("linux/x64/liblwjgl.so");
+        assert_eq!(fs::read(&library).unwrap(), b"native");
+        let manifest = read_manifest(&directory).unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        assert!(is_intact(&directory, &manifest));
+
+        fs::write(&library, b"corrupted").unwrap();
+        assert!(!is_intact(&directory, &manifest));
+        assert_eq!(
+            extract_natives(&minecraft, &version, &directory).unwrap(),
+            directory
+        );
+        assert_eq!(fs::read(&library).unwrap(), b"native");
+        assert_eq!(
+            fs::read_dir(directory.parent().unwrap()).unwrap().count(),
+            1
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
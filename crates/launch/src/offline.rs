@@ -0,0 +1,186 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Launching without network.
+//!
+//! An offline launch sends no request: the session cached for the account is
+//! used as is, authlib-injector is not updated and the game files are checked
+//! against their checksums instead of being downloaded. Only the files that
+//! are missing or corrupted fail the launch, as nothing can fetch them.
+//!
+//! authlib-injector is given the Yggdrasil server metadata saved by the last
+//! online launch, since it cannot fetch it either.
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use account::Account;
+use download::{Checksum, DownloadTask, progress::DownloadState};
+use folder::DATA_LOCATION;
+use log::{info, warn};
+
+use crate::error::*;
+
+/// Returns `account` with the session last saved for it.
+///
+/// An expired Microsoft session is still returned for local play, as
+/// singleplayer does not check it.
+pub(crate) async fn cached_account(account: Account) -> Account {
+    match account {
+        Account::Microsoft(account) => {
+            let account = account::microsoft::get_account(account.profile.uuid)
+                .await
+                .unwrap_or(account);
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Incorrect system time")
+                .as_secs();
+            if account.expires_at > now {
+                info!(
+                    "Using the cached session, it expires in {} seconds",
+                    account.expires_at - now
+                );
+            } else {
+                warn!(
+                    "The session of {} has expired, servers in online mode will reject it until the account is refreshed",
+                    account.profile.profile_name
+                );
+            }
+            Account::Microsoft(account)
+        }
+        Account::Yggdrasil(account) => {
+            let account = account::yggdrasil::get_account(account.identifier)
+                .await
+                .unwrap_or(account);
+            warn!(
+                "The session of {} cannot be validated offline, servers may reject it",
+                account.profile.name
+            );
+            Account::Yggdrasil(account)
+        }
+        account => account,
+    }
+}
+
+/// Returns an error when the authlib-injector agent was never downloaded.
+pub(crate) fn ensure_authlib_injector() -> Result<()> {
+    let path = &DATA_LOCATION.authlib_injector;
+    if !path.is_file() {
+        return Err(Error::MissingFiles(vec![
+            path.to_string_lossy().to_string(),
+        ]));
+    }
+    Ok(())
+}
+
+/// Returns the file the metadata of the Yggdrasil server `api_root` is saved in.
+fn yggdrasil_metadata_file(api_root: &str) -> PathBuf {
+    let name = sha1_smol::Sha1::from(api_root.trim_end_matches('/'))
+        .digest()
+        .to_string();
+    DATA_LOCATION.cache.join("yggdrasil").join(name)
+}
+
+/// Saves the base64 metadata of the Yggdrasil server `api_root`, fetched by
+/// an online launch, for the offline launches.
+pub(crate) fn save_yggdrasil_metadata(api_root: &str, metadata: &str) {
+    let file = yggdrasil_metadata_file(api_root);
+    let saved = file
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&file, metadata));
+    if let Err(error) = saved {
+        warn!("Could not save the metadata of {api_root}: {error}");
+    }
+}
+
+/// Returns the base64 metadata of the Yggdrasil server `api_root` saved by
+/// the last online launch.
+pub(crate) fn cached_yggdrasil_metadata(api_root: &str) -> Option<String> {
+    let metadata = std::fs::read_to_string(yggdrasil_metadata_file(api_root)).ok();
+    if metadata.is_none() {
+        warn!("No metadata of {api_root} was saved, authlib-injector will try to fetch it");
+    }
+    metadata
+}
+
+/// Verifies the files of `tasks` against their checksums, without
+/// downloading anything.
+///
+/// Files without a checksum only have to exist. Returns
+/// [`Error::MissingFiles`] with every file that is missing or corrupted.
+pub(crate) fn verify_local_files(tasks: Vec<DownloadTask>, progress: &DownloadState) -> Result<()> {
+    let (existing, mut missing): (Vec<_>, Vec<_>) =
+        tasks.into_iter().partition(|task| task.file.is_file());
+    let to_verify = existing
+        .into_iter()
+        .filter(|task| task.checksum != Checksum::None)
+        .collect();
+    for task in download::filter_existing_and_verified_files(to_verify, progress) {
+        warn!("{} does not match its checksum", task.file.display());
+        missing.push(task);
+    }
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let mut files: Vec<String> = missing
+        .into_iter()
+        .map(|task| task.file.to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    for file in &files {
+        warn!("Unavailable offline: {file}");
+    }
+    Err(Error::MissingFiles(files))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use download::DownloadTaskType;
+
+    use super::*;
+
+    fn task(file: PathBuf, checksum: Checksum) -> DownloadTask {
+        DownloadTask {
+            url: String::new(),
+            file,
+            size_bytes: None,
+            checksum,
+            task_type: DownloadTaskType::Unknown,
+        }
+    }
+
+    #[test]
+    fn reports_missing_and_corrupted_files() {
+        let dir = std::env::temp_dir().join(format!("conic-offline-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("valid"), "conic").unwrap();
+        fs::write(dir.join("corrupted"), "conic").unwrap();
+        fs::write(dir.join("unchecked"), "conic").unwrap();
+        let sha1 = sha1_smol::Sha1::from("conic").digest().to_string();
+        let progress = DownloadState::default();
+
+        let valid = vec![
+            task(dir.join("valid"), Checksum::Sha1(sha1.clone())),
+            task(dir.join("unchecked"), Checksum::None),
+        ];
+        assert!(verify_local_files(valid, &progress).is_ok());
+
+        let broken = vec![
+            task(dir.join("valid"), Checksum::Sha1(sha1)),
+            task(dir.join("corrupted"), Checksum::Sha1("0".repeat(40))),
+            task(dir.join("missing"), Checksum::None),
+        ];
+        let Err(Error::MissingFiles(files)) = verify_local_files(broken, &progress) else {
+            panic!("missing files not reported");
+        };
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("corrupted") && files[1].ends_with("missing"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -2,17 +2,20 @@
 // Copyright 2022-2026 ConicMC developers. All rights reserved.
 // SPDX-License-Identifier: GPL-3.0-only
 
-use account::Account;
+use std::{collections::HashMap, path::PathBuf};
+
+use account::{Account, offline::OfflineAccount, yggdrasil::yggdrasil_user_api};
 use config::{
     Config,
-    launch::{GC, Server},
+    launch::{GC, GpuPreference, JavaAgent, JvmPreset, LinuxPerformance, NativesMode},
 };
 use folder::DATA_LOCATION;
-use instance::Instance;
-use java_runtime::JavaArch;
+use instance::{AccountBinding, Instance};
+use java_runtime::{JavaArch, JavaVendor};
 use log::info;
+use uuid::Uuid;
 
-use crate::error::*;
+use crate::{error::*, quick_play::QuickPlay, tuning};
 
 /// Represents all launch options required to start a Minecraft instance.
 ///
@@ -23,6 +26,10 @@ pub struct LaunchOptions {
 
     pub user_properties: String,
 
+    /// Allocate the memory from the available physical memory, see
+    /// [`LaunchOptions::allocate_memory`].
+    pub auto_memory: bool,
+
     /// Max memory, this will add a jvm flag -Xmx to the command result
     pub max_memory: usize,
 
@@ -30,8 +37,8 @@ pub struct LaunchOptions {
     /// command result. Only set when auto memory allocation is used.
     pub xmn_memory: usize,
 
-    /// Enter a server after launch. TODO: support 1.21.1
-    pub server: Option<Server>,
+    /// Enter a world, server or realm after launch.
+    pub quick_play: Option<QuickPlay>,
 
     /// window width
     pub width: usize,
@@ -65,81 +72,194 @@ pub struct LaunchOptions {
 
     pub gc: GC,
 
+    /// The JVM tuning preset, which replaces the flags of [`Self::gc`].
+    pub jvm_preset: Option<JvmPreset>,
+
+    /// The flags of [`Self::jvm_preset`] supported by the Java runtime, see
+    /// [`LaunchOptions::select_jvm_flags`].
+    pub jvm_preset_flags: Option<Vec<String>>,
+
     pub launcher_name: String,
 
     /// Optional command used to wrap the final launch command.
     pub wrap_command: String,
 
+    /// Performance tools and process settings, only applied on Linux.
+    pub linux_performance: LinuxPerformance,
+
+    /// The GPU running the game, only applied on Linux.
+    pub gpu: GpuPreference,
+
+    /// Where the LWJGL natives are loaded from, only applied on Linux.
+    pub natives_mode: NativesMode,
+
+    /// The directory the natives are extracted to, set once the Java runtime
+    /// is known. `None` uses the natives root of the version.
+    pub natives_directory: Option<PathBuf>,
+
+    /// The agents of the global launch config followed by the agents of the
+    /// instance.
+    pub java_agents: Vec<JavaAgent>,
+
+    /// Where Java Flight Recorder records the game from its start, set for a
+    /// profile launch.
+    pub profile_recording: Option<PathBuf>,
+
+    /// The values of the launcher placeholders, set once the Java runtime is
+    /// known.
+    pub placeholders: HashMap<&'static str, String>,
+
     /// Shell command to execute before the game launches.
     pub execute_before_launch: String,
 
+    /// Shell command to execute once the game window is ready.
+    pub execute_after_start: String,
+
     /// Shell command to execute after the game exits.
     pub execute_after_launch: String,
+
+    /// Shell command to execute after the game exits with an error.
+    pub execute_on_crash: String,
+
+    /// Seconds a hook may run before it is killed, `0` disables the timeout.
+    pub hook_timeout_secs: u64,
+
+    /// Start the legacy proxy for versions older than the cutoff.
+    pub legacy_proxy: bool,
+
+    /// Base64 metadata of the Yggdrasil server, prefetched for authlib-injector
+    /// right before launch.
+    pub yggdrasil_prefetched: Option<String>,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            selected_account: Account::Offline(OfflineAccount {
+                name: "Steve".to_string(),
+                uuid: Uuid::nil(),
+                skin: None,
+            }),
+            user_properties: "{}".to_string(),
+            auto_memory: true,
+            max_memory: 2048,
+            xmn_memory: 0,
+            quick_play: None,
+            width: 854,
+            height: 480,
+            fullscreen: false,
+            extra_jvm_args: String::new(),
+            extra_mc_args: String::new(),
+            is_demo: false,
+            ignore_invalid_minecraft_certificates: false,
+            ignore_patch_discrepancies: false,
+            extra_class_paths: String::new(),
+            gc: GC::default(),
+            jvm_preset: None,
+            jvm_preset_flags: None,
+            launcher_name: "Conic_Launcher".to_string(),
+            wrap_command: String::new(),
+            linux_performance: LinuxPerformance::default(),
+            gpu: GpuPreference::default(),
+            natives_mode: NativesMode::default(),
+            natives_directory: None,
+            java_agents: Vec::new(),
+            profile_recording: None,
+            placeholders: HashMap::new(),
+            execute_before_launch: String::new(),
+            execute_after_start: String::new(),
+            execute_after_launch: String::new(),
+            execute_on_crash: String::new(),
+            hook_timeout_secs: 60,
+            legacy_proxy: true,
+            yggdrasil_prefetched: None,
+        }
+    }
 }
 
 impl LaunchOptions {
     /// Creates a new [`LaunchOptions`] instance from the given Minecraft instance and account.
     ///
+    /// The game is launched with the account bound to the instance, or with
+    /// the account selected in the launcher when none is. Returns
+    /// [`Error::BoundAccountMissing`] when the bound account was removed.
+    ///
     /// Launch configuration is resolved from both global and per-instance settings,
     /// with per-instance settings taking priority when defined.
     ///
-    /// `java_arch` is the architecture of the Java runtime that will be used to
-    /// launch the game; a 32-bit runtime caps the auto-allocated heap at 1 GiB.
-    pub fn new(config: &Config, instance: &Instance, java_arch: JavaArch) -> Result<Self> {
+    /// With auto memory allocation, the memory is left unset until
+    /// [`LaunchOptions::allocate_memory`] is called with the Java runtime.
+    pub async fn new(config: &Config, instance: &Instance) -> Result<Self> {
         let global_launch_config = config.launch.clone();
         let launch_config = &instance.config.launch_config;
-        let selected_account = match config.current_account.clone() {
-            None => return Err(Error::InvalidProfile),
-            Some(account) => account,
+        let selected_account = match &instance.config.account {
+            Some(binding) => check_bound_account(binding, load_bound_account(binding).await)?,
+            None => config
+                .current_account
+                .clone()
+                .ok_or(Error::InvalidProfile)?,
         };
         let auto_memory = launch_config
             .auto_memory
             .unwrap_or(global_launch_config.auto_memory);
-        let is_32_bit = is_32_bit_java(java_arch);
-        let (max_memory, xmn_memory) = if auto_memory {
-            let available = platform::get_available_memory_bytes();
-            let mod_count = count_instance_mods(instance);
-            let (max_memory, xmn_memory) = auto_allocate_memory(
-                available,
-                instance_has_mod_loader(instance),
-                mod_count,
-                is_32_bit,
-            );
-            info!(
-                "Auto memory allocation: -Xmx{max_memory}M -Xmn{xmn_memory}M \
-                 (available {} MiB, mod count {mod_count}, {} Java)",
-                available / 1024 / 1024,
-                if is_32_bit { "32-bit" } else { "64-bit" }
-            );
-            (max_memory, xmn_memory)
+        let max_memory = if auto_memory {
+            0
         } else {
-            let max_memory = launch_config
+            launch_config
                 .max_memory
-                .unwrap_or(global_launch_config.max_memory);
-            info!("Manual memory allocation: -Xmx{max_memory}M");
-            (max_memory, 0)
+                .unwrap_or(global_launch_config.max_memory)
         };
         Ok(Self {
             selected_account,
+            auto_memory,
             max_memory,
-            xmn_memory,
+            xmn_memory: 0,
             wrap_command: launch_config
                 .wrap_command
                 .clone()
                 .unwrap_or(global_launch_config.wrap_command),
+            linux_performance: launch_config
+                .linux_performance
+                .clone()
+                .unwrap_or(global_launch_config.linux_performance),
+            gpu: launch_config.gpu.clone().unwrap_or_default(),
+            natives_mode: launch_config
+                .natives_mode
+                .clone()
+                .unwrap_or(global_launch_config.natives_mode),
+            natives_directory: None,
+            java_agents: global_launch_config
+                .java_agents
+                .iter()
+                .chain(launch_config.java_agents.iter().flatten())
+                .cloned()
+                .collect(),
+            profile_recording: None,
+            placeholders: HashMap::new(),
             execute_before_launch: launch_config
                 .execute_before_launch
                 .clone()
                 .unwrap_or(global_launch_config.execute_before_launch),
+            execute_after_start: launch_config
+                .execute_after_start
+                .clone()
+                .unwrap_or(global_launch_config.execute_after_start),
             execute_after_launch: launch_config
                 .execute_after_launch
                 .clone()
                 .unwrap_or(global_launch_config.execute_after_launch),
+            execute_on_crash: launch_config
+                .execute_on_crash
+                .clone()
+                .unwrap_or(global_launch_config.execute_on_crash),
+            hook_timeout_secs: launch_config
+                .hook_timeout_secs
+                .unwrap_or(global_launch_config.hook_timeout_secs),
             launcher_name: launch_config
                 .launcher_name
                 .clone()
                 .unwrap_or(global_launch_config.launcher_name),
-            server: launch_config.server.clone(),
+            quick_play: QuickPlay::configured(instance),
             width: launch_config.width.unwrap_or(global_launch_config.width),
             height: launch_config.height.unwrap_or(global_launch_config.height),
             fullscreen: launch_config
@@ -167,9 +287,130 @@ impl LaunchOptions {
                 .clone()
                 .unwrap_or(global_launch_config.extra_class_paths),
             gc: launch_config.gc.clone().unwrap_or(global_launch_config.gc),
+            jvm_preset: tuning::selected_preset(config, instance),
+            jvm_preset_flags: None,
+            legacy_proxy: launch_config
+                .legacy_proxy
+                .unwrap_or(global_launch_config.legacy_proxy),
             user_properties: "{}".to_string(),
+            yggdrasil_prefetched: None,
         })
     }
+
+    /// Sets the memory of the game, given the architecture of the Java runtime
+    /// that will be used to launch it.
+    ///
+    /// A 32-bit runtime caps the auto-allocated heap at 1 GiB.
+    pub fn allocate_memory(&mut self, instance: &Instance, java_arch: JavaArch) {
+        if !self.auto_memory {
+            info!("Manual memory allocation: -Xmx{}M", self.max_memory);
+            return;
+        }
+        let is_32_bit = is_32_bit_java(java_arch);
+        let available = platform::get_available_memory_bytes();
+        let mod_count = count_instance_mods(instance);
+        let (max_memory, xmn_memory) = auto_allocate_memory(
+            available,
+            instance_has_mod_loader(instance),
+            mod_count,
+            is_32_bit,
+        );
+        info!(
+            "Auto memory allocation: -Xmx{max_memory}M -Xmn{xmn_memory}M \
+             (available {} MiB, mod count {mod_count}, {} Java)",
+            available / 1024 / 1024,
+            if is_32_bit { "32-bit" } else { "64-bit" }
+        );
+        self.max_memory = max_memory;
+        self.xmn_memory = xmn_memory;
+    }
+
+    /// Keeps the flags of the JVM tuning preset that the Java runtime used to
+    /// launch the game supports.
+    pub fn select_jvm_flags(&mut self, major_version: Option<u32>, vendor: JavaVendor) {
+        self.jvm_preset_flags = self.jvm_preset.as_ref().map(|preset| {
+            info!("Using JVM preset: {}", preset.name);
+            tuning::supported_flags(preset, major_version, vendor)
+        });
+    }
+
+    /// The features enabling the rule-gated arguments of the version JSON.
+    pub fn enabled_features(&self) -> Vec<String> {
+        let mut features = Vec::new();
+        if self.is_demo {
+            features.push("is_demo_user".to_string());
+        }
+        if !self.fullscreen {
+            features.push("has_custom_resolution".to_string());
+        }
+        if let Some(quick_play) = &self.quick_play {
+            features.extend(quick_play.features());
+        }
+        features
+    }
+}
+
+/// Loads the saved account `binding` refers to.
+async fn load_bound_account(binding: &AccountBinding) -> account::Result<Account> {
+    match binding {
+        AccountBinding::Microsoft { uuid } => account::microsoft::get_account(*uuid)
+            .await
+            .map(Account::Microsoft),
+        AccountBinding::Offline { uuid } => account::offline::get_account(*uuid)
+            .await
+            .map(Account::Offline),
+        AccountBinding::Yggdrasil { identifier, .. } => {
+            account::yggdrasil::get_account(*identifier)
+                .await
+                .map(Account::Yggdrasil)
+        }
+    }
+}
+
+/// Returns the `account` loaded for `binding`, or
+/// [`Error::BoundAccountMissing`] when it was removed.
+fn check_bound_account(
+    binding: &AccountBinding,
+    account: account::Result<Account>,
+) -> Result<Account> {
+    match (binding, account) {
+        // A binding made for another server does not match.
+        (AccountBinding::Yggdrasil { api_root, .. }, Ok(Account::Yggdrasil(account)))
+            if account.api_root.trim_end_matches('/') != api_root.trim_end_matches('/') =>
+        {
+            Err(Error::BoundAccountMissing(binding.to_string()))
+        }
+        (_, Ok(account)) => {
+            info!("Using the {binding} bound to the instance");
+            Ok(account)
+        }
+        (_, Err(account::Error::AccountNotfound(_))) => {
+            Err(Error::BoundAccountMissing(binding.to_string()))
+        }
+        (_, Err(error)) => Err(error.into()),
+    }
+}
+
+/// Refreshes the account bound to the instance before the launch, as the
+/// frontend does for the account selected in the launcher. Microsoft accounts
+/// are refreshed before their token expires, Yggdrasil accounts when their
+/// server no longer validates it.
+pub(crate) async fn refresh_bound_account(account: Account) -> Result<Account> {
+    match account {
+        Account::Microsoft(account) => Ok(Account::Microsoft(
+            account::microsoft::refresh_account(account.profile.uuid, false).await?,
+        )),
+        Account::Yggdrasil(account) => {
+            if yggdrasil_user_api::validate(account.clone()).await? {
+                return Ok(Account::Yggdrasil(account));
+            }
+            info!("Refreshing the Yggdrasil account: {}", account.identifier);
+            let refreshed = yggdrasil_user_api::refresh(account).await?;
+            account::yggdrasil::update_account(refreshed.identifier, refreshed.clone()).await?;
+            Ok(Account::Yggdrasil(refreshed))
+        }
+        account => Ok(account),
+    }
 }
 
 /// Returns whether the instance has a mod loader installed.
@@ -250,3 +491,83 @@ fn auto_allocate_memory(
     let xmn_memory = (ram_give * 1024.0 * 0.15).floor() as usize;
     (max_memory, xmn_memory)
 }
+
+#[cfg(test)]
+mod tests {
+    use account::yggdrasil::{YggdrasilAccount, yggdrasil_user_api::Profile};
+    use instance::InstanceConfig;
+
+    use super::*;
+
+    fn offline(name: &str) -> Account {
+        Account::Offline(OfflineAccount {
+            name: name.to_string(),
+            uuid: Uuid::new_v4(),
+            skin: None,
+        })
+    }
+
+    fn yggdrasil(api_root: &str) -> Account {
+        Account::Yggdrasil(YggdrasilAccount {
+            api_root: api_root.to_string(),
+            username: "alex@example.com".to_string(),
+            access_token: String::new(),
+            client_token: String::new(),
+            identifier: Uuid::new_v4(),
+            profile: Profile {
+                id: Uuid::new_v4(),
+                name: "Alex".to_string(),
+                properties: None,
+            },
+            textures: HashMap::new(),
+            added_at: 0,
+        })
+    }
+
+    #[test]
+    fn resolves_bound_accounts() {
+        let uuid = Uuid::new_v4();
+        let binding = AccountBinding::Offline { uuid };
+        let account = check_bound_account(&binding, Ok(offline("Alex"))).unwrap();
+        assert_eq!(account.get_profile_name(), "Alex");
+        assert!(matches!(
+            check_bound_account(&binding, Err(account::Error::AccountNotfound(uuid))),
+            Err(Error::BoundAccountMissing(_))
+        ));
+
+        let binding = AccountBinding::Yggdrasil {
+            identifier: Uuid::new_v4(),
+            api_root: "https://example.com/api/yggdrasil".to_string(),
+        };
+        assert!(
+            check_bound_account(
+                &binding,
+                Ok(yggdrasil("https://example.com/api/yggdrasil/"))
+            )
+            .is_ok()
+        );
+        assert!(matches!(
+            check_bound_account(&binding, Ok(yggdrasil("https://example.org/api/yggdrasil"))),
+            Err(Error::BoundAccountMissing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_selected_account() {
+        let instance = Instance {
+            config: InstanceConfig::new("Unbound", "1.21.4"),
+            installed: true,
+            id: "unbound".to_string(),
+            last_played: None,
+            has_background: false,
+        };
+        let mut config = Config::default();
+        assert!(matches!(
+            LaunchOptions::new(&config, &instance).await,
+            Err(Error::InvalidProfile)
+        ));
+        config.current_account = Some(offline("Steve"));
+        let options = LaunchOptions::new(&config, &instance).await.unwrap();
+        assert_eq!(options.selected_account.get_profile_name(), "Steve");
+    }
+}
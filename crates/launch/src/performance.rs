@@ -0,0 +1,197 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Performance tools wrapping the game on Linux.
+//!
+//! Feral GameMode, MangoHud and the niceness are prefixed to the command line
+//! in this order, before the wrap command of the user. A selected tool that is
+//! not installed fails the launch, rather than failing to spawn the game.
+
+use std::env;
+
+use config::launch::LinuxPerformance;
+use log::warn;
+use serde::Serialize;
+
+use crate::error::*;
+
+/// The performance tools installed on the system.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct PerformanceTools {
+    pub gamemode: bool,
+    pub mangohud: bool,
+}
+
+impl PerformanceTools {
+    /// Looks for the tools in `PATH`. No tool is available outside of Linux.
+    pub fn detect() -> Self {
+        let installed =
+            |name: &str| cfg!(target_os = "linux") && platform::find_executable(name).is_some();
+        Self {
+            gamemode: installed("gamemoderun"),
+            mangohud: installed("mangohud"),
+        }
+    }
+}
+
+/// The command prefix and the environment applying the performance options.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PerformanceWrapper {
+    pub prefix: Vec<String>,
+    pub env: Vec<(String, String)>,
+}
+
+/// Composes `options` into a [`PerformanceWrapper`] with the installed
+/// `tools`.
+///
+/// Returns [`Error::PerformanceToolMissing`] when a selected tool is not
+/// installed, and [`Error::InvalidPerformanceOption`] for invalid values.
+pub(crate) fn compose(
+    options: &LinuxPerformance,
+    tools: PerformanceTools,
+) -> Result<PerformanceWrapper> {
+    let mut wrapper = PerformanceWrapper::default();
+    if !cfg!(target_os = "linux") {
+        if options.gamemode
+            || options.mangohud
+            || options.niceness.is_some()
+            || !options.env.is_empty()
+            || !options.ld_preload.is_empty()
+        {
+            warn!("Linux performance options are ignored on this platform");
+        }
+        return Ok(wrapper);
+    }
+
+    if let Some(niceness) = options.niceness.filter(|niceness| *niceness != 0) {
+        if !(-20..=19).contains(&niceness) {
+            return Err(Error::InvalidPerformanceOption(format!(
+                "niceness {niceness} is not between -20 and 19"
+            )));
+        }
+        wrapper
+            .prefix
+            .extend(["nice".to_string(), "-n".to_string(), niceness.to_string()]);
+    }
+    if options.gamemode {
+        if !tools.gamemode {
+            return Err(Error::PerformanceToolMissing("GameMode".to_string()));
+        }
+        wrapper.prefix.push("gamemoderun".to_string());
+    }
+    if options.mangohud {
+        if !tools.mangohud {
+            return Err(Error::PerformanceToolMissing("MangoHud".to_string()));
+        }
+        wrapper.prefix.push("mangohud".to_string());
+    }
+
+    for (name, value) in &options.env {
+        if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+            return Err(Error::InvalidPerformanceOption(format!(
+                "{name:?} is not a valid environment variable"
+            )));
+        }
+        wrapper.env.push((name.clone(), value.clone()));
+    }
+
+    if !options.ld_preload.is_empty() {
+        for library in &options.ld_preload {
+            if library.is_empty() || library.contains([':', ' ']) {
+                return Err(Error::InvalidPerformanceOption(format!(
+                    "{library:?} is not a valid LD_PRELOAD entry"
+                )));
+            }
+            // Bare names are searched by the dynamic linker, paths must exist.
+            if library.contains('/') && !std::path::Path::new(library).is_file() {
+                return Err(Error::InvalidPerformanceOption(format!(
+                    "{library} does not exist"
+                )));
+            }
+        }
+        let mut preload = options.ld_preload.join(":");
+        if let Some(inherited) = options
+            .env
+            .get("LD_PRELOAD")
+            .cloned()
+            .or_else(|| env::var("LD_PRELOAD").ok())
+            .filter(|inherited| !inherited.is_empty())
+        {
+            preload = format!("{preload}:{inherited}");
+        }
+        wrapper.env.retain(|(name, _)| name != "LD_PRELOAD");
+        wrapper.env.push(("LD_PRELOAD".to_string(), preload));
+    }
+    Ok(wrapper)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    const NO_TOOLS: PerformanceTools = PerformanceTools {
+        gamemode: false,
+        mangohud: false,
+    };
+
+    #[test]
+    fn composes_wrapper() {
+        let options = LinuxPerformance {
+            gamemode: true,
+            mangohud: true,
+            env: [
+                ("LD_PRELOAD".to_string(), "libextra.so".to_string()),
+                ("MANGOHUD_CONFIG".to_string(), "fps_limit=144".to_string()),
+            ]
+            .into(),
+            ld_preload: vec!["libfoo.so".to_string()],
+            niceness: Some(5),
+        };
+        let tools = PerformanceTools {
+            gamemode: true,
+            mangohud: true,
+        };
+        let wrapper = compose(&options, tools).unwrap();
+        assert_eq!(
+            wrapper.prefix,
+            vec!["nice", "-n", "5", "gamemoderun", "mangohud"]
+        );
+        assert_eq!(
+            wrapper.env,
+            vec![
+                ("MANGOHUD_CONFIG".to_string(), "fps_limit=144".to_string()),
+                (
+                    "LD_PRELOAD".to_string(),
+                    "libfoo.so:libextra.so".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_tools_and_invalid_values() {
+        let gamemode = LinuxPerformance {
+            gamemode: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            compose(&gamemode, NO_TOOLS),
+            Err(Error::PerformanceToolMissing(_))
+        ));
+        let niceness = LinuxPerformance {
+            niceness: Some(40),
+            ..Default::default()
+        };
+        assert!(compose(&niceness, NO_TOOLS).is_err());
+        let preload = LinuxPerformance {
+            ld_preload: vec!["/nonexistent/libfoo.so".to_string()],
+            ..Default::default()
+        };
+        assert!(compose(&preload, NO_TOOLS).is_err());
+        assert_eq!(
+            compose(&LinuxPerformance::default(), NO_TOOLS).unwrap(),
+            PerformanceWrapper::default()
+        );
+    }
+}
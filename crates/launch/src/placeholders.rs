@@ -0,0 +1,217 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Expansion of the `${...}` placeholders in the arguments, wrap command and
+//! hooks set by the user.
+//!
+//! Which placeholders a string may contain is decided by its
+//! [`config::launch::PlaceholderScope`], and checked when the config is saved. Placeholders
+//! that are still unknown at launch are left as they are.
+
+use std::{collections::HashMap, path::Path};
+
+use config::launch::{find_placeholders, is_shell_variable};
+use folder::DATA_LOCATION;
+use instance::Instance;
+use log::warn;
+
+use crate::{
+    command::{ScriptShell, split_shell_words},
+    error::*,
+};
+
+/// Returns the values of the launcher placeholders of a launch of `instance`
+/// by `account_name` on the Java executable `java_path`.
+pub(crate) fn launcher_placeholders(
+    instance: &Instance,
+    account_name: String,
+    java_path: &Path,
+) -> HashMap<&'static str, String> {
+    let java_home = java_path
+        .parent()
+        .and_then(Path::parent)
+        .unwrap_or(java_path);
+    HashMap::from([
+        (
+            "instance_dir",
+            DATA_LOCATION
+                .get_instance_root(&instance.id)
+                .to_string_lossy()
+                .to_string(),
+        ),
+        ("instance_name", instance.config.name.clone()),
+        ("java_home", java_home.to_string_lossy().to_string()),
+        (
+            "minecraft_version",
+            instance.config.runtime.minecraft.clone(),
+        ),
+        (
+            "loader",
+            instance
+                .config
+                .runtime
+                .mod_loader_type
+                .as_ref()
+                .map(|loader| loader.to_string().to_lowercase())
+                .unwrap_or_else(|| "vanilla".to_string()),
+        ),
+        ("account_name", account_name),
+    ])
+}
+
+/// Replaces the placeholders of `template` with their value in `values`.
+pub(crate) fn expand_placeholders(template: &str, values: &HashMap<&str, String>) -> String {
+    expand_with(template, values, false, |_, value| value.to_string())
+}
+
+/// Splits `input` into words like [`split_shell_words`] and expands the
+/// placeholders of each word, so values with spaces stay one argument.
+pub(crate) fn expand_shell_words(
+    input: &str,
+    values: &HashMap<&str, String>,
+) -> Result<Vec<String>> {
+    Ok(split_shell_words(input)?
+        .iter()
+        .map(|word| expand_placeholders(word, values))
+        .collect())
+}
+
+/// Replaces the placeholders of the shell command `command`, escaping their
+/// values so they stay single words. Other names are left to the shell.
+pub(crate) fn expand_shell_placeholders(command: &str, values: &HashMap<&str, String>) -> String {
+    let shell = ScriptShell::native();
+    expand_with(command, values, true, |before, value| {
+        match (shell, open_quote(shell, before)) {
+            (_, None) => shell.quote(value),
+            (ScriptShell::Sh, Some('\'')) => value.replace('\'', r"'\''"),
+            (ScriptShell::Sh, Some(_)) => value
+                .chars()
+                .flat_map(|c| {
+                    let escape = matches!(c, '\\' | '"' | '$' | '`').then_some('\\');
+                    escape.into_iter().chain([c])
+                })
+                .collect(),
+            // Batch files expand `%` even within double quotes, which
+            // Windows paths and names cannot contain.
+            (ScriptShell::Cmd, Some(_)) => value.replace('"', "").replace('%', "%%"),
+        }
+    })
+}
+
+/// Returns the quote left open at the end of the shell command `command`.
+fn open_quote(shell: ScriptShell, command: &str) -> Option<char> {
+    let mut open = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (shell, open, c) {
+            (ScriptShell::Sh, Some('"') | None, '\\') => {
+                chars.next();
+            }
+            (ScriptShell::Sh, None, '\'' | '"') | (ScriptShell::Cmd, None, '"') => open = Some(c),
+            (_, Some(quote), _) if quote == c => open = None,
+            _ => {}
+        }
+    }
+    open
+}
+
+/// Replaces the placeholders of `template` found in `values` with
+/// `format_value` of the text before them and their value. Unknown names are
+/// left as they are, silently when `shell_variables` and they can be shell
+/// variables.
+fn expand_with(
+    template: &str,
+    values: &HashMap<&str, String>,
+    shell_variables: bool,
+    format_value: impl Fn(&str, &str) -> String,
+) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut end = 0;
+    for (range, name) in find_placeholders(template) {
+        expanded.push_str(&template[end..range.start]);
+        match values.get(name) {
+            Some(value) => expanded.push_str(&format_value(&template[..range.start], value)),
+            None => {
+                if !(shell_variables && is_shell_variable(name)) {
+                    warn!("Unknown placeholder ${{{name}}} left unexpanded");
+                }
+                expanded.push_str(&template[range.clone()]);
+            }
+        }
+        end = range.end;
+    }
+    expanded.push_str(&template[end..]);
+    expanded
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use config::launch::LAUNCHER_PLACEHOLDERS;
+    use instance::InstanceConfig;
+
+    use super::*;
+
+    #[test]
+    fn launcher_placeholders_match_the_accepted_names() {
+        let instance = Instance {
+            config: InstanceConfig::new("Pack", "1.20.1"),
+            installed: true,
+            id: "pack".to_string(),
+            last_played: None,
+            has_background: false,
+        };
+        let values = launcher_placeholders(
+            &instance,
+            "Steve".to_string(),
+            Path::new("/usr/lib/jvm/java-17/bin/java"),
+        );
+        assert_eq!(
+            values.into_keys().collect::<BTreeSet<_>>(),
+            BTreeSet::from(LAUNCHER_PLACEHOLDERS)
+        );
+    }
+
+    #[test]
+    fn expands_known_placeholders() {
+        let values = HashMap::from([
+            ("instance_name", "My Pack".to_string()),
+            ("loader", "fabric".to_string()),
+        ]);
+        assert_eq!(
+            expand_placeholders("-Dpack=${instance_name}-${loader}", &values),
+            "-Dpack=My Pack-fabric"
+        );
+        assert_eq!(
+            expand_placeholders("${unknown} ${loader", &values),
+            "${unknown} ${loader"
+        );
+        assert_eq!(
+            expand_shell_words("-Dname=${instance_name} -Xss2M", &values).unwrap(),
+            vec!["-Dname=My Pack", "-Xss2M"]
+        );
+        assert_eq!(
+            expand_shell_placeholders("echo ${instance_name} ${HOME} ${user}", &values),
+            "echo 'My Pack' ${HOME} ${user}"
+        );
+    }
+
+    #[test]
+    fn escapes_values_inside_quotes() {
+        let values = HashMap::from([("instance_name", "Bob's \"$pack\"".to_string())]);
+        assert_eq!(
+            expand_shell_placeholders("echo \"name: ${instance_name}\"", &values),
+            r#"echo "name: Bob's \"\$pack\"""#
+        );
+        assert_eq!(
+            expand_shell_placeholders("echo 'name: ${instance_name}'", &values),
+            r#"echo 'name: Bob'\''s "$pack"'"#
+        );
+        assert_eq!(
+            expand_shell_placeholders("echo \"it's\" ${instance_name}", &values),
+            r#"echo "it's" 'Bob'\''s "$pack"'"#
+        );
+    }
+}
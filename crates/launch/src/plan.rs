@@ -0,0 +1,95 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Dry runs of a launch.
+
+use std::path::PathBuf;
+
+use config::Config;
+use folder::{DATA_LOCATION, MinecraftLocation};
+use instance::Instance;
+use serde::Serialize;
+
+use crate::{
+    JavaSource,
+    arguments::get_natives_directory,
+    error::*,
+    game_environment,
+    performance::{self, PerformanceTools},
+    placeholders::expand_shell_words,
+    prepare_launch,
+};
+
+/// Replaces secrets in a [`LaunchPlan`].
+const REDACTED: &str = "<redacted>";
+
+/// Everything the launcher would run to start an instance.
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchPlan {
+    pub java_path: PathBuf,
+    /// Why this Java runtime was chosen.
+    pub java_source: JavaSource,
+    pub jvm_arguments: Vec<String>,
+    pub main_class: String,
+    pub game_arguments: Vec<String>,
+    pub classpath: Vec<String>,
+    pub natives_directory: PathBuf,
+    /// The wrapper command the Java command line is passed to, if any.
+    pub wrapper: Vec<String>,
+    /// Variables set in addition to the environment of the launcher.
+    pub env: Vec<(String, String)>,
+    pub cwd: PathBuf,
+}
+
+/// Resolves the command line of `instance` without spawning the game or a
+/// Java runtime, contacting the network or writing any file.
+///
+/// Java runtimes are only known from the ones inspected by earlier launches,
+/// so the runtime may be reported without its version and architecture, and
+/// a system runtime must have been used before to be found.
+///
+/// The access token of the account is redacted. The arguments of the legacy
+/// proxy and the prefetched metadata of Yggdrasil servers are left out, as
+/// they only exist during a real launch.
+pub async fn plan_launch(config: &Config, instance: &Instance) -> Result<LaunchPlan> {
+    let minecraft_location = MinecraftLocation::new(&DATA_LOCATION.root);
+    let prepared = prepare_launch(config, instance, &minecraft_location, true).await?;
+    let access_token = prepared.launch_options.selected_account.get_access_token();
+    let arguments = prepared.arguments;
+    let performance = performance::compose(
+        &prepared.launch_options.linux_performance,
+        PerformanceTools::detect(),
+    )?;
+    let mut wrapper = performance.prefix;
+    wrapper.extend(expand_shell_words(
+        &prepared.launch_options.wrap_command,
+        &prepared.launch_options.placeholders,
+    )?);
+    Ok(LaunchPlan {
+        java_path: prepared.java.path,
+        java_source: prepared.java.source,
+        jvm_arguments: redact(arguments.jvm, &access_token),
+        main_class: arguments.main_class,
+        game_arguments: redact(arguments.game, &access_token),
+        classpath: arguments.classpath,
+        natives_directory: get_natives_directory(
+            &minecraft_location,
+            &prepared.launch_options,
+            &prepared.resolved_version,
+        ),
+        wrapper,
+        env: game_environment(&prepared.launch_options, performance.env),
+        cwd: DATA_LOCATION.get_instance_root(&instance.id),
+    })
+}
+
+fn redact(arguments: Vec<String>, secret: &str) -> Vec<String> {
+    if secret.is_empty() {
+        return arguments;
+    }
+    arguments
+        .into_iter()
+        .map(|argument| argument.replace(secret, REDACTED))
+        .collect()
+}
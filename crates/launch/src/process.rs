@@ -0,0 +1,271 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Registry of the game processes started by the launcher.
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::{
+    diagnosis::Diagnosis,
+    error::*,
+    logs::LogHub,
+    monitor::{ResourceSample, ResourceStats, ResourceSummary},
+    profiling::ProfileReport,
+};
+
+/// The Tauri event [`GameEvent`]s are emitted on.
+pub const GAME_EVENT_CHANNEL: &str = "conic-launch://game";
+
+/// A game process started by the launcher.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RunningGame {
+    pub pid: u32,
+    pub instance_id: String,
+    pub instance_name: String,
+    /// Profile name of the account the game was launched with.
+    pub account: String,
+    /// Unix timestamp in seconds.
+    pub started_at: u64,
+    /// The Java executable running the game, whose JDK tools can profile it.
+    pub java_path: PathBuf,
+}
+
+/// Information about a game process that has exited.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GameExit {
+    pub pid: u32,
+    pub instance_id: String,
+    /// `None` when the process was terminated by a signal.
+    pub exit_code: Option<i32>,
+    pub duration_secs: u64,
+    /// The analysis of the crash, when the game exited with an error.
+    pub diagnosis: Option<Diagnosis>,
+    /// Peak and average resource usage, `None` when the game was never
+    /// sampled.
+    pub usage: Option<ResourceSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "type", content = "payload")]
+pub enum GameEvent {
+    Started(RunningGame),
+    Exited(GameExit),
+    /// The resource usage of a running game, sampled every few seconds.
+    Usage(ResourceSample),
+    /// The heap usage of a game came close to its `-Xmx`.
+    HeapWarning(ResourceSample),
+    /// The recording of a profile launch was summarized after the game
+    /// exited.
+    Profiled(ProfileReport),
+}
+
+type EventEmitter = Arc<dyn Fn(&GameEvent) + Send + Sync>;
+
+/// Tracks every running game, so several instances can run at once.
+#[derive(Clone)]
+pub struct GameRegistry {
+    games: Arc<Mutex<HashMap<u32, RunningGame>>>,
+    usage: Arc<Mutex<HashMap<u32, ResourceStats>>>,
+    logs: LogHub,
+    emitter: EventEmitter,
+}
+
+impl Default for GameRegistry {
+    fn default() -> Self {
+        Self::new(|_| {})
+    }
+}
+
+impl GameRegistry {
+    /// Creates a registry calling `emitter` whenever a game starts or exits.
+    pub fn new(emitter: impl Fn(&GameEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            games: Arc::new(Mutex::new(HashMap::new())),
+            usage: Arc::new(Mutex::new(HashMap::new())),
+            logs: LogHub::default(),
+            emitter: Arc::new(emitter),
+        }
+    }
+
+    pub fn register(&self, game: RunningGame) {
+        info!(
+            "Registered game process {} of instance {}",
+            game.pid, game.instance_name
+        );
+        self.logs.start(game.pid, &game.instance_id);
+        self.usage
+            .lock()
+            .expect("Internal error")
+            .insert(game.pid, ResourceStats::default());
+        self.games
+            .lock()
+            .expect("Internal error")
+            .insert(game.pid, game.clone());
+        (self.emitter)(&GameEvent::Started(game));
+    }
+
+    /// Removes the game `pid` from the registry and emits its exit event.
+    pub fn finish(
+        &self,
+        pid: u32,
+        exit_code: Option<i32>,
+        diagnosis: Option<Diagnosis>,
+    ) -> Option<GameExit> {
+        let game = self.games.lock().expect("Internal error").remove(&pid)?;
+        self.logs.finish(pid);
+        let usage = self
+            .usage
+            .lock()
+            .expect("Internal error")
+            .remove(&pid)
+            .and_then(|stats| stats.summary());
+        if let Some(usage) = &usage {
+            info!(
+                "Game process {pid} used {} MiB of memory at peak, {} MiB on average",
+                usage.peak_rss_bytes >> 20,
+                usage.average_rss_bytes >> 20
+            );
+        }
+        let exit = GameExit {
+            pid,
+            instance_id: game.instance_id,
+            exit_code,
+            duration_secs: unix_now().saturating_sub(game.started_at),
+            diagnosis,
+            usage,
+        };
+        (self.emitter)(&GameEvent::Exited(exit.clone()));
+        Some(exit)
+    }
+
+    /// Adds a resource sample to the session of its game and emits it.
+    pub(crate) fn record_usage(&self, sample: ResourceSample) {
+        let heap_warning = {
+            let mut usage = self.usage.lock().expect("Internal error");
+            let Some(stats) = usage.get_mut(&sample.pid) else {
+                return;
+            };
+            stats.push(&sample)
+        };
+        if heap_warning {
+            warn!(
+                "Game process {} uses {} MiB of its {} MiB heap, consider allocating more memory",
+                sample.pid,
+                sample.heap_used_bytes.unwrap_or_default() >> 20,
+                sample.max_heap_bytes.unwrap_or_default() >> 20
+            );
+            (self.emitter)(&GameEvent::HeapWarning(sample.clone()));
+        }
+        (self.emitter)(&GameEvent::Usage(sample));
+    }
+
+    /// Emits the report of a profile launch.
+    pub(crate) fn record_profile(&self, report: ProfileReport) {
+        (self.emitter)(&GameEvent::Profiled(report));
+    }
+
+    /// The logs of the games.
+    pub fn logs(&self) -> &LogHub {
+        &self.logs
+    }
+
+    /// Returns the running games, oldest first.
+    pub fn list(&self) -> Vec<RunningGame> {
+        let mut games: Vec<_> = self
+            .games
+            .lock()
+            .expect("Internal error")
+            .values()
+            .cloned()
+            .collect();
+        games.sort_by_key(|game| (game.started_at, game.pid));
+        games
+    }
+
+    pub fn get(&self, pid: u32) -> Option<RunningGame> {
+        self.games
+            .lock()
+            .expect("Internal error")
+            .get(&pid)
+            .cloned()
+    }
+
+    /// Kills the game `pid`. The exit event is emitted once the process is gone.
+    pub fn kill(&self, pid: u32) -> Result<()> {
+        if self.get(pid).is_none() || !platform::kill_process_tree(pid) {
+            return Err(Error::GameNotRunning(pid));
+        }
+        info!("Killed game process {pid}");
+        Ok(())
+    }
+
+    /// Brings the window of the game `pid` to the foreground.
+    pub fn focus(&self, pid: u32) -> Result<()> {
+        if self.get(pid).is_none() {
+            return Err(Error::GameNotRunning(pid));
+        }
+        if !platform::focus_process_window(pid) {
+            return Err(Error::FocusWindowFailed);
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Incorrect system time")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(pid: u32, started_at: u64) -> RunningGame {
+        RunningGame {
+            pid,
+            instance_id: format!("instance-{pid}"),
+            instance_name: "Test".to_string(),
+            account: "Steve".to_string(),
+            started_at,
+            java_path: PathBuf::from("java"),
+        }
+    }
+
+    #[test]
+    fn tracks_several_games() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let registry = GameRegistry::new({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event.clone())
+        });
+        let now = unix_now();
+        registry.register(game(2, now - 10));
+        registry.register(game(1, now - 60));
+        assert_eq!(
+            registry
+                .list()
+                .iter()
+                .map(|game| game.pid)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        let exit = registry.finish(1, Some(0), None).unwrap();
+        assert_eq!(exit.instance_id, "instance-1");
+        assert!(exit.duration_secs >= 60);
+        assert!(registry.finish(1, Some(0), None).is_none());
+        assert_eq!(registry.list().len(), 1);
+        assert_eq!(events.lock().unwrap().len(), 3);
+    }
+}
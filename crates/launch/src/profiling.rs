@@ -0,0 +1,543 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Java Flight Recorder profiling of the games.
+//!
+//! A profile launch records the game from its start, and a running game can
+//! be recorded from any point with `jcmd JFR.start`. Recordings are stored in
+//! the `profiles` folder of the instance. Once a recording ends, the `jfr`
+//! tool of the runtime running the game summarizes it into a
+//! [`ProfileReport`], saved next to the recording.
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt, fs,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+use folder::DATA_LOCATION;
+use log::{info, warn};
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+};
+use serde_json::Value;
+
+use crate::{
+    error::*,
+    monitor::find_jdk_tool,
+    process::{RunningGame, unix_now},
+};
+
+/// The name of the recordings started by the launcher.
+const RECORDING_NAME: &str = "conic";
+
+/// The events read from a recording to summarize it.
+const SUMMARY_EVENTS: &str = "jdk.GarbageCollection,jdk.ObjectAllocationSample,jdk.ObjectAllocationInNewTLAB,jdk.ObjectAllocationOutsideTLAB,jdk.ExecutionSample,jdk.ThreadCPULoad";
+
+/// How many methods and threads a report lists.
+const REPORT_ENTRIES: usize = 20;
+
+/// The summary of a Java Flight Recorder recording.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProfileReport {
+    pub instance_id: String,
+    pub recording: PathBuf,
+    /// Unix timestamp in seconds.
+    pub created_at: u64,
+    /// `None` when `jfr summary` does not report it.
+    pub duration_secs: Option<f64>,
+    pub gc: GcSummary,
+    /// `None` when the recording has no allocation events or no duration.
+    pub allocation_bytes_per_sec: Option<f64>,
+    /// The methods most often on top of the sampled stacks, hottest first.
+    pub hot_methods: Vec<HotMethod>,
+    /// The threads using the most CPU, busiest first.
+    pub threads: Vec<ThreadCpu>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GcSummary {
+    pub collections: u64,
+    pub total_pause_ms: f64,
+    pub longest_pause_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HotMethod {
+    /// The class and method name, e.g. `net.minecraft.client.Minecraft.run`.
+    pub method: String,
+    pub samples: u64,
+    /// Share of all execution samples.
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThreadCpu {
+    pub name: String,
+    /// Average share of the machine CPU spent in user mode.
+    pub user_percent: f64,
+    /// Average share of the machine CPU spent in kernel mode.
+    pub system_percent: f64,
+}
+
+/// Returns the folder the recordings of `instance_id` are stored in.
+pub fn profiles_directory(instance_id: &str) -> PathBuf {
+    DATA_LOCATION
+        .get_instance_root(instance_id)
+        .join("profiles")
+}
+
+/// Returns the path of a new recording of `instance_id`.
+pub(crate) fn new_recording_path(instance_id: &str) -> Result<PathBuf> {
+    let directory = profiles_directory(instance_id);
+    fs::create_dir_all(&directory)?;
+    Ok(directory.join(format!("{}.jfr", unix_now())))
+}
+
+/// Returns the JVM argument recording the game from its start to `recording`.
+pub(crate) fn flight_recording_argument(recording: &Path) -> String {
+    format!(
+        "-XX:StartFlightRecording=name={RECORDING_NAME},settings=profile,dumponexit=true,filename={}",
+        recording.to_string_lossy()
+    )
+}
+
+/// Starts recording the running `game` with `jcmd`.
+pub fn start_recording(game: &RunningGame) -> Result<()> {
+    let output = run_jdk_tool(
+        &game.java_path,
+        "jcmd",
+        &[
+            game.pid.to_string(),
+            "JFR.start".to_string(),
+            format!("name={RECORDING_NAME}"),
+            "settings=profile".to_string(),
+        ],
+    )?;
+    if !output.contains("Started recording") {
+        return Err(Error::ProfilingFailed(output.trim().to_string()));
+    }
+    info!("Started profiling game process {}", game.pid);
+    Ok(())
+}
+
+/// Stops the recording of the running `game` and summarizes it.
+pub fn stop_recording(game: &RunningGame) -> Result<ProfileReport> {
+    let recording = new_recording_path(&game.instance_id)?;
+    let output = run_jdk_tool(
+        &game.java_path,
+        "jcmd",
+        &[
+            game.pid.to_string(),
+            "JFR.stop".to_string(),
+            format!("name={RECORDING_NAME}"),
+            format!("filename={}", recording.to_string_lossy()),
+        ],
+    )?;
+    if !recording.is_file() {
+        return Err(Error::ProfilingFailed(output.trim().to_string()));
+    }
+    info!(
+        "Stopped profiling game process {}, recorded to {}",
+        game.pid,
+        recording.display()
+    );
+    summarize_recording(&game.java_path, &game.instance_id, &recording)
+}
+
+/// Summarizes `recording` with the `jfr` tool of the runtime at `java_path`,
+/// and saves the report next to it.
+pub(crate) fn summarize_recording(
+    java_path: &Path,
+    instance_id: &str,
+    recording: &Path,
+) -> Result<ProfileReport> {
+    let recording_arg = recording.to_string_lossy().to_string();
+    let summary = run_jdk_tool(
+        java_path,
+        "jfr",
+        &["summary".to_string(), recording_arg.clone()],
+    )?;
+    // Every sample of the recording is printed, so the events are read as
+    // they are printed instead of buffering the whole output.
+    let mut print = jdk_tool_command(
+        java_path,
+        "jfr",
+        &[
+            "print".to_string(),
+            "--json".to_string(),
+            "--stack-depth".to_string(),
+            "1".to_string(),
+            "--events".to_string(),
+            SUMMARY_EVENTS.to_string(),
+            recording_arg,
+        ],
+    )?
+    .spawn()?;
+    let events = BufReader::new(print.stdout.take().expect("Internal error"));
+    let report = summarize_events(events, parse_summary_duration(&summary));
+    let output = print.wait_with_output()?;
+    if !output.status.success() {
+        return Err(tool_failed("jfr", &output));
+    }
+    let mut report = report?;
+    report.instance_id = instance_id.to_string();
+    report.recording = recording.to_path_buf();
+    fs::write(
+        recording.with_extension("json"),
+        serde_json::to_vec_pretty(&report).map_err(std::io::Error::other)?,
+    )?;
+    Ok(report)
+}
+
+/// Returns the saved reports of `instance_id`, newest first.
+pub fn list_reports(instance_id: &str) -> Vec<ProfileReport> {
+    let Ok(entries) = fs::read_dir(profiles_directory(instance_id)) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<ProfileReport> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).ok(),
+            Err(error) => {
+                warn!(
+                    "Could not read the profile report {}: {error}",
+                    path.display()
+                );
+                None
+            }
+        })
+        .collect();
+    reports.sort_by_key(|report| Reverse(report.created_at));
+    reports
+}
+
+/// Runs the tool `name` of the JDK of `java_path`, returning its output.
+fn run_jdk_tool(java_path: &Path, name: &str, args: &[String]) -> Result<String> {
+    let output = jdk_tool_command(java_path, name, args)?.output()?;
+    if !output.status.success() {
+        return Err(tool_failed(name, &output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Returns the command running the tool `name` of the JDK of `java_path`, with
+/// its output piped.
+fn jdk_tool_command(java_path: &Path, name: &str, args: &[String]) -> Result<Command> {
+    let tool =
+        find_jdk_tool(java_path, name).ok_or_else(|| Error::JdkToolMissing(name.to_string()))?;
+    let mut command = Command::new(tool);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    Ok(command)
+}
+
+/// Returns the error of the tool `name` that exited with `output`.
+fn tool_failed(name: &str, output: &Output) -> Error {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Error::ProfilingFailed(format!(
+        "{name} exited with {}: {}",
+        output.status,
+        if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        }
+    ))
+}
+
+/// Reads the `Duration: 61 s` line of `jfr summary`.
+fn parse_summary_duration(summary: &str) -> Option<f64> {
+    summary.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("Duration:")?.trim();
+        value.strip_suffix('s')?.trim().parse().ok()
+    })
+}
+
+/// Parses an ISO-8601 duration of `jfr print --json`, e.g. `PT0.0123S`, in
+/// milliseconds.
+fn parse_iso_duration_ms(duration: &str) -> Option<f64> {
+    let mut rest = duration.strip_prefix("PT")?;
+    let mut seconds = 0.0;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let value: f64 = rest[..end].parse().ok()?;
+        seconds += value
+            * match &rest[end..=end] {
+                "H" => 3600.0,
+                "M" => 60.0,
+                "S" => 1.0,
+                _ => return None,
+            };
+        rest = &rest[end + 1..];
+    }
+    Some(seconds * 1000.0)
+}
+
+#[derive(Deserialize)]
+struct JfrEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    values: Value,
+}
+
+/// The totals of the events of a recording.
+#[derive(Default)]
+struct EventTotals {
+    gc: GcSummary,
+    sampled_bytes: f64,
+    tlab_bytes: f64,
+    methods: HashMap<String, u64>,
+    total_samples: u64,
+    /// Sum of the user and system loads and number of events, by thread.
+    threads: HashMap<String, (f64, f64, u64)>,
+}
+
+impl EventTotals {
+    fn add(&mut self, event: JfrEvent) {
+        let values = &event.values;
+        match event.event_type.as_str() {
+            "jdk.GarbageCollection" => {
+                let pause = |key: &str| {
+                    values[key]
+                        .as_str()
+                        .and_then(parse_iso_duration_ms)
+                        .unwrap_or_default()
+                };
+                self.gc.collections += 1;
+                self.gc.total_pause_ms += pause("sumOfPauses");
+                self.gc.longest_pause_ms = self.gc.longest_pause_ms.max(pause("longestPause"));
+            }
+            "jdk.ObjectAllocationSample" => {
+                self.sampled_bytes += values["weight"].as_f64().unwrap_or_default();
+            }
+            "jdk.ObjectAllocationInNewTLAB" => {
+                self.tlab_bytes += values["tlabSize"].as_f64().unwrap_or_default();
+            }
+            "jdk.ObjectAllocationOutsideTLAB" => {
+                self.tlab_bytes += values["allocationSize"].as_f64().unwrap_or_default();
+            }
+            "jdk.ExecutionSample" => {
+                self.total_samples += 1;
+                let method = &values["stackTrace"]["frames"][0]["method"];
+                if let (Some(class), Some(name)) =
+                    (method["type"]["name"].as_str(), method["name"].as_str())
+                {
+                    let method = format!("{}.{name}", class.replace('/', "."));
+                    *self.methods.entry(method).or_default() += 1;
+                }
+            }
+            "jdk.ThreadCPULoad" => {
+                let thread = &values["eventThread"];
+                let Some(name) = thread["javaName"]
+                    .as_str()
+                    .or_else(|| thread["osName"].as_str())
+                else {
+                    return;
+                };
+                let load = self.threads.entry(name.to_string()).or_default();
+                load.0 += values["user"].as_f64().unwrap_or_default();
+                load.1 += values["system"].as_f64().unwrap_or_default();
+                load.2 += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads the events of `{"recording": {"events": [...]}}` one at a time,
+/// adding each one to the totals and dropping it.
+struct EventsSeed<'a> {
+    totals: &'a mut EventTotals,
+    /// The keys of the events array from the value being read.
+    path: &'static [&'static str],
+}
+
+impl<'de> DeserializeSeed<'de> for EventsSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        if self.path.is_empty() {
+            deserializer.deserialize_seq(self)
+        } else {
+            deserializer.deserialize_map(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for EventsSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.path.first() {
+            Some(key) => write!(formatter, "an object with a `{key}` field"),
+            None => formatter.write_str("an array of events"),
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        let Some((&key, path)) = self.path.split_first() else {
+            return Err(de::Error::invalid_type(de::Unexpected::Map, &self));
+        };
+        let mut found = false;
+        while let Some(name) = map.next_key::<String>()? {
+            if name == key && !found {
+                map.next_value_seed(EventsSeed {
+                    totals: &mut *self.totals,
+                    path,
+                })?;
+                found = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        if !found {
+            return Err(de::Error::missing_field(key));
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(event) = seq.next_element::<JfrEvent>()? {
+            self.totals.add(event);
+        }
+        Ok(())
+    }
+}
+
+/// Summarizes the events printed by `jfr print --json` into a report without
+/// instance or recording.
+fn summarize_events(json: impl Read, duration_secs: Option<f64>) -> Result<ProfileReport> {
+    let mut totals = EventTotals::default();
+    let mut deserializer = serde_json::Deserializer::from_reader(json);
+    EventsSeed {
+        totals: &mut totals,
+        path: &["recording", "events"],
+    }
+    .deserialize(&mut deserializer)
+    .and_then(|()| deserializer.end())
+    .map_err(|error| Error::ProfilingFailed(format!("Unreadable recording: {error}")))?;
+    let EventTotals {
+        gc,
+        sampled_bytes,
+        tlab_bytes,
+        methods,
+        total_samples,
+        threads,
+    } = totals;
+
+    // Sampled allocations estimate every allocation, and replace the TLAB
+    // events in the profile settings of Java 16 and later.
+    let allocated_bytes = if sampled_bytes > 0.0 {
+        sampled_bytes
+    } else {
+        tlab_bytes
+    };
+    let allocation_bytes_per_sec = duration_secs
+        .filter(|duration| *duration > 0.0 && allocated_bytes > 0.0)
+        .map(|duration| allocated_bytes / duration);
+
+    let mut hot_methods: Vec<HotMethod> = methods
+        .into_iter()
+        .map(|(method, samples)| HotMethod {
+            method,
+            samples,
+            percent: samples as f64 * 100.0 / total_samples as f64,
+        })
+        .collect();
+    hot_methods.sort_by(|a, b| b.samples.cmp(&a.samples).then(a.method.cmp(&b.method)));
+    hot_methods.truncate(REPORT_ENTRIES);
+
+    let mut threads: Vec<ThreadCpu> = threads
+        .into_iter()
+        .map(|(name, (user, system, count))| ThreadCpu {
+            name,
+            user_percent: user * 100.0 / count as f64,
+            system_percent: system * 100.0 / count as f64,
+        })
+        .collect();
+    threads.sort_by(|a, b| {
+        (b.user_percent + b.system_percent)
+            .total_cmp(&(a.user_percent + a.system_percent))
+            .then(a.name.cmp(&b.name))
+    });
+    threads.truncate(REPORT_ENTRIES);
+
+    Ok(ProfileReport {
+        instance_id: String::new(),
+        recording: PathBuf::new(),
+        created_at: unix_now(),
+        duration_secs,
+        gc,
+        allocation_bytes_per_sec,
+        hot_methods,
+        threads,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_iso_duration_ms("PT0.5S"), Some(500.0));
+        assert_eq!(parse_iso_duration_ms("PT1M2S"), Some(62_000.0));
+        assert_eq!(parse_iso_duration_ms("PT0S"), Some(0.0));
+        assert_eq!(parse_iso_duration_ms("0.5"), None);
+        let summary = " Version: 2.1\n Chunks: 1\n Size: 4096 bytes\n Duration: 61 s\n";
+        assert_eq!(parse_summary_duration(summary), Some(61.0));
+    }
+
+    #[test]
+    fn summarizes_events() {
+        let json = r#"{"recording": {"events": [
+            {"type": "jdk.GarbageCollection", "values": {"sumOfPauses": "PT0.02S", "longestPause": "PT0.015S"}},
+            {"type": "jdk.GarbageCollection", "values": {"sumOfPauses": "PT0.01S", "longestPause": "PT0.01S"}},
+            {"type": "jdk.ObjectAllocationSample", "values": {"weight": 1000}},
+            {"type": "jdk.ObjectAllocationInNewTLAB", "values": {"tlabSize": 99999}},
+            {"type": "jdk.ExecutionSample", "values": {"stackTrace": {"frames": [{"method": {"type": {"name": "net/minecraft/client/Minecraft"}, "name": "run"}}]}}},
+            {"type": "jdk.ExecutionSample", "values": {"stackTrace": {"frames": [{"method": {"type": {"name": "net/minecraft/client/Minecraft"}, "name": "run"}}]}}},
+            {"type": "jdk.ExecutionSample", "values": {"stackTrace": {"frames": [{"method": {"type": {"name": "java.lang.Thread"}, "name": "sleep"}}]}}},
+            {"type": "jdk.ThreadCPULoad", "values": {"eventThread": {"javaName": "Render thread"}, "user": 0.5, "system": 0.1}},
+            {"type": "jdk.ThreadCPULoad", "values": {"eventThread": {"javaName": "Render thread"}, "user": 0.3, "system": 0.1}},
+            {"type": "jdk.ThreadCPULoad", "values": {"eventThread": {"javaName": "Server thread"}, "user": 0.1, "system": 0.0}}
+        ]}}"#;
+        let report = summarize_events(json.as_bytes(), Some(10.0)).unwrap();
+        assert_eq!(report.gc.collections, 2);
+        assert!((report.gc.total_pause_ms - 30.0).abs() < 1e-9);
+        assert!((report.gc.longest_pause_ms - 15.0).abs() < 1e-9);
+        assert_eq!(report.allocation_bytes_per_sec, Some(100.0));
+        assert_eq!(
+            report.hot_methods[0].method,
+            "net.minecraft.client.Minecraft.run"
+        );
+        assert_eq!(report.hot_methods[0].samples, 2);
+        assert_eq!(report.hot_methods[1].method, "java.lang.Thread.sleep");
+        assert_eq!(report.threads[0].name, "Render thread");
+        assert!((report.threads[0].user_percent - 40.0).abs() < 1e-9);
+        assert_eq!(report.threads.len(), 2);
+        assert!(summarize_events("{}".as_bytes(), None).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Quick Play, which starts the game straight into a world, server or realm.
+//!
+//! Versions since 1.20 take the target through the `quickPlay*` arguments,
+//! which the version JSON gates behind the `is_quick_play_*` features. Older
+//! versions can only join a server, through `--server` and `--port`.
+
+use std::path::PathBuf;
+
+use config::launch::Server;
+use folder::DATA_LOCATION;
+use instance::Instance;
+use serde::{Deserialize, Serialize};
+use version::ResolvedVersion;
+
+/// Where the game goes right after it starts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum QuickPlay {
+    /// A world, by the name of its folder in `saves`.
+    Singleplayer { world: String },
+    /// A server, by its address in `host[:port]` form.
+    Multiplayer { address: String },
+    /// A realm, by its id.
+    Realms { realm_id: String },
+}
+
+impl QuickPlay {
+    /// The Quick Play target of the server configured for the instance.
+    pub(crate) fn configured(instance: &Instance) -> Option<Self> {
+        instance
+            .config
+            .launch_config
+            .server
+            .as_ref()
+            .map(Self::from_server)
+    }
+
+    pub fn from_server(server: &Server) -> Self {
+        let address = match server.port {
+            Some(port) if server.ip.contains(':') => format!("[{}]:{port}", server.ip),
+            Some(port) => format!("{}:{port}", server.ip),
+            None => server.ip.clone(),
+        };
+        Self::Multiplayer { address }
+    }
+
+    /// The version JSON features enabling the arguments of this target.
+    pub fn features(&self) -> Vec<String> {
+        let target = match self {
+            Self::Singleplayer { .. } => "is_quick_play_singleplayer",
+            Self::Multiplayer { .. } => "is_quick_play_multiplayer",
+            Self::Realms { .. } => "is_quick_play_realms",
+        };
+        vec!["has_quick_plays_support".to_string(), target.to_string()]
+    }
+
+    /// The placeholder of the target in the version JSON, and its value.
+    pub(crate) fn placeholder(&self) -> (&'static str, &str) {
+        match self {
+            Self::Singleplayer { world } => ("quickPlaySingleplayer", world),
+            Self::Multiplayer { address } => ("quickPlayMultiplayer", address),
+            Self::Realms { realm_id } => ("quickPlayRealms", realm_id),
+        }
+    }
+
+    /// Whether the arguments of `version`, resolved with [`Self::features`],
+    /// pass this target to the game.
+    pub fn is_supported_by(&self, version: &ResolvedVersion) -> bool {
+        let placeholder = format!("${{{}}}", self.placeholder().0);
+        version
+            .game_arguments
+            .iter()
+            .any(|argument| argument.contains(&placeholder))
+    }
+
+    /// The arguments passing this target to versions without Quick Play.
+    ///
+    /// Only servers can be joined this way, `None` is returned for worlds and
+    /// realms.
+    pub(crate) fn legacy_arguments(&self) -> Option<Vec<String>> {
+        let Self::Multiplayer { address } = self else {
+            return None;
+        };
+        let (host, port) = split_address(address);
+        let mut arguments = vec!["--server".to_string(), host.to_string()];
+        if let Some(port) = port {
+            arguments.extend(["--port".to_string(), port.to_string()]);
+        }
+        Some(arguments)
+    }
+}
+
+/// The file the game writes the Quick Play log of the instance to.
+pub(crate) fn get_quick_play_log_path(instance_id: &str) -> PathBuf {
+    DATA_LOCATION
+        .get_instance_root(instance_id)
+        .join("quickPlay")
+        .join("log.json")
+}
+
+/// Splits a `host[:port]` address, where an IPv6 host is in brackets.
+fn split_address(address: &str) -> (&str, Option<&str>) {
+    if let Some(rest) = address.strip_prefix('[')
+        && let Some((host, port)) = rest.split_once(']')
+    {
+        return (host, port.strip_prefix(':'));
+    }
+    match address.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => (host, Some(port)),
+        _ => (address, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_server_arguments() {
+        let server = Server {
+            ip: "::1".to_string(),
+            port: Some(25566),
+        };
+        assert_eq!(
+            QuickPlay::from_server(&server).legacy_arguments().unwrap(),
+            vec!["--server", "::1", "--port", "25566"]
+        );
+        let quick_play = QuickPlay::Multiplayer {
+            address: "mc.example.com".to_string(),
+        };
+        assert_eq!(
+            quick_play.legacy_arguments().unwrap(),
+            vec!["--server", "mc.example.com"]
+        );
+        let quick_play = QuickPlay::Singleplayer {
+            world: "New World".to_string(),
+        };
+        assert!(quick_play.legacy_arguments().is_none());
+    }
+}
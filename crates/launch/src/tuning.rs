@@ -0,0 +1,295 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! JVM tuning presets.
+//!
+//! A preset replaces the flags of the selected garbage collector. Every flag
+//! states the Java versions and vendors supporting it, since the JVM refuses to
+//! start on an unrecognized `-XX` option. Unsupported flags are dropped with a
+//! warning before launch.
+
+use std::sync::LazyLock;
+
+use config::{
+    Config,
+    launch::{JvmFlag, JvmPreset},
+};
+use instance::Instance;
+use java_runtime::JavaVendor;
+use log::warn;
+
+use crate::error::*;
+
+static BUILTIN_PRESETS: LazyLock<Vec<JvmPreset>> = LazyLock::new(|| {
+    vec![
+        preset(
+            "aikar",
+            "Aikar's flags",
+            "G1 tuned for short pauses, from the Paper project.",
+            &[
+                ("-XX:+UseG1GC", None, None),
+                ("-XX:+ParallelRefProcEnabled", None, None),
+                ("-XX:MaxGCPauseMillis=200", None, None),
+                ("-XX:+UnlockExperimentalVMOptions", None, None),
+                ("-XX:+DisableExplicitGC", None, None),
+                ("-XX:+AlwaysPreTouch", None, None),
+                ("-XX:G1NewSizePercent=30", None, None),
+                ("-XX:G1MaxNewSizePercent=40", None, None),
+                ("-XX:G1HeapRegionSize=8M", None, None),
+                ("-XX:G1ReservePercent=20", None, None),
+                ("-XX:G1HeapWastePercent=5", None, None),
+                ("-XX:G1MixedGCCountTarget=4", None, None),
+                ("-XX:InitiatingHeapOccupancyPercent=15", None, None),
+                ("-XX:G1MixedGCLiveThresholdPercent=90", None, None),
+                ("-XX:SurvivorRatio=32", None, None),
+                ("-XX:+PerfDisableSharedMem", None, None),
+                ("-XX:MaxTenuringThreshold=1", None, None),
+            ],
+        ),
+        preset(
+            "zgc-generational",
+            "Generational ZGC",
+            "Sub-millisecond pauses for large heaps, generational since Java 21.",
+            &[
+                ("-XX:+UseZGC", Some(15), None),
+                // Generational mode is the only mode since Java 24.
+                ("-XX:+ZGenerational", Some(21), Some(23)),
+                ("-XX:+AlwaysPreTouch", None, None),
+            ],
+        ),
+        excluding_vendor(
+            preset(
+                "shenandoah",
+                "Shenandoah",
+                "Concurrent compaction with low pauses, not built by Oracle.",
+                &[
+                    // Shenandoah is experimental before Java 15.
+                    ("-XX:+UnlockExperimentalVMOptions", Some(12), Some(14)),
+                    ("-XX:+UseShenandoahGC", Some(12), None),
+                    ("-XX:+AlwaysPreTouch", None, None),
+                    ("-XX:+DisableExplicitGC", None, None),
+                    ("-XX:+ParallelRefProcEnabled", None, None),
+                ],
+            ),
+            "oracle",
+        ),
+        preset(
+            "low-memory",
+            "Low memory",
+            "A small footprint for computers with little memory.",
+            &[
+                ("-XX:+UseSerialGC", None, None),
+                // The serial collector supports deduplication since Java 18.
+                ("-XX:+UseStringDeduplication", Some(18), None),
+                ("-XX:MinHeapFreeRatio=10", None, None),
+                ("-XX:MaxHeapFreeRatio=30", None, None),
+                ("-XX:ReservedCodeCacheSize=48M", None, None),
+            ],
+        ),
+    ]
+});
+
+fn flag(value: &str, min_java: Option<u32>, max_java: Option<u32>) -> JvmFlag {
+    JvmFlag {
+        value: value.to_string(),
+        min_java,
+        max_java,
+        excluded_vendors: Vec::new(),
+    }
+}
+
+fn preset(
+    id: &str,
+    name: &str,
+    description: &str,
+    flags: &[(&str, Option<u32>, Option<u32>)],
+) -> JvmPreset {
+    JvmPreset {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        flags: flags
+            .iter()
+            .map(|(value, min_java, max_java)| flag(value, *min_java, *max_java))
+            .collect(),
+    }
+}
+
+/// Marks every flag of `preset` as unsupported by `vendor`.
+fn excluding_vendor(mut preset: JvmPreset, vendor: &str) -> JvmPreset {
+    for flag in &mut preset.flags {
+        flag.excluded_vendors.push(vendor.to_string());
+    }
+    preset
+}
+
+/// Returns the built-in presets followed by the valid presets of the user. A
+/// user preset with the id of a built-in preset replaces it.
+pub fn list_presets(config: &Config) -> Vec<JvmPreset> {
+    let user_presets: Vec<&JvmPreset> = config
+        .launch
+        .jvm_presets
+        .iter()
+        .filter(|preset| match preset.invalid_reason() {
+            Some(reason) => {
+                warn!("Ignoring the JVM preset {}: {reason}", preset.name);
+                false
+            }
+            None => true,
+        })
+        .collect();
+    BUILTIN_PRESETS
+        .iter()
+        .filter(|builtin| !user_presets.iter().any(|preset| preset.id == builtin.id))
+        .chain(user_presets)
+        .cloned()
+        .collect()
+}
+
+/// Returns the preset selected for the instance, which overrides the global
+/// selection. An empty id selects no preset.
+pub fn selected_preset(config: &Config, instance: &Instance) -> Option<JvmPreset> {
+    let id = instance
+        .config
+        .launch_config
+        .jvm_preset
+        .as_ref()
+        .or(config.launch.jvm_preset.as_ref())?;
+    if id.is_empty() {
+        return None;
+    }
+    let preset = list_presets(config)
+        .into_iter()
+        .find(|preset| &preset.id == id);
+    if preset.is_none() {
+        warn!("JVM preset {id} does not exist, using the garbage collector setting");
+    }
+    preset
+}
+
+/// Parses a preset shared as JSON, and checks that it can be saved.
+pub fn import_preset(json: &str) -> Result<JvmPreset> {
+    let preset: JvmPreset =
+        serde_json::from_str(json).map_err(|error| Error::InvalidJvmPreset(error.to_string()))?;
+    validate_preset(&preset)?;
+    Ok(preset)
+}
+
+/// Checks that the preset has an id and that every flag is a single JVM
+/// option.
+pub fn validate_preset(preset: &JvmPreset) -> Result<()> {
+    match preset.invalid_reason() {
+        Some(reason) => Err(Error::InvalidJvmPreset(reason)),
+        None => Ok(()),
+    }
+}
+
+/// Returns the flags of `preset` supported by a Java runtime, logging a warning
+/// for every dropped flag.
+///
+/// Requirements that cannot be checked, because the version or the vendor of
+/// the runtime is unknown, are assumed to be met.
+pub fn supported_flags(
+    preset: &JvmPreset,
+    major_version: Option<u32>,
+    vendor: JavaVendor,
+) -> Vec<String> {
+    let vendor_id = serde_json::to_value(vendor)
+        .ok()
+        .and_then(|vendor| vendor.as_str().map(str::to_string));
+    preset
+        .flags
+        .iter()
+        .filter(|flag| {
+            let unsupported_reason = match (major_version, flag.min_java, flag.max_java) {
+                (Some(version), Some(min), _) if version < min => {
+                    Some(format!("Java {version} is older than Java {min}"))
+                }
+                (Some(version), _, Some(max)) if version > max => {
+                    Some(format!("Java {version} is newer than Java {max}"))
+                }
+                _ => None,
+            }
+            .or_else(|| {
+                vendor_id
+                    .as_ref()
+                    .filter(|vendor_id| flag.excluded_vendors.contains(vendor_id))
+                    .map(|_| format!("{} builds lack it", vendor.display_name()))
+            });
+            if let Some(reason) = &unsupported_reason {
+                warn!(
+                    "Dropping {} of the JVM preset {}: {reason}",
+                    flag.value, preset.id
+                );
+            }
+            unsupported_reason.is_none()
+        })
+        .map(|flag| flag.value.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin(id: &str) -> &'static JvmPreset {
+        BUILTIN_PRESETS
+            .iter()
+            .find(|preset| preset.id == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn drops_unsupported_flags() {
+        let zgc = builtin("zgc-generational");
+        assert_eq!(
+            supported_flags(zgc, Some(17), JavaVendor::EclipseAdoptium),
+            vec!["-XX:+UseZGC", "-XX:+AlwaysPreTouch"]
+        );
+        assert_eq!(supported_flags(zgc, Some(21), JavaVendor::Oracle).len(), 3);
+        assert_eq!(supported_flags(zgc, Some(24), JavaVendor::Oracle).len(), 2);
+        assert_eq!(supported_flags(zgc, None, JavaVendor::Unknown).len(), 3);
+
+        let shenandoah = builtin("shenandoah");
+        let oracle = supported_flags(shenandoah, Some(21), JavaVendor::Oracle);
+        assert!(!oracle.contains(&"-XX:+UseShenandoahGC".to_string()));
+        let temurin = supported_flags(shenandoah, Some(21), JavaVendor::EclipseAdoptium);
+        assert!(temurin.contains(&"-XX:+UseShenandoahGC".to_string()));
+        assert!(!temurin.contains(&"-XX:+UnlockExperimentalVMOptions".to_string()));
+        let experimental = supported_flags(shenandoah, Some(13), JavaVendor::EclipseAdoptium);
+        assert_eq!(
+            experimental[..2],
+            ["-XX:+UnlockExperimentalVMOptions", "-XX:+UseShenandoahGC"]
+        );
+    }
+
+    #[test]
+    fn validates_imported_presets() {
+        let preset = import_preset(
+            r#"{ "id": "mine", "name": "Mine", "flags": [{ "value": "-XX:+UseZGC" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(preset.flags[0].min_java, None);
+        assert!(
+            import_preset(
+                r#"{ "id": "mine", "name": "Mine", "flags": [{ "value": "-Xmx2G -Xms2G" }] }"#
+            )
+            .is_err()
+        );
+        assert!(import_preset(r#"{ "id": " ", "name": "Blank", "flags": [] }"#).is_err());
+    }
+
+    #[test]
+    fn ignores_invalid_user_presets() {
+        let mut config = Config::default();
+        config.launch.jvm_presets = vec![
+            preset("mine", "Mine", "", &[("-XX:+UseZGC", None, None)]),
+            preset("heap", "Heap", "", &[("-Xmx2G -Xms2G", None, None)]),
+        ];
+        assert!(config.launch.validate_presets().is_err());
+        let presets = list_presets(&config);
+        assert!(presets.iter().any(|preset| preset.id == "mine"));
+        assert!(!presets.iter().any(|preset| preset.id == "heap"));
+    }
+}
@@ -16,3 +16,22 @@ export type PlatformInfo = {
 export async function getPlatformInfo(): Promise<PlatformInfo> {
     return await invoke("plugin:platform|cmd_get_platform_info")
 }
+
+export type Gpu = {
+    /** The DRM card name, e.g. "card1" */
+    card: string
+    /** The PCI slot, e.g. "0000:01:00.0" */
+    pci_address: string | null
+    vendor: "Nvidia" | "Amd" | "Intel" | "Unknown"
+    vendor_id: number
+    device_id: number
+    driver: string | null
+    /** Whether the GPU is the default renderer */
+    boot_vga: boolean
+    discrete: boolean
+}
+
+/** Lists the GPUs of the system, always empty outside of Linux */
+export async function listGpus(): Promise<Gpu[]> {
+    return await invoke("plugin:platform|cmd_list_gpus")
+}
@@ -0,0 +1,162 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+    Unknown,
+}
+
+impl GpuVendor {
+    /// Maps a PCI vendor id.
+    pub fn from_pci_id(vendor_id: u16) -> Self {
+        match vendor_id {
+            0x10de => Self::Nvidia,
+            0x1002 => Self::Amd,
+            0x8086 => Self::Intel,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// A GPU found in `/sys/class/drm`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct Gpu {
+    /// The DRM card name, e.g. `card1`.
+    pub card: String,
+    /// The PCI slot, e.g. `0000:01:00.0`.
+    pub pci_address: Option<String>,
+    pub vendor: GpuVendor,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// The kernel driver, e.g. `nvidia` or `amdgpu`.
+    pub driver: Option<String>,
+    /// Whether the firmware initialized the GPU, which makes it the default
+    /// renderer.
+    pub boot_vga: bool,
+    pub discrete: bool,
+}
+
+/// Lists the GPUs of the system, sorted by card name. Always empty outside of
+/// Linux.
+pub fn list_gpus() -> Vec<Gpu> {
+    if cfg!(target_os = "linux") {
+        list_gpus_in(Path::new("/sys/class/drm"))
+    } else {
+        Vec::new()
+    }
+}
+
+/// Lists the GPUs of a sysfs `class/drm` directory.
+///
+/// Connectors such as `card0-eDP-1` and render nodes are skipped, as are
+/// cards without a PCI vendor id.
+pub fn list_gpus_in(drm: &Path) -> Vec<Gpu> {
+    let Ok(entries) = fs::read_dir(drm) else {
+        return Vec::new();
+    };
+    let mut gpus: Vec<Gpu> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let card = entry.file_name().to_string_lossy().to_string();
+            let index = card.strip_prefix("card")?;
+            if index.is_empty() || !index.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            read_gpu(card, &entry.path().join("device"))
+        })
+        .collect();
+    gpus.sort_by_key(|gpu| gpu.card[4..].parse::<u32>().unwrap_or(u32::MAX));
+    gpus
+}
+
+fn read_gpu(card: String, device: &Path) -> Option<Gpu> {
+    let read = |name: &str| {
+        fs::read_to_string(device.join(name))
+            .ok()
+            .map(|content| content.trim().to_string())
+    };
+    let parse_id = |name: &str| u16::from_str_radix(read(name)?.trim_start_matches("0x"), 16).ok();
+    let vendor_id = parse_id("vendor")?;
+    let device_id = parse_id("device").unwrap_or_default();
+    let uevent = read("uevent").unwrap_or_default();
+    let uevent_value = |key: &str| {
+        uevent
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::to_string)
+    };
+    let vendor = GpuVendor::from_pci_id(vendor_id);
+    let boot_vga = read("boot_vga").as_deref() == Some("1");
+    Some(Gpu {
+        card,
+        pci_address: uevent_value("PCI_SLOT_NAME"),
+        vendor,
+        vendor_id,
+        device_id,
+        driver: uevent_value("DRIVER"),
+        boot_vga,
+        discrete: is_discrete(vendor, device_id, boot_vga),
+    })
+}
+
+/// Guesses whether a GPU is discrete.
+///
+/// NVIDIA only makes discrete GPUs, and Intel only integrated ones except
+/// for Arc. AMD makes both, and on hybrid systems the firmware initializes
+/// the integrated GPU.
+fn is_discrete(vendor: GpuVendor, device_id: u16, boot_vga: bool) -> bool {
+    match vendor {
+        GpuVendor::Nvidia => true,
+        GpuVendor::Intel => matches!(device_id, 0x5690..=0x56c1 | 0xe202..=0xe212),
+        GpuVendor::Amd | GpuVendor::Unknown => !boot_vga,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("fixtures")
+            .join("drm")
+            .join(name)
+    }
+
+    #[test]
+    fn lists_hybrid_intel_nvidia() {
+        let gpus = list_gpus_in(&fixture("intel-nvidia"));
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].card, "card0");
+        assert_eq!(gpus[0].vendor, GpuVendor::Intel);
+        assert_eq!(gpus[0].driver.as_deref(), Some("i915"));
+        assert!(gpus[0].boot_vga && !gpus[0].discrete);
+        assert_eq!(gpus[1].vendor, GpuVendor::Nvidia);
+        assert_eq!(gpus[1].device_id, 0x25a2);
+        assert_eq!(gpus[1].pci_address.as_deref(), Some("0000:01:00.0"));
+        assert!(!gpus[1].boot_vga && gpus[1].discrete);
+    }
+
+    #[test]
+    fn lists_hybrid_amd() {
+        let gpus = list_gpus_in(&fixture("amd-amd"));
+        assert_eq!(
+            gpus.iter()
+                .map(|gpu| (gpu.card.as_str(), gpu.discrete))
+                .collect::<Vec<_>>(),
+            vec![("card0", false), ("card1", true)]
+        );
+        assert!(list_gpus_in(&fixture("missing")).is_empty());
+    }
+}
@@ -12,15 +12,24 @@ use tauri::{
     plugin::{Builder, TauriPlugin},
 };
 
+mod executable;
+mod gpu;
 mod memory;
+mod process;
 
+pub use executable::find_executable;
+pub use gpu::{Gpu, GpuVendor, list_gpus, list_gpus_in};
 pub use memory::get_available_memory_bytes;
+pub use process::{ProcessSampler, ProcessUsage, focus_process_window, kill_process_tree};
 
 pub static PLATFORM_INFO: Lazy<PlatformInfo> = Lazy::new(PlatformInfo::new);
 
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
     Builder::new("platform")
-        .invoke_handler(tauri::generate_handler![cmd_get_platform_info,])
+        .invoke_handler(tauri::generate_handler![
+            cmd_get_platform_info,
+            cmd_list_gpus,
+        ])
         .build()
 }
 
@@ -29,6 +38,11 @@ fn cmd_get_platform_info() -> PlatformInfo {
     PLATFORM_INFO.clone()
 }
 
+#[command]
+fn cmd_list_gpus() -> Vec<Gpu> {
+    list_gpus()
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub enum OsArch {
     X64,
@@ -0,0 +1,200 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::process::{Command, Stdio};
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+/// Kills the process `pid` together with its child processes.
+///
+/// Returns `false` when no process with this PID exists.
+pub fn kill_process_tree(pid: u32) -> bool {
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    let root = Pid::from_u32(pid);
+    if system.process(root).is_none() {
+        return false;
+    }
+    let to_kill = process_tree(&system, root);
+    // Kill children first so they are not reparented while the tree is torn down.
+    for pid in to_kill.iter().rev() {
+        if let Some(process) = system.process(*pid) {
+            process.kill();
+        }
+    }
+    true
+}
+
+/// Returns `root` followed by its descendants, parents before children.
+///
+/// Threads are skipped, since on Linux sysinfo lists them as processes whose
+/// parent is the process they belong to.
+fn process_tree(system: &System, root: Pid) -> Vec<Pid> {
+    let mut tree = vec![root];
+    let mut index = 0;
+    while index < tree.len() {
+        let parent = tree[index];
+        tree.extend(
+            system
+                .processes()
+                .iter()
+                .filter(|(_, process)| {
+                    process.parent() == Some(parent) && process.thread_kind().is_none()
+                })
+                .map(|(pid, _)| *pid),
+        );
+        index += 1;
+    }
+    tree
+}
+
+/// Resource usage of a process together with its child processes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessUsage {
+    /// Resident memory of the whole tree, in bytes.
+    pub rss_bytes: u64,
+    /// CPU usage of the whole tree, where 100 is one fully used core.
+    pub cpu_percent: f32,
+    /// Threads of the root process, `None` where the platform does not
+    /// report them.
+    pub threads: Option<usize>,
+    /// Number of descendants of the root process.
+    pub children: usize,
+}
+
+/// Samples the resource usage of a process tree.
+///
+/// The CPU usage is measured between two samples, so the first sample always
+/// reports zero.
+pub struct ProcessSampler {
+    system: System,
+    root: Pid,
+}
+
+impl ProcessSampler {
+    pub fn new(pid: u32) -> Self {
+        Self {
+            system: System::new(),
+            root: Pid::from_u32(pid),
+        }
+    }
+
+    /// Returns the current usage, or `None` once the root process is gone.
+    pub fn sample(&mut self) -> Option<ProcessUsage> {
+        self.system.refresh_processes(ProcessesToUpdate::All, true);
+        let threads = self
+            .system
+            .process(self.root)?
+            .tasks()
+            .map(|tasks| tasks.len());
+        let tree = process_tree(&self.system, self.root);
+        let (rss_bytes, cpu_percent) = tree
+            .iter()
+            .filter_map(|pid| self.system.process(*pid))
+            .fold((0, 0.0), |(rss, cpu), process| {
+                (rss + process.memory(), cpu + process.cpu_usage())
+            });
+        Some(ProcessUsage {
+            rss_bytes,
+            cpu_percent,
+            threads,
+            children: tree.len() - 1,
+        })
+    }
+}
+
+/// Brings the window of process `pid` to the foreground.
+///
+/// Uses `AppActivate` on Windows, System Events on macOS and `xdotool` on
+/// Linux (X11 and XWayland only). Returns `false` when the window could not be
+/// activated.
+pub fn focus_process_window(pid: u32) -> bool {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("powershell");
+        command.args([
+            "-NoProfile",
+            "-Command",
+            &format!("(New-Object -ComObject WScript.Shell).AppActivate({pid})"),
+        ]);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!(
+                "tell application \"System Events\" to set frontmost of \
+                 (first process whose unix id is {pid}) to true"
+            ),
+        ]);
+        command
+    } else {
+        let mut command = Command::new("xdotool");
+        command.args([
+            "search",
+            "--pid",
+            &pid.to_string(),
+            "--onlyvisible",
+            "windowactivate",
+        ]);
+        command
+    };
+    command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    /// Set in the environment of the child process spawned by
+    /// `does_not_count_threads_as_children`.
+    const THREADED_CHILD: &str = "CONIC_THREADED_CHILD";
+
+    #[test]
+    #[ignore = "only runs as the child process of does_not_count_threads_as_children"]
+    fn threaded_child() {
+        if std::env::var_os(THREADED_CHILD).is_none() {
+            return;
+        }
+        let threads: Vec<_> = (0..4)
+            .map(|_| thread::spawn(|| thread::sleep(Duration::from_secs(10))))
+            .collect();
+        for thread in threads {
+            let _ = thread.join();
+        }
+    }
+
+    #[test]
+    fn does_not_count_threads_as_children() {
+        let mut child = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "process::tests::threaded_child", "--ignored"])
+            .env(THREADED_CHILD, "1")
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut sampler = ProcessSampler::new(child.id());
+        let start = Instant::now();
+        let usage = loop {
+            let usage = sampler.sample().unwrap();
+            if usage.threads.is_some_and(|threads| threads > 4)
+                || start.elapsed() > Duration::from_secs(5)
+            {
+                break usage;
+            }
+            thread::sleep(Duration::from_millis(50));
+        };
+        assert!(usage.threads.is_some_and(|threads| threads > 4));
+        assert_eq!(usage.children, 0);
+        assert!(kill_process_tree(child.id()));
+        let _ = child.wait();
+    }
+}
@@ -83,16 +83,43 @@ pub(crate) fn check_os(rule: &Value) -> bool {
     }
 }
 
+/// Check if every feature of the rule has the expected state, a feature is on
+/// when it is in `enabled_features`.
 pub(crate) fn check_features(rule: &Value, enabled_features: &[String]) -> bool {
     if let Some(features) = rule["features"].as_object() {
-        let mut enabled_features_iter = enabled_features.iter();
-        features
-            .iter()
-            .filter(|x| enabled_features_iter.any(|y| x.0 == y) && x.1.as_bool().unwrap_or(false))
-            .collect::<Vec<_>>()
-            .len()
-            == features.len()
+        features.iter().all(|(feature, expected)| {
+            expected.as_bool().unwrap_or(false) == enabled_features.contains(feature)
+        })
     } else {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn checks_every_feature() {
+        let rule = json!({
+            "action": "allow",
+            "features": { "has_quick_plays_support": true, "is_quick_play_singleplayer": true }
+        });
+        let enabled = |features: &[&str]| -> Vec<String> {
+            features.iter().map(|feature| feature.to_string()).collect()
+        };
+        assert!(check_features(
+            &rule,
+            &enabled(&["is_quick_play_singleplayer", "has_quick_plays_support"])
+        ));
+        assert!(!check_features(
+            &rule,
+            &enabled(&["has_quick_plays_support"])
+        ));
+        let rule = json!({ "action": "allow", "features": { "is_demo_user": false } });
+        assert!(check_features(&rule, &[]));
+        assert!(!check_allowed(vec![rule], &enabled(&["is_demo_user"])));
+    }
+}
@@ -33,4 +33,7 @@ pub enum Error {
 
     #[error("Invalid Minecraft version")]
     InvalidMinecraftVersion,
+
+    #[error("Invalid version patch: {0}")]
+    InvalidPatch(String),
 }
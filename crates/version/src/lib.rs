@@ -4,7 +4,7 @@
 
 use once_cell::sync::Lazy;
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::{collections::HashMap, fs::read_to_string, path::PathBuf};
 
 use folder::MinecraftLocation;
@@ -14,11 +14,13 @@ mod checks;
 mod error;
 mod library;
 mod model;
+mod patch;
 
 pub use crate::model::*;
 use argument::*;
 pub use error::*;
 pub use library::*;
+pub use patch::*;
 
 static DEFAULT_GAME_ARGS: Lazy<Vec<String>> = Lazy::new(|| {
     vec![
@@ -44,32 +46,43 @@ static DEFAULT_GAME_ARGS: Lazy<Vec<String>> = Lazy::new(|| {
         "${user_type}".to_string(),
         "--versionType".to_string(),
         "${version_type}".to_string(),
-        "--width".to_string(),
-        "${resolution_width}".to_string(),
-        "--height".to_string(),
-        "${resolution_height}".to_string(),
+    ]
+});
+
+/// The rule-gated game arguments of modern version JSONs, appended to the
+/// `minecraftArguments` of legacy versions.
+static LEGACY_FEATURE_ARGS: Lazy<Vec<Value>> = Lazy::new(|| {
+    vec![
+        json!({
+            "rules": [{ "action": "allow", "features": { "is_demo_user": true } }],
+            "value": "--demo"
+        }),
+        json!({
+            "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }],
+            "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]
+        }),
     ]
 });
 
 static DEFAULT_JVM_ARGS: Lazy<Vec<String>> = Lazy::new(|| {
     vec![
-        "\"-Djava.library.path=${natives_directory}\"".to_string(),
-        "\"-Djna.tmpdir=${natives_directory}\"".to_string(),
-        "\"-Dorg.lwjgl.system.SharedLibraryExtractPath=${natives_directory}\"".to_string(),
-        "\"-Dio.netty.native.workdir=${natives_directory}\"".to_string(),
-        "\"-Dminecraft.launcher.brand=${launcher_name}\"".to_string(),
-        "\"-Dminecraft.launcher.version=${launcher_version}\"".to_string(),
-        "\"-Dfile.encoding=UTF-8\"".to_string(),
-        "\"-Dsun.stdout.encoding=UTF-8\"".to_string(),
-        "\"-Dsun.stderr.encoding=UTF-8\"".to_string(),
-        "\"-Djava.rmi.server.useCodebaseOnly=true\"".to_string(),
-        "\"-XX:MaxInlineSize=420\"".to_string(),
-        "\"-XX:-UseAdaptiveSizePolicy\"".to_string(),
-        "\"-XX:-OmitStackTraceInFastThrow\"".to_string(),
-        "\"-XX:-DontCompileHugeMethods\"".to_string(),
-        "\"-Dcom.sun.jndi.rmi.object.trustURLCodebase=false\"".to_string(),
-        "\"-Dcom.sun.jndi.cosnaming.object.trustURLCodebase=false\"".to_string(),
-        "\"-Dlog4j2.formatMsgNoLookups=true\"".to_string(),
+        "-Djava.library.path=${natives_directory}".to_string(),
+        "-Djna.tmpdir=${natives_directory}".to_string(),
+        "-Dorg.lwjgl.system.SharedLibraryExtractPath=${natives_directory}".to_string(),
+        "-Dio.netty.native.workdir=${natives_directory}".to_string(),
+        "-Dminecraft.launcher.brand=${launcher_name}".to_string(),
+        "-Dminecraft.launcher.version=${launcher_version}".to_string(),
+        "-Dfile.encoding=UTF-8".to_string(),
+        "-Dsun.stdout.encoding=UTF-8".to_string(),
+        "-Dsun.stderr.encoding=UTF-8".to_string(),
+        "-Djava.rmi.server.useCodebaseOnly=true".to_string(),
+        "-XX:MaxInlineSize=420".to_string(),
+        "-XX:-UseAdaptiveSizePolicy".to_string(),
+        "-XX:-OmitStackTraceInFastThrow".to_string(),
+        "-XX:-DontCompileHugeMethods".to_string(),
+        "-Dcom.sun.jndi.rmi.object.trustURLCodebase=false".to_string(),
+        "-Dcom.sun.jndi.cosnaming.object.trustURLCodebase=false".to_string(),
+        "-Dlog4j2.formatMsgNoLookups=true".to_string(),
         "-cp".to_string(),
         "${classpath}".to_string(),
     ]
@@ -130,10 +143,18 @@ impl ResolvedVersion {
     fn join_game_arguments(
         &mut self,
         arguments: Option<Arguments>,
+        minecraft_arguments: Option<String>,
         enabled_features: &[String],
     ) -> &mut Self {
         if self.minimum_launcher_version < 21 {
-            self.game_arguments = DEFAULT_GAME_ARGS.clone();
+            if let Some(minecraft_arguments) = minecraft_arguments {
+                self.game_arguments = minecraft_arguments
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect();
+            } else if self.game_arguments.is_empty() {
+                self.game_arguments = DEFAULT_GAME_ARGS.clone();
+            }
             return self;
         }
         if let Some(arguments) = arguments
@@ -224,11 +245,24 @@ impl ResolvedVersion {
 
 /// parse a Minecraft version json
 ///
-/// If you are not use this to launch the game, you can set `enabled_features` to `&vec![]`
+/// `enabled_features` turns on the rule-gated arguments, such as
+/// `has_custom_resolution` or `is_demo_user`. If you are not use this to launch
+/// the game, you can set it to `&[]`.
 pub async fn resolve_version(
     version: &Version,
     minecraft: &MinecraftLocation,
     enabled_features: &[String],
+) -> Result<ResolvedVersion> {
+    resolve_version_with_patches(version, minecraft, enabled_features, &[]).await
+}
+
+/// Same as [`resolve_version`], but merges `patches` in order on top of the
+/// resolved inheritance chain, see [`VersionPatch`].
+pub async fn resolve_version_with_patches(
+    version: &Version,
+    minecraft: &MinecraftLocation,
+    enabled_features: &[String],
+    patches: &[VersionPatch],
 ) -> Result<ResolvedVersion> {
     let mut inherits_from = version.inherits_from.clone();
     let versions_folder = &minecraft.versions;
@@ -265,9 +299,21 @@ pub async fn resolve_version(
             .join_asset_index(version.asset_index)
             .join_downloads(version.downloads)
             .join_jvm_arguments(&version.arguments, enabled_features)
-            .join_game_arguments(version.arguments, enabled_features)
+            .join_game_arguments(
+                version.arguments,
+                version.minecraft_arguments,
+                enabled_features,
+            )
             .join_libraries(version.libraries)?;
     }
+    if resolved_version.minimum_launcher_version < 21 {
+        resolved_version
+            .game_arguments
+            .extend(resolve_arguments(&LEGACY_FEATURE_ARGS, enabled_features));
+    }
+    for patch in patches {
+        patch.apply(&mut resolved_version, enabled_features)?;
+    }
     if resolved_version.main_class.is_none()
         || resolved_version.asset_index.is_none()
         || resolved_version.downloads.is_empty()
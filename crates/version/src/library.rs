@@ -20,13 +20,15 @@ pub fn resolve_libraries(libraries: Vec<Value>) -> Result<Vec<ResolvedLibrary>>
         {
             continue;
         }
-        if let Some(native_library) = resolve_native_libraries(&library) {
-            result.push(native_library);
+        let name = library["name"].as_str().map(|name| name.to_string());
+        let resolved_library = if let Some(native_library) = resolve_native_libraries(&library) {
+            native_library
         } else if let Some(common_library) = resolve_common_libraries(&library)? {
-            result.push(common_library);
+            common_library
         } else {
-            result.push(resolve_modloader_libraries(&library)?);
-        }
+            resolve_modloader_libraries(&library)?
+        };
+        result.push(resolved_library.with_name(name));
     }
     Ok(result)
 }
@@ -52,6 +54,7 @@ fn resolve_native_libraries(library: &Value) -> Option<ResolvedLibrary> {
             size: classifier.get("size").and_then(|v| v.as_u64()),
             url: url.to_string(),
             path: path.to_string(),
+            name: None,
         }))
     } else {
         None
@@ -98,6 +101,7 @@ fn resolve_modloader_libraries(library: &Value) -> Result<ResolvedLibrary> {
         size: None,
         url: format!("{url}{path}"),
         path,
+        name: None,
     }))
 }
 
@@ -113,12 +117,42 @@ pub enum ResolvedLibrary {
     Common(LibraryDownloadInfo),
 }
 
+impl ResolvedLibrary {
+    pub fn download_info(&self) -> &LibraryDownloadInfo {
+        match self {
+            ResolvedLibrary::Native(download_info) => download_info,
+            ResolvedLibrary::Common(download_info) => download_info,
+        }
+    }
+
+    fn with_name(self, name: Option<String>) -> Self {
+        match self {
+            ResolvedLibrary::Native(download_info) => {
+                ResolvedLibrary::Native(LibraryDownloadInfo {
+                    name,
+                    ..download_info
+                })
+            }
+            ResolvedLibrary::Common(download_info) => {
+                ResolvedLibrary::Common(LibraryDownloadInfo {
+                    name,
+                    ..download_info
+                })
+            }
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct LibraryDownloadInfo {
     pub sha1: Option<String>,
     pub size: Option<u64>,
     pub url: String,
     pub path: String,
+
+    /// Maven coordinate of the library (`group:artifact:version[:classifier]`),
+    /// taken from the `name` field of the version JSON.
+    pub name: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -16,7 +16,7 @@ pub struct Arguments {
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AssetIndex {
-    // pub sha1: String,
+    pub sha1: Option<String>,
     pub size: u64,
     pub url: String,
     pub id: String,
@@ -104,6 +104,45 @@ pub struct AssetIndexObjectInfo {
 
 pub type AssetIndexObject = HashMap<String, AssetIndexObjectInfo>;
 
+/// Content of an asset index file (`assets/indexes/<id>.json`).
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AssetIndexContent {
+    pub objects: AssetIndexObject,
+
+    /// Set by the `legacy` index (1.6 - 1.7.2): objects are read by name from
+    /// `assets/virtual/<id>`.
+    #[serde(rename = "virtual", default)]
+    pub is_virtual: bool,
+
+    /// Set by the `pre-1.6` index: objects are read by name from the
+    /// `resources` folder of the game directory.
+    #[serde(default)]
+    pub map_to_resources: bool,
+}
+
+/// Where the game expects its asset files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetLayout {
+    /// Hashed objects under `assets/objects`.
+    Objects,
+    /// Named files under `assets/virtual/<id>`.
+    Virtual,
+    /// Named files under `<game directory>/resources`.
+    MapToResources,
+}
+
+impl AssetIndexContent {
+    pub fn layout(&self) -> AssetLayout {
+        if self.map_to_resources {
+            AssetLayout::MapToResources
+        } else if self.is_virtual {
+            AssetLayout::Virtual
+        } else {
+            AssetLayout::Objects
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct LoggingFile {
     pub size: u64,
@@ -178,3 +217,26 @@ fn parse_version(raw: &str) -> Result<MinecraftVersion> {
         Ok(MinecraftVersion::Unknown(raw.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_legacy_asset_layouts() {
+        let layout = |raw: &str| {
+            serde_json::from_str::<AssetIndexContent>(raw)
+                .unwrap()
+                .layout()
+        };
+        assert_eq!(layout(r#"{"objects":{}}"#), AssetLayout::Objects);
+        assert_eq!(
+            layout(r#"{"virtual":true,"objects":{}}"#),
+            AssetLayout::Virtual
+        );
+        assert_eq!(
+            layout(r#"{"map_to_resources":true,"objects":{}}"#),
+            AssetLayout::MapToResources
+        );
+    }
+}
@@ -0,0 +1,388 @@
+// Conic Launcher
+// Copyright 2022-2026 ConicMC developers. All rights reserved.
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Per-instance component patches.
+//!
+//! A patch is an overlay JSON document, similar to a MultiMC component patch,
+//! merged on top of the resolved inheritance chain. Patches live in the
+//! `patches` folder of an instance, so reinstalling the game (which rewrites the
+//! version JSONs) keeps them.
+//!
+//! ```json
+//! {
+//!   "uid": "org.lwjgl3",
+//!   "name": "Custom LWJGL",
+//!   "order": 10,
+//!   "+libraries": [{ "name": "org.lwjgl:lwjgl:3.3.4", "downloads": { "artifact": { ... } } }],
+//!   "-libraries": ["org.lwjgl:lwjgl-openal"],
+//!   "+arguments": { "jvm": ["-javaagent:/path/to/agent.jar"] },
+//!   "-arguments": { "game": ["--demo"] }
+//! }
+//! ```
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    Arguments, AssetIndex, JavaVersion, ResolvedLibrary, ResolvedVersion, argument::*, error::*,
+    resolve_libraries,
+};
+
+/// An overlay document merged on top of a resolved version.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionPatch {
+    /// Unique identifier of the patch, e.g. `org.lwjgl3`.
+    pub uid: String,
+
+    /// Display name of the patch.
+    pub name: Option<String>,
+
+    /// Patches are applied in ascending order, ties are broken by `uid`.
+    #[serde(default)]
+    pub order: i32,
+
+    pub main_class: Option<String>,
+    pub asset_index: Option<AssetIndex>,
+    pub assets: Option<String>,
+    pub java_version: Option<JavaVersion>,
+
+    /// Replaces every library of the resolved version.
+    pub libraries: Option<Vec<Value>>,
+
+    /// Libraries to add. A library with the same group, artifact and classifier
+    /// as an existing one replaces it.
+    #[serde(rename = "+libraries", default)]
+    pub add_libraries: Vec<Value>,
+
+    /// Maven coordinates of libraries to remove, e.g. `group:artifact`.
+    ///
+    /// The version and classifier parts are optional, `*` matches any version.
+    #[serde(rename = "-libraries", default)]
+    pub remove_libraries: Vec<String>,
+
+    /// Replaces the game and/or JVM arguments of the resolved version.
+    pub arguments: Option<Arguments>,
+
+    /// Arguments appended to the resolved version.
+    #[serde(rename = "+arguments")]
+    pub add_arguments: Option<Arguments>,
+
+    /// Arguments removed from the resolved version, compared literally.
+    ///
+    /// A removed flag also removes its value, the following argument when it
+    /// does not start with `-`, e.g. `--tweakClass` removes every
+    /// `--tweakClass optifine.OptiFineTweaker`. A flag and its value separated
+    /// by a space, e.g. `--tweakClass optifine.OptiFineTweaker`, only removes
+    /// that pair.
+    #[serde(rename = "-arguments")]
+    pub remove_arguments: Option<RemovedArguments>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct RemovedArguments {
+    pub game: Vec<String>,
+    pub jvm: Vec<String>,
+}
+
+impl VersionPatch {
+    /// Merges this patch into `version`.
+    ///
+    /// Rule-gated arguments in the patch are evaluated with `enabled_features`,
+    /// the same way as the arguments of the version JSON.
+    pub fn apply(&self, version: &mut ResolvedVersion, enabled_features: &[String]) -> Result<()> {
+        if self.main_class.is_some() {
+            version.main_class = self.main_class.clone();
+        }
+        if self.asset_index.is_some() {
+            version.asset_index = self.asset_index.clone();
+        }
+        if self.assets.is_some() {
+            version.assets = self.assets.clone();
+        }
+        if let Some(java_version) = &self.java_version {
+            version.java_version = java_version.clone();
+        }
+        if let Some(libraries) = &self.libraries {
+            version.libraries = resolve_libraries(libraries.clone())?;
+        }
+        if !self.remove_libraries.is_empty() {
+            version.libraries.retain(|library| {
+                !self
+                    .remove_libraries
+                    .iter()
+                    .any(|coordinate| matches_coordinate(library, coordinate))
+            });
+        }
+        if !self.add_libraries.is_empty() {
+            let added = resolve_libraries(self.add_libraries.clone())?;
+            version
+                .libraries
+                .retain(|library| !added.iter().any(|new| is_same_library(library, new)));
+            version.libraries.splice(0..0, added);
+        }
+        if let Some(arguments) = &self.arguments {
+            if let Some(game) = &arguments.game {
+                version.game_arguments = resolve_arguments(game, enabled_features);
+            }
+            if let Some(jvm) = &arguments.jvm {
+                version.jvm_arguments = resolve_arguments(jvm, enabled_features);
+            }
+        }
+        if let Some(arguments) = &self.add_arguments {
+            if let Some(game) = &arguments.game {
+                version
+                    .game_arguments
+                    .extend(resolve_arguments(game, enabled_features));
+            }
+            if let Some(jvm) = &arguments.jvm {
+                version
+                    .jvm_arguments
+                    .extend(resolve_arguments(jvm, enabled_features));
+            }
+        }
+        if let Some(arguments) = &self.remove_arguments {
+            remove_arguments(&mut version.game_arguments, &arguments.game);
+            remove_arguments(&mut version.jvm_arguments, &arguments.jvm);
+        }
+        Ok(())
+    }
+}
+
+/// Removes `removed` from `arguments`, along with the value following each
+/// removed flag, and the flag and value pairs of `removed`.
+fn remove_arguments(arguments: &mut Vec<String>, removed: &[String]) {
+    let mut iter = std::mem::take(arguments).into_iter().peekable();
+    while let Some(argument) = iter.next() {
+        if removed.contains(&argument) {
+            if argument.starts_with('-') {
+                iter.next_if(|value| !value.starts_with('-'));
+            }
+            continue;
+        }
+        let next = iter.peek();
+        let is_pair = removed.iter().any(|entry| {
+            entry.split_once(' ').is_some_and(|(flag, value)| {
+                flag.starts_with('-') && flag == argument && next.is_some_and(|next| next == value)
+            })
+        });
+        if is_pair {
+            iter.next();
+        } else {
+            arguments.push(argument);
+        }
+    }
+}
+
+/// Reads every `*.json` patch in `folder`, sorted in application order.
+///
+/// A missing folder means the instance has no patches.
+pub fn load_patches<P: AsRef<Path>>(folder: P) -> Result<Vec<VersionPatch>> {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => return Err(error.into()),
+    };
+    let mut patches = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let patch = serde_json::from_str::<VersionPatch>(&content)
+            .map_err(|error| Error::InvalidPatch(format!("{}: {error}", path.display())))?;
+        patches.push(patch);
+    }
+    patches.sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.uid.cmp(&b.uid)));
+    Ok(patches)
+}
+
+/// Splits a maven coordinate into `(group, artifact, version, classifier)`.
+fn split_coordinate(coordinate: &str) -> Option<(&str, &str, Option<&str>, Option<&str>)> {
+    let coordinate = coordinate.split('@').next().unwrap_or(coordinate);
+    let mut parts = coordinate.split(':');
+    let group = parts.next()?;
+    let artifact = parts.next()?;
+    Some((group, artifact, parts.next(), parts.next()))
+}
+
+fn matches_coordinate(library: &ResolvedLibrary, coordinate: &str) -> bool {
+    let Some(name) = &library.download_info().name else {
+        return false;
+    };
+    let (Some(library), Some(pattern)) = (split_coordinate(name), split_coordinate(coordinate))
+    else {
+        return false;
+    };
+    let version_matches = match pattern.2 {
+        None | Some("*") => true,
+        Some(version) => library.2 == Some(version),
+    };
+    let classifier_matches = match pattern.3 {
+        None => true,
+        Some(classifier) => library.3 == Some(classifier),
+    };
+    library.0 == pattern.0 && library.1 == pattern.1 && version_matches && classifier_matches
+}
+
+fn is_same_library(a: &ResolvedLibrary, b: &ResolvedLibrary) -> bool {
+    let same_kind = matches!(
+        (a, b),
+        (ResolvedLibrary::Native(_), ResolvedLibrary::Native(_))
+            | (ResolvedLibrary::Common(_), ResolvedLibrary::Common(_))
+    );
+    let (Some(a), Some(b)) = (&a.download_info().name, &b.download_info().name) else {
+        return false;
+    };
+    match (split_coordinate(a), split_coordinate(b)) {
+        (Some(a), Some(b)) => same_kind && a.0 == b.0 && a.1 == b.1 && a.3 == b.3,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn library(name: &str) -> Value {
+        let path = format!("{}.jar", name.replace(':', "/"));
+        json!({
+            "name": name,
+            "downloads": {
+                "artifact": {
+                    "path": path,
+                    "url": format!("https://libraries.minecraft.net/{path}"),
+                    "sha1": null,
+                    "size": null
+                }
+            }
+        })
+    }
+
+    fn version() -> ResolvedVersion {
+        ResolvedVersion {
+            libraries: resolve_libraries(vec![
+                library("org.lwjgl:lwjgl:3.3.1"),
+                library("org.lwjgl:lwjgl-openal:3.3.1"),
+                library("com.mojang:brigadier:1.0.18"),
+            ])
+            .unwrap(),
+            main_class: Some("net.minecraft.client.main.Main".to_string()),
+            game_arguments: vec!["--demo".to_string(), "--width".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn library_names(version: &ResolvedVersion) -> Vec<String> {
+        version
+            .libraries
+            .iter()
+            .filter_map(|library| library.download_info().name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn replaces_and_removes_libraries() {
+        let patch: VersionPatch = serde_json::from_value(json!({
+            "uid": "org.lwjgl3",
+            "+libraries": [library("org.lwjgl:lwjgl:3.3.4")],
+            "-libraries": ["org.lwjgl:lwjgl-openal"]
+        }))
+        .unwrap();
+        let mut version = version();
+        patch.apply(&mut version, &[]).unwrap();
+        assert_eq!(
+            library_names(&version),
+            vec!["org.lwjgl:lwjgl:3.3.4", "com.mojang:brigadier:1.0.18"]
+        );
+    }
+
+    #[test]
+    fn removal_respects_version() {
+        let patch: VersionPatch = serde_json::from_value(json!({
+            "uid": "test",
+            "-libraries": ["org.lwjgl:lwjgl:3.2.2", "com.mojang:brigadier:*"]
+        }))
+        .unwrap();
+        let mut version = version();
+        patch.apply(&mut version, &[]).unwrap();
+        assert_eq!(
+            library_names(&version),
+            vec!["org.lwjgl:lwjgl:3.3.1", "org.lwjgl:lwjgl-openal:3.3.1"]
+        );
+    }
+
+    #[test]
+    fn overrides_main_class_and_arguments() {
+        let patch: VersionPatch = serde_json::from_value(json!({
+            "uid": "test",
+            "mainClass": "com.example.Main",
+            "+arguments": { "jvm": ["-javaagent:agent.jar"] },
+            "-arguments": { "game": ["--demo"] }
+        }))
+        .unwrap();
+        let mut version = version();
+        patch.apply(&mut version, &[]).unwrap();
+        assert_eq!(version.main_class.as_deref(), Some("com.example.Main"));
+        assert_eq!(version.game_arguments, vec!["--width"]);
+        assert_eq!(version.jvm_arguments, vec!["-javaagent:agent.jar"]);
+    }
+
+    #[test]
+    fn removes_flags_with_their_value() {
+        let patch: VersionPatch = serde_json::from_value(json!({
+            "uid": "test",
+            "-arguments": { "game": ["--tweakClass", "--demo"] }
+        }))
+        .unwrap();
+        let mut version = ResolvedVersion {
+            game_arguments: [
+                "--tweakClass",
+                "optifine.OptiFineTweaker",
+                "--demo",
+                "--width",
+                "854",
+            ]
+            .map(String::from)
+            .to_vec(),
+            ..Default::default()
+        };
+        patch.apply(&mut version, &[]).unwrap();
+        assert_eq!(version.game_arguments, vec!["--width", "854"]);
+    }
+
+    #[test]
+    fn removes_one_flag_and_value_pair() {
+        let patch: VersionPatch = serde_json::from_value(json!({
+            "uid": "test",
+            "-arguments": { "game": ["--tweakClass optifine.OptiFineTweaker"] }
+        }))
+        .unwrap();
+        let mut version = ResolvedVersion {
+            game_arguments: [
+                "--tweakClass",
+                "net.minecraftforge.fml.common.launcher.FMLTweaker",
+                "--tweakClass",
+                "optifine.OptiFineTweaker",
+            ]
+            .map(String::from)
+            .to_vec(),
+            ..Default::default()
+        };
+        patch.apply(&mut version, &[]).unwrap();
+        assert_eq!(
+            version.game_arguments,
+            vec![
+                "--tweakClass",
+                "net.minecraftforge.fml.common.launcher.FMLTweaker"
+            ]
+        );
+    }
+}